@@ -1,18 +1,35 @@
 //! CLI parsing and orchestration. Parses args, runs scrape -> EPUB, JSON, HTML, Markdown, or text. Maps errors to exit codes.
 
 use crate::config;
-use crate::epub::{write_epub, EpubError, EpubVersion};
-use crate::formats::{write_html, write_markdown, write_text, FormatError, OutputFormat};
-use crate::model::Book;
+use crate::epub::{
+    fetch_cover_image, write_epub, write_opds_entry, ChapterFileNames, EpubError, EpubOptions,
+    EpubVersion, DEFAULT_COVER_TIMEOUT_SECS,
+};
+use crate::formats::{
+    markdown_residual_html, write_bundle, write_fb2, write_html, write_index, write_markdown,
+    write_pdf, write_text, BodyOptions, FormatError, OutputFormat,
+};
+use crate::model::{is_language_tag, Book, Chapter, Contributor};
 use crate::scraper::{
-    resolve_site, scrape_book, EmptyChapterBehavior, LockedChapterBehavior, ScrapeOptions,
-    ScraperError, Site,
+    check_blocked_host, drop_nav_artifacts, resolve_site, scrape_book, site_override_mismatch,
+    ChapterAllowlist, ChapterSelection, ChapterSource, EmptyChapterBehavior,
+    IndexCollisionBehavior, LockedChapterBehavior, ProgressCallback, ScrapeOptions, ScraperError,
+    ScribbleHubTocMode, Site, TocCallback, TocEntry, TocSort, UNKNOWN_AUTHOR,
 };
 use crate::PoliteClient;
 use clap::Parser;
-use std::cell::RefCell;
-use std::path::{Path, PathBuf};
-use std::time::Duration;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 /// CLI error carrying exit code and message. Per ERROR_HANDLING.md 2.1.
@@ -32,20 +49,276 @@ pub enum CliRunError {
 
     #[error("{0}")]
     Validation(String),
+
+    /// `--deadline` elapsed; the scrape stopped after the chapter in progress.
+    #[error("Deadline of {secs}s reached; stopped after {done} chapter(s).{hint}")]
+    DeadlineReached {
+        secs: u64,
+        done: u32,
+        /// How to continue (points at the --resume checkpoint when one is kept).
+        hint: String,
+    },
+
+    /// Ctrl-C; the scrape stopped after the chapter in progress.
+    #[error("Interrupted; stopped after {done} chapter(s).{hint}")]
+    Interrupted {
+        done: u32,
+        /// How to continue (points at the --resume checkpoint when one is kept).
+        hint: String,
+    },
 }
 
 impl CliRunError {
     pub fn exit_code(&self) -> i32 {
         match self {
             CliRunError::InvalidInput(_) => 1,
+            CliRunError::Scraper(ScraperError::Cancelled) => 130,
             CliRunError::Scraper(_) => 2,
             CliRunError::Epub(_) | CliRunError::Format(_) | CliRunError::Validation(_) => 3,
+            CliRunError::DeadlineReached { .. } => 4,
+            CliRunError::Interrupted { .. } => 130,
+        }
+    }
+
+    /// Stable snake_case discriminant for `--error-format json`. Wrapped errors report the inner variant.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            CliRunError::InvalidInput(_) => "invalid_input",
+            CliRunError::Scraper(e) => e.kind_name(),
+            CliRunError::Epub(e) => e.kind_name(),
+            CliRunError::Format(e) => e.kind_name(),
+            CliRunError::Validation(_) => "validation",
+            CliRunError::DeadlineReached { .. } => "deadline_reached",
+            CliRunError::Interrupted { .. } => "interrupted",
+        }
+    }
+
+    /// URL the error refers to, if any (scraper errors only).
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            CliRunError::Scraper(e) => e.url(),
+            _ => None,
+        }
+    }
+
+    /// Machine-readable form: `{code, kind, message, url?}`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut obj = serde_json::json!({
+            "code": self.exit_code(),
+            "kind": self.kind_name(),
+            "message": self.to_string(),
+        });
+        if let Some(url) = self.url() {
+            obj["url"] = serde_json::Value::String(url.to_string());
+        }
+        obj
+    }
+}
+
+/// Output syntax for `--print-config`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+/// Settings after merging CLI flags, the config file, and built-in defaults, as printed by
+/// `--print-config`. Field names match the config file keys where one exists.
+#[derive(Debug, Serialize)]
+struct EffectiveConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    site: Option<&'static str>,
+    format: &'static str,
+    output_dir: PathBuf,
+    request_delay_secs: u64,
+    timeout_secs: u64,
+    retry_count: u32,
+    retry_backoff_secs: Vec<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_total_backoff_secs: Option<u64>,
+    chapter_parse_retries: u32,
+    user_agent: String,
+    allowed_block_tags: Vec<String>,
+    allowed_inline_tags: Vec<String>,
+}
+
+impl EffectiveConfig {
+    fn render(&self, format: ConfigFormat) -> Result<String, CliRunError> {
+        let rendered = match format {
+            ConfigFormat::Toml => toml::to_string(self).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::to_string_pretty(self).map_err(|e| e.to_string()),
+        };
+        rendered.map_err(|e| CliRunError::InvalidInput(format!("Failed to print config: {}", e)))
+    }
+}
+
+/// `--dry-run --format json` output: what a real run would fetch and write, plus the settings it
+/// would use.
+#[derive(Debug, Serialize)]
+struct DryRunPlan {
+    site: &'static str,
+    url: String,
+    title: String,
+    /// Chapters a real run would fetch (after `--chapters` and other filters).
+    chapters: usize,
+    /// Locked (premium) chapters in the story's TOC.
+    locked_chapters: usize,
+    outputs: Vec<PlannedOutput>,
+    settings: EffectiveConfig,
+}
+
+/// One file a real run would write and how many chapters go into it.
+#[derive(Debug, Serialize)]
+struct PlannedOutput {
+    path: PathBuf,
+    chapters: usize,
+}
+
+/// One `--list-chapters --format json` entry.
+#[derive(Debug, Serialize)]
+struct ListedChapter {
+    index: u32,
+    title: String,
+    /// Royal Road premium chapter the account cannot read.
+    locked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+}
+
+/// The chapters of a TOC-only `book` for `--list-chapters`. Locked placeholders lose the
+/// ` (locked)` title suffix the scraper gave them; `locked` carries it instead.
+fn listed_chapters(book: &Book, locked: &HashSet<u32>) -> Vec<ListedChapter> {
+    book.chapters
+        .iter()
+        .map(|ch| {
+            let is_locked = locked.contains(&ch.index);
+            let title = match ch.title.strip_suffix(" (locked)") {
+                Some(t) if is_locked => t.to_string(),
+                _ => ch.title.clone(),
+            };
+            ListedChapter {
+                index: ch.index,
+                title,
+                locked: is_locked,
+                url: ch.url.clone(),
+            }
+        })
+        .collect()
+}
+
+/// `--list-chapters` text output: one `index<TAB>title` line per chapter, ` (locked)` after a
+/// locked chapter's title. Tabs and line breaks in titles become spaces.
+fn chapter_list_text(chapters: &[ListedChapter]) -> String {
+    chapters
+        .iter()
+        .map(|ch| {
+            format!(
+                "{}\t{}{}\n",
+                ch.index,
+                ch.title.replace(['\t', '\r', '\n'], " "),
+                if ch.locked { " (locked)" } else { "" }
+            )
+        })
+        .collect()
+}
+
+/// How errors are printed to stderr on failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Plain message (default); `--verbose` adds the cause chain.
+    Human,
+    /// One JSON object: `{code, kind, message, url?}`.
+    Json,
+}
+
+/// How scrape progress is reported on stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// Animated progress bar (default).
+    Bar,
+    /// One plain line per chapter: `Chapter n/total: title`.
+    Plain,
+    /// One JSON object per line: `{"done":n,"total":t,"title":...}`.
+    Json,
+    /// No progress output.
+    None,
+}
+
+/// Progress mode after `--quiet` and terminal detection: the animated bar needs a terminal on
+/// stderr, so redirected or CI output gets plain lines instead.
+fn effective_progress_mode(requested: ProgressMode, quiet: bool, stderr_tty: bool) -> ProgressMode {
+    match requested {
+        _ if quiet => ProgressMode::None,
+        ProgressMode::Bar if !stderr_tty => ProgressMode::Plain,
+        mode => mode,
+    }
+}
+
+/// indicatif template for the `--progress bar` line.
+const PROGRESS_TEMPLATE: &str = "{spinner} {msg} [{bar:40}] {pos}/{len} ({elapsed})";
+
+/// Progress bar style from `template`. An invalid template is reported once as a warning and
+/// replaced by indicatif's plain default bar instead of panicking.
+fn progress_style(
+    template: &str,
+    tick_chars: &str,
+    progress_chars: &str,
+) -> indicatif::ProgressStyle {
+    indicatif::ProgressStyle::default_bar()
+        .template(template)
+        .unwrap_or_else(|e| {
+            eprintln!(
+                "Warning: invalid progress template {:?} ({}); using a plain bar.",
+                template, e
+            );
+            indicatif::ProgressStyle::default_bar()
+        })
+        .tick_chars(tick_chars)
+        .progress_chars(progress_chars)
+}
+
+/// Spinner and bar characters: Unicode by default, ASCII with `--no-color` / `NO_COLOR`.
+fn bar_chars(no_color: bool) -> (&'static str, &'static str) {
+    if no_color {
+        ("-\\|/ ", "=> ")
+    } else {
+        ("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏", "█▉▊▋▌▍▎▏ ")
+    }
+}
+
+/// One progress line for `--progress plain` or `json`; None for modes that do not print lines.
+fn progress_line(mode: ProgressMode, done: u32, total: u32, title: &str) -> Option<String> {
+    match mode {
+        ProgressMode::Plain => Some(format!("Chapter {}/{}: {}", done, total, title)),
+        ProgressMode::Json => {
+            Some(serde_json::json!({ "done": done, "total": total, "title": title }).to_string())
         }
+        ProgressMode::Bar | ProgressMode::None => None,
     }
 }
 
+/// Fail when written Markdown still contains HTML tags that the conversion could not turn into
+/// Markdown (see [markdown_residual_html]), listing each tag with its chapter.
+fn validate_markdown(path: &Path) -> Result<(), CliRunError> {
+    let markdown = std::fs::read_to_string(path).map_err(|e| {
+        CliRunError::Validation(format!("Could not read {}: {}", path.display(), e))
+    })?;
+    let residual = markdown_residual_html(&markdown);
+    if residual.is_empty() {
+        return Ok(());
+    }
+    let lines: Vec<String> = residual
+        .iter()
+        .map(|(section, tag)| format!("  {}: <{}>", section, tag))
+        .collect();
+    Err(CliRunError::Validation(format!(
+        "Markdown still contains HTML tags that were not converted:\n{}",
+        lines.join("\n")
+    )))
+}
+
 /// Run epubcheck on the given EPUB path. Requires epubcheck on PATH.
-fn validate_epub(path: &PathBuf) -> Result<(), CliRunError> {
+fn validate_epub(path: &Path) -> Result<(), CliRunError> {
     let output = std::process::Command::new("epubcheck")
         .arg(path)
         .output()
@@ -72,21 +345,42 @@ fn validate_epub(path: &PathBuf) -> Result<(), CliRunError> {
 #[command(name = "rdrscrape")]
 #[command(about = "Scrape Royal Road or Scribble Hub fiction and write EPUB")]
 #[command(
-    after_help = "Config file keys (output_dir, user_agent, request_delay_secs, timeout_secs, toc_page, retry_count, retry_backoff_secs, empty_chapters) are documented in the README. CLI flags override config."
+    after_help = "Config file keys (output_dir, user_agent, request_delay_secs, timeout_secs, cover_timeout_secs, toc_page, retry_count, retry_backoff_secs, empty_chapters, index_collision, strict_extension, blocked_hosts, max_total_backoff_secs, allowed_block_tags, allowed_inline_tags, scribblehub_toc_mode, rights, language, chapter_parse_retries, extra_hosts, royalroad_hidden_classes) are documented in the README. CLI flags override config."
 )]
 pub struct Args {
-    /// Story or series URL (Royal Road fiction page or Scribble Hub series page).
-    pub url: String,
+    /// Story or series URL (Royal Road fiction page or Scribble Hub series page), or a chapter URL with --single-chapter. Not used with --from-json.
+    #[arg(required_unless_present = "from_json")]
+    pub url: Option<String>,
+
+    /// Write a Book JSON file saved earlier with --format json (or a --resume checkpoint) in --format, without scraping. Only the cover and chapter images are fetched.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["url", "resume", "dry_run"])]
+    pub from_json: Option<PathBuf>,
 
     /// Output path. Default: ./{sanitized-title}.{ext} where ext depends on --format.
     #[arg(short, long)]
     pub output: Option<PathBuf>,
 
-    /// Output format: epub, json, html, markdown, or text.
+    /// Create missing parent directories of the output, --index, --opds-entry and --bundle paths instead of failing (e.g. -o out/by-author/book.epub).
+    #[arg(long)]
+    pub mkdirs: bool,
+
+    /// Also package the output file and book.json (canonical JSON with contentHash) into this zip.
+    #[arg(long)]
+    pub bundle: Option<PathBuf>,
+
+    /// Also write a chapter index as UTF-8 TSV (index, title, url, word_count, with a header row).
+    #[arg(long)]
+    pub index: Option<PathBuf>,
+
+    /// Also write an OPDS 1.2 Atom <entry> (title, author, summary, link to the EPUB) for a personal OPDS catalog server. Requires --format epub.
+    #[arg(long)]
+    pub opds_entry: Option<PathBuf>,
+
+    /// Output format: epub, json, html, markdown, text, pdf, or fb2.
     #[arg(long, default_value = "epub", value_parser = parse_format)]
     pub format: OutputFormat,
 
-    /// Override site detection (royalroad or scribblehub).
+    /// Override site detection (royalroad, scribblehub or ao3).
     #[arg(long, value_parser = parse_site)]
     pub site: Option<Site>,
 
@@ -98,93 +392,408 @@ pub struct Args {
     #[arg(short, long)]
     pub quiet: bool,
 
+    /// Progress output on stderr: bar (default), plain (one line per chapter), json (one JSON object per chapter), or none. --quiet implies none.
+    #[arg(long, default_value = "bar", value_parser = parse_progress_mode)]
+    pub progress: ProgressMode,
+
+    /// Draw the progress bar with plain ASCII characters. Also set by a non-empty NO_COLOR environment variable. When stderr is not a terminal, bar progress falls back to plain lines regardless.
+    #[arg(long)]
+    pub no_color: bool,
+
     /// Print verbose error chain.
     #[arg(long)]
     pub verbose: bool,
 
+    /// Print the effective settings (after merging flags, config file, and defaults) as toml or json to stdout and exit without scraping.
+    #[arg(long, value_parser = parse_config_format)]
+    pub print_config: Option<ConfigFormat>,
+
+    /// Error output on failure: human (default) or json (one object with code, kind, message, url on stderr).
+    #[arg(long, default_value = "human", value_parser = parse_error_format)]
+    pub error_format: ErrorFormat,
+
     /// Include toc.ncx in EPUB 3 output for legacy readers (no effect for EPUB 2, which always includes NCX).
     #[arg(long)]
     pub ncx: bool,
 
-    /// Scrape only chapters in this range (1-based inclusive), e.g. 1-10 or 5-20.
-    #[arg(long, value_parser = parse_chapter_range)]
-    pub chapters: Option<(u32, u32)>,
+    /// Use this book description instead of the scraped one. An empty string clears it.
+    #[arg(long, conflicts_with = "no_description")]
+    pub description: Option<String>,
+
+    /// Omit the book description from the output (no dc:description in EPUB).
+    #[arg(long)]
+    pub no_description: bool,
+
+    /// Use "Title — Author" as the book title: in the default output filename and the embedded title (EPUB dc:title, HTML/Markdown/text heading). The --resume checkpoint keeps the scraped title.
+    #[arg(long)]
+    pub title_with_author: bool,
+
+    /// EPUB dc:language as a BCP 47 tag, e.g. fr or pt-BR (overrides config language; default en).
+    #[arg(long, value_parser = parse_language)]
+    pub language: Option<String>,
+
+    /// Series name for the EPUB (EPUB 3 belongs-to-collection, EPUB 2 calibre:series).
+    #[arg(long)]
+    pub series: Option<String>,
+
+    /// Extra credit as ROLE:NAME with a MARC relator role, e.g. trl:Jane Doe for a translator (repeatable; EPUB dc:contributor).
+    #[arg(long = "contributor", value_name = "ROLE:NAME", value_parser = parse_contributor)]
+    pub contributors: Vec<Contributor>,
+
+    /// Position in --series, e.g. 2 or 2.5 (EPUB 3 group-position, EPUB 2 calibre:series_index).
+    #[arg(long, requires = "series", value_parser = parse_series_index)]
+    pub series_index: Option<f32>,
+
+    /// Heading for the EPUB table of contents (nav and TOC page). Default: "Table of Contents".
+    #[arg(long)]
+    pub toc_title: Option<String>,
+
+    /// Add Previous/Next links between chapters (EPUB chapter files and single-file HTML).
+    #[arg(long)]
+    pub nav_links: bool,
+
+    /// Insert an EPUB "Description" page after the cover with the story description as paragraphs. The description metadata (<dc:description>) is written either way.
+    #[arg(long)]
+    pub description_page: bool,
+
+    /// Append an EPUB "About" page after the last chapter: source URL, scrape date, rdrscrape version, chapter count.
+    #[arg(long)]
+    pub about_page: bool,
+
+    /// EPUB: use this CSS file as style.css, linked from every page, instead of the built-in stylesheet.
+    #[arg(long)]
+    pub css: Option<PathBuf>,
+
+    /// EPUB: <dc:rights> statement (overrides config rights). Default "© <author>; see <source URL>"; pass "" to omit.
+    #[arg(long)]
+    pub rights: Option<String>,
+
+    /// EPUB: chapter titles in the nav, NCX, and chapter headings, e.g. "{num}. {title}". Placeholders: {num} (chapter index), {title}, {volume} (empty for now). Stored titles and JSON output are unchanged.
+    #[arg(long)]
+    pub chapter_title_template: Option<String>,
+
+    /// EPUB: use a short "Chapter N" as each chapter page's <title> element (shown by some readers in their header bar); nav, NCX, and the TOC page keep the full title.
+    #[arg(long)]
+    pub short_title_element: bool,
 
-    /// Resume from a partial scrape saved at this path (JSON). Load existing chapters and fetch only missing ones; save progress after each chapter.
+    /// EPUB: leave out the cover entirely; the cover image is not requested and no cover page is written.
     #[arg(long)]
+    pub no_cover: bool,
+
+    /// EPUB chapter file names: position (chapter-1.xhtml, ...) or index (chapter-0050.xhtml, from each chapter's TOC index).
+    #[arg(long, default_value = "position", value_parser = parse_chapter_file_names)]
+    pub chapter_filenames: ChapterFileNames,
+
+    /// Scrape only these chapters (1-based, inclusive): ranges like 1-10, open ranges 5- (to the end) and -10 (the first 10), single chapters like 7, or a comma-separated mix such as 1-3,7,20-.
+    #[arg(long, value_parser = parse_chapter_selection, allow_hyphen_values = true)]
+    pub chapters: Option<ChapterSelection>,
+
+    /// Fetch only the chapters listed in this file: one chapter index or chapter URL per line (blank lines and # comments ignored). Cannot be combined with --chapters.
+    #[arg(long, conflicts_with = "chapters")]
+    pub include_chapters: Option<PathBuf>,
+
+    /// Never fetch chapters whose TOC title matches this regex (e.g. "(?i)author'?s note"). Applies on --resume runs too.
+    #[arg(long, value_parser = parse_title_exclude)]
+    pub title_exclude: Option<Regex>,
+
+    /// Split an anthology into one output per story: a chapter whose title matches this regex starts a new book named after it, written to the output directory as {sanitized-title}.{ext}. Chapters before the first match form their own book. Cannot be combined with --output, --index, --opds-entry, or --bundle.
+    #[arg(long, value_parser = parse_split_on_title, conflicts_with_all = ["output", "index", "opds_entry", "bundle"])]
+    pub split_on_title: Option<Regex>,
+
+    /// Resume from a partial scrape saved at this path (JSON; gzip-compressed when the path ends in .gz). Load existing chapters and fetch only missing ones; save progress after each chapter.
+    #[arg(long, group = "saved_book")]
     pub resume: Option<PathBuf>,
 
+    /// Update a book saved earlier as JSON at this path (gzip-compressed when it ends in .gz): fetch the TOC, fetch only chapters the book does not have yet, then rewrite PATH and the output. Chapters no longer in the TOC are kept with a warning (see --prune-deleted). Reports the number of new chapters.
+    #[arg(long, value_name = "PATH", group = "saved_book", conflicts_with_all = ["resume", "from_json", "single_chapter", "dry_run"])]
+    pub update: Option<PathBuf>,
+
+    /// With --update, drop saved chapters whose URL is no longer in the TOC (deleted or unpublished on the site) instead of keeping them.
+    #[arg(long, requires = "update")]
+    pub prune_deleted: bool,
+
+    /// Build the output from the --resume checkpoint alone, with no network access (no TOC, chapter, or cover requests). Use to convert a finished scrape to another --format.
+    #[arg(long, requires = "resume", conflicts_with = "dry_run")]
+    pub no_fetch: bool,
+
+    /// With --resume, write the checkpoint only every N chapters (plus once when the scrape ends or stops). Each write is the whole book, so large books save a lot of I/O with e.g. 25.
+    #[arg(long, default_value_t = 1, requires = "resume", value_parser = clap::value_parser!(u32).range(1..))]
+    pub checkpoint_interval: u32,
+
+    /// With --resume or --update, cache the parsed TOC next to the saved book (PATH.toc.json) and reuse it for this many seconds, skipping the story page (and Scribble Hub TOC pages) on quick successive runs. Not used with --stats.
+    #[arg(long, requires = "saved_book")]
+    pub toc_cache_ttl: Option<u64>,
+
+    /// With --resume or --update: when a TOC entry has a saved chapter's index but a different URL (renumbered or edited chapters): keep-existing (default; prefer-new with --update), prefer-new, or fail. Overrides config index_collision.
+    #[arg(long, value_parser = parse_index_collision)]
+    pub index_collision: Option<IndexCollisionBehavior>,
+
     /// How to handle Royal Road locked (premium) chapters: skip (default), placeholder, or fail.
     #[arg(long, default_value = "skip", value_parser = parse_locked_behavior)]
     pub locked_chapters: LockedChapterBehavior,
 
-    /// How to handle chapters with empty body or missing content: skip (default), placeholder, or fail.
+    /// How to handle chapters with empty body, missing content, or HTTP 4xx: skip (default), placeholder, or fail.
     #[arg(long, value_parser = parse_empty_chapter_behavior)]
     pub empty_chapters: Option<EmptyChapterBehavior>,
 
+    /// Abort on the first chapter network, HTTP, or body-read error (after writing the --resume checkpoint) instead of skipping the chapter.
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// Fail (exit 2) instead of warning when the final chapter list is out of order: indices not strictly increasing or empty titles. A title repeated by the next chapter is only a warning.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Royal Road: read chapters from html pages (default) or the site's JSON chapter endpoint (api), falling back to html if it fails.
+    #[arg(long, default_value = "html", value_parser = parse_chapter_source)]
+    pub source: ChapterSource,
+
+    /// Treat chapters with fewer plain-text words than this as empty (handled by --empty-chapters). 0 disables.
+    #[arg(long, default_value_t = 0)]
+    pub min_chapter_words: u32,
+
+    /// ScribbleHub chapter list: auto (AJAX, then pagination), ajax (AJAX only), or paginate (never AJAX). Overrides config scribblehub_toc_mode.
+    #[arg(long, value_parser = parse_scribblehub_toc_mode)]
+    pub scribblehub_toc: Option<ScribbleHubTocMode>,
+
+    /// ScribbleHub reading order: order (the site's TOC order) or number (chapter numbers parsed from titles, for newest-first TOCs).
+    #[arg(long, default_value = "order", value_parser = parse_toc_sort)]
+    pub toc_sort: TocSort,
+
+    /// Also record story-page popularity stats (views, followers, favorites) in the book's "stats" (JSON output only).
+    #[arg(long)]
+    pub stats: bool,
+
+    /// After scraping, drop chapters whose title looks like navigation ("Next", "Previous", "Index") and whose body is only a few words.
+    #[arg(long)]
+    pub drop_nav_artifacts: bool,
+
+    /// Royal Road: fetch all unlocked chapters first and add locked placeholders afterward, so an interrupted run keeps the most real content.
+    #[arg(long)]
+    pub unlocked_first: bool,
+
+    /// Treat the URL as a chapter page: fetch only that chapter and write a one-chapter book (no TOC). Author may be "Unknown Author".
+    #[arg(long, conflicts_with_all = ["chapters", "resume"])]
+    pub single_chapter: bool,
+
     /// HTTP User-Agent (overrides config).
     #[arg(long)]
     pub user_agent: Option<String>,
 
+    /// Text appended to the User-Agent in effect (default or --user-agent), e.g. "(+contact@example.com)".
+    #[arg(long)]
+    pub ua_suffix: Option<String>,
+
+    /// Trust this PEM root certificate in addition to the built-in roots, e.g. the CA of a corporate TLS-intercepting proxy.
+    #[arg(long)]
+    pub ca_cert: Option<PathBuf>,
+
+    /// DANGEROUS: accept any TLS certificate (self-signed, expired, wrong host), disabling protection against interception. Prefer --ca-cert.
+    #[arg(long)]
+    pub danger_accept_invalid_certs: bool,
+
     /// Delay between requests in seconds (overrides config; default 2).
     #[arg(long)]
     pub delay: Option<u64>,
 
+    /// Fetch up to N chapter pages at once. Requests still start at least --delay apart across all workers; only the waiting for responses overlaps. 1 fetches one chapter at a time.
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=16))]
+    pub concurrency: u32,
+
     /// Request timeout in seconds (overrides config; default 30).
     #[arg(long)]
     pub timeout: Option<u64>,
 
-    /// Resolve site, fetch TOC only, print chapter count and output path without writing.
+    /// Delay in seconds before cover image requests (default: same as the chapter delay). Use 0 for assets on a CDN.
+    #[arg(long)]
+    pub asset_delay: Option<u64>,
+
+    /// Wall-clock budget in seconds for the whole scrape. When exceeded, stop after the current chapter and exit with code 4 (progress is kept with --resume).
+    #[arg(long)]
+    pub deadline: Option<u64>,
+
+    /// When the scrape is stopped by Ctrl-C or --deadline, still write the output from the chapters fetched so far (at least one), then exit with the usual code.
+    #[arg(long)]
+    pub partial_ok: bool,
+
+    /// Attempts per request (overrides config retry_count; default 3, minimum 1).
+    #[arg(long)]
+    pub retries: Option<u32>,
+
+    /// Seconds to wait between retries, comma-separated, e.g. 1,2,4 (overrides config retry_backoff_secs).
+    #[arg(long, value_delimiter = ',')]
+    pub retry_backoff: Option<Vec<u64>>,
+
+    /// Resolve site, fetch TOC only, print chapter count and output path without writing. Fails if the output directory is missing or not writable.
     #[arg(long)]
     pub dry_run: bool,
 
-    /// After writing an EPUB, run epubcheck to validate it (epubcheck must be on PATH). No effect for non-EPUB output.
+    /// Fetch the TOC only and print each chapter as index<TAB>title to stdout, marking Royal Road locked chapters "(locked)". Writes no output file. With --format json, print a JSON array instead.
+    #[arg(long, conflicts_with_all = ["dry_run", "from_json", "update", "single_chapter"])]
+    pub list_chapters: bool,
+
+    /// Before finishing an EPUB, check that every spine, nav, and NCX reference resolves to a written manifest item (lighter than --validate).
+    #[arg(long)]
+    pub verify_links: bool,
+
+    /// After writing, validate the output: EPUB with epubcheck (must be on PATH); Markdown by scanning for HTML tags the conversion left behind. No effect for other formats.
     #[arg(long)]
     pub validate: bool,
+
+    /// If title/author cannot be parsed, take the title from the page <title> and use "Unknown Author" instead of failing.
+    #[arg(long)]
+    pub allow_missing_metadata: bool,
+
+    /// If the title cannot be parsed, derive it from the URL slug (e.g. mother-of-learning -> "Mother Of Learning") before falling back to the page <title>.
+    #[arg(long)]
+    pub title_from_url: bool,
 }
 
-fn parse_chapter_range(s: &str) -> Result<(u32, u32), String> {
-    let s = s.trim();
-    let (from_str, to_str) = s.split_once('-').ok_or_else(|| {
-        format!(
-            "Invalid --chapters: expected 'from-to' (e.g. 1-10), got '{}'",
+/// Parse an `--include-chapters` file: one chapter index or http(s) URL per line; blank lines
+/// and lines starting with `#` are skipped.
+fn parse_chapter_allowlist(text: &str) -> Result<ChapterAllowlist, String> {
+    let mut allow = ChapterAllowlist::default();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Ok(index) = line.parse::<u32>() {
+            allow.indices.insert(index);
+        } else if line.starts_with("http://") || line.starts_with("https://") {
+            allow.urls.insert(line.trim_end_matches('/').to_string());
+        } else {
+            return Err(format!(
+                "line {}: expected a chapter index or URL, got '{}'",
+                n + 1,
+                line
+            ));
+        }
+    }
+    Ok(allow)
+}
+
+fn parse_title_exclude(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|e| format!("Invalid --title-exclude regex: {}", e))
+}
+
+fn parse_language(s: &str) -> Result<String, String> {
+    if is_language_tag(s) {
+        Ok(s.to_string())
+    } else {
+        Err(format!(
+            "Invalid --language '{}': expected a BCP 47 tag such as fr or pt-BR",
             s
-        )
-    })?;
-    let from_str = from_str.trim();
-    let to_str = to_str.trim();
-    let from: u32 = from_str.parse().map_err(|_| {
-        format!(
-            "Invalid --chapters: '{}' is not a valid start chapter number",
-            from_str
-        )
-    })?;
-    let to: u32 = to_str.parse().map_err(|_| {
+        ))
+    }
+}
+
+fn parse_contributor(s: &str) -> Result<Contributor, String> {
+    let invalid = || {
         format!(
-            "Invalid --chapters: '{}' is not a valid end chapter number",
-            to_str
+            "Invalid --contributor '{}': expected ROLE:NAME with a three-letter MARC relator role, e.g. trl:Jane Doe",
+            s
         )
-    })?;
-    if from > to {
-        return Err(format!(
-            "Invalid --chapters: start ({}). must be <= end ({})",
-            from, to
-        ));
+    };
+    let (role, name) = s.split_once(':').ok_or_else(invalid)?;
+    let role = role.trim();
+    if role.len() != 3 || !role.chars().all(|c| c.is_ascii_lowercase()) || name.trim().is_empty() {
+        return Err(invalid());
+    }
+    Ok(Contributor {
+        role: role.to_string(),
+        name: name.trim().to_string(),
+    })
+}
+
+fn parse_series_index(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(n) if n.is_finite() && n >= 0.0 => Ok(n),
+        _ => Err(format!(
+            "Invalid --series-index '{}': expected a non-negative number such as 2 or 2.5",
+            s
+        )),
+    }
+}
+
+fn parse_split_on_title(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|e| format!("Invalid --split-on-title regex: {}", e))
+}
+
+/// Parse `--chapters`: comma-separated parts, each `from-to`, `from-` (to the end), `-to` (from
+/// the first chapter), or a single chapter number.
+fn parse_chapter_selection(s: &str) -> Result<ChapterSelection, String> {
+    let mut ranges = Vec::new();
+    for part in s.split(',').map(str::trim) {
+        if part.is_empty() {
+            return Err(format!(
+                "Invalid --chapters: empty entry in '{}' (e.g. 1-3,7,20-)",
+                s.trim()
+            ));
+        }
+        let number = |text: &str, which: &str| {
+            text.parse::<u32>().map_err(|_| {
+                format!(
+                    "Invalid --chapters: '{}' is not a valid {}chapter number",
+                    text, which
+                )
+            })
+        };
+        let (from, to) = match part.split_once('-') {
+            Some((from_str, to_str)) => {
+                let (from_str, to_str) = (from_str.trim(), to_str.trim());
+                if from_str.is_empty() && to_str.is_empty() {
+                    return Err(format!(
+                        "Invalid --chapters: '{}' needs a start or an end (e.g. 5- or -10)",
+                        part
+                    ));
+                }
+                let from = match from_str {
+                    "" => 1,
+                    f => number(f, "start ")?,
+                };
+                let to = match to_str {
+                    "" => u32::MAX,
+                    t => number(t, "end ")?,
+                };
+                (from, to)
+            }
+            None => {
+                let n = number(part, "")?;
+                (n, n)
+            }
+        };
+        if from > to {
+            return Err(format!(
+                "Invalid --chapters: start ({}) must be <= end ({})",
+                from, to
+            ));
+        }
+        ranges.push((from, to));
     }
-    Ok((from, to))
+    Ok(ChapterSelection { ranges })
 }
 
 fn parse_site(s: &str) -> Result<Site, String> {
     match s.to_lowercase().as_str() {
         "royalroad" | "rr" => Ok(Site::RoyalRoad),
         "scribblehub" | "sh" => Ok(Site::ScribbleHub),
+        "ao3" | "archiveofourown" => Ok(Site::Ao3),
         _ => Err(format!(
-            "Invalid --site value: '{}'. Use 'royalroad' or 'scribblehub'.",
+            "Invalid --site value: '{}'. Use 'royalroad', 'scribblehub' or 'ao3'.",
             s
         )),
     }
 }
 
+fn site_flag_name(site: Site) -> &'static str {
+    match site {
+        Site::RoyalRoad => "royalroad",
+        Site::ScribbleHub => "scribblehub",
+        Site::Ao3 => "ao3",
+    }
+}
+
 fn parse_locked_behavior(s: &str) -> Result<LockedChapterBehavior, String> {
     match s.to_lowercase().as_str() {
         "skip" => Ok(LockedChapterBehavior::Skip),
@@ -209,6 +818,95 @@ fn parse_empty_chapter_behavior(s: &str) -> Result<EmptyChapterBehavior, String>
     }
 }
 
+fn parse_index_collision(s: &str) -> Result<IndexCollisionBehavior, String> {
+    match s.to_lowercase().as_str() {
+        "keep-existing" => Ok(IndexCollisionBehavior::KeepExisting),
+        "prefer-new" => Ok(IndexCollisionBehavior::PreferNew),
+        "fail" => Ok(IndexCollisionBehavior::Fail),
+        _ => Err(format!(
+            "Invalid --index-collision value: '{}'. Use keep-existing, prefer-new, or fail.",
+            s
+        )),
+    }
+}
+
+fn parse_error_format(s: &str) -> Result<ErrorFormat, String> {
+    match s.to_lowercase().as_str() {
+        "human" => Ok(ErrorFormat::Human),
+        "json" => Ok(ErrorFormat::Json),
+        _ => Err(format!(
+            "Invalid --error-format value: '{}'. Use human or json.",
+            s
+        )),
+    }
+}
+
+fn parse_chapter_file_names(s: &str) -> Result<ChapterFileNames, String> {
+    match s.to_lowercase().as_str() {
+        "position" => Ok(ChapterFileNames::Position),
+        "index" => Ok(ChapterFileNames::Index),
+        _ => Err(format!(
+            "Invalid --chapter-filenames value: '{}'. Use position or index.",
+            s
+        )),
+    }
+}
+
+fn parse_chapter_source(s: &str) -> Result<ChapterSource, String> {
+    match s.to_lowercase().as_str() {
+        "html" => Ok(ChapterSource::Html),
+        "api" => Ok(ChapterSource::Api),
+        _ => Err(format!("Invalid --source value: '{}'. Use html or api.", s)),
+    }
+}
+
+fn parse_scribblehub_toc_mode(s: &str) -> Result<ScribbleHubTocMode, String> {
+    match s.to_lowercase().as_str() {
+        "auto" => Ok(ScribbleHubTocMode::Auto),
+        "ajax" => Ok(ScribbleHubTocMode::Ajax),
+        "paginate" => Ok(ScribbleHubTocMode::Paginate),
+        _ => Err(format!(
+            "Invalid --scribblehub-toc value: '{}'. Use auto, ajax, or paginate.",
+            s
+        )),
+    }
+}
+
+fn parse_toc_sort(s: &str) -> Result<TocSort, String> {
+    match s.to_lowercase().as_str() {
+        "order" => Ok(TocSort::Order),
+        "number" => Ok(TocSort::Number),
+        _ => Err(format!(
+            "Invalid --toc-sort value: '{}'. Use order or number.",
+            s
+        )),
+    }
+}
+
+fn parse_progress_mode(s: &str) -> Result<ProgressMode, String> {
+    match s.to_lowercase().as_str() {
+        "bar" => Ok(ProgressMode::Bar),
+        "plain" => Ok(ProgressMode::Plain),
+        "json" => Ok(ProgressMode::Json),
+        "none" => Ok(ProgressMode::None),
+        _ => Err(format!(
+            "Invalid --progress value: '{}'. Use bar, plain, json, or none.",
+            s
+        )),
+    }
+}
+
+fn parse_config_format(s: &str) -> Result<ConfigFormat, String> {
+    match s.to_lowercase().as_str() {
+        "toml" => Ok(ConfigFormat::Toml),
+        "json" => Ok(ConfigFormat::Json),
+        _ => Err(format!(
+            "Invalid --print-config value: '{}'. Use toml or json.",
+            s
+        )),
+    }
+}
+
 fn parse_format(s: &str) -> Result<OutputFormat, String> {
     match s.to_lowercase().as_str() {
         "epub" => Ok(OutputFormat::Epub),
@@ -216,8 +914,10 @@ fn parse_format(s: &str) -> Result<OutputFormat, String> {
         "html" => Ok(OutputFormat::Html),
         "markdown" | "md" => Ok(OutputFormat::Markdown),
         "text" | "txt" => Ok(OutputFormat::Text),
+        "pdf" => Ok(OutputFormat::Pdf),
+        "fb2" => Ok(OutputFormat::Fb2),
         _ => Err(format!(
-            "Invalid --format value: '{}'. Use epub, json, html, markdown, or text.",
+            "Invalid --format value: '{}'. Use epub, json, html, markdown, text, pdf, or fb2.",
             s
         )),
     }
@@ -230,6 +930,28 @@ fn extension_for_format(format: OutputFormat) -> &'static str {
         OutputFormat::Html => "html",
         OutputFormat::Markdown => "md",
         OutputFormat::Text => "txt",
+        OutputFormat::Pdf => "pdf",
+        OutputFormat::Fb2 => "fb2",
+    }
+}
+
+/// Compare the output path's extension against the one expected for `format` (case-insensitive).
+/// Returns a message describing the mismatch, or None if the extension matches.
+fn extension_mismatch(path: &Path, format: OutputFormat) -> Option<String> {
+    let expected = extension_for_format(format);
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case(expected) => None,
+        Some(ext) => Some(format!(
+            "Output path {} has extension .{} but --format writes .{} files.",
+            path.display(),
+            ext,
+            expected
+        )),
+        None => Some(format!(
+            "Output path {} has no extension; --format writes .{} files.",
+            path.display(),
+            expected
+        )),
     }
 }
 
@@ -256,12 +978,43 @@ fn sanitize_title(title: &str) -> String {
     s
 }
 
-/// Ensure output path parent exists and is writable; return path.
-fn validate_output_path(path: &Path) -> Result<(), CliRunError> {
+/// `--split-on-title` output paths: `{dir}/{sanitized-title}.{ext}` per part, with `-2`, `-3`, ...
+/// appended when two parts sanitize to the same name.
+fn split_output_paths(parts: &[Book], dir: &Path, format: OutputFormat) -> Vec<PathBuf> {
+    let ext = extension_for_format(format);
+    let mut seen: Vec<String> = Vec::with_capacity(parts.len());
+    parts
+        .iter()
+        .map(|part| {
+            let base = sanitize_title(&part.title);
+            let mut name = base.clone();
+            let mut n = 1;
+            while seen.contains(&name) {
+                n += 1;
+                name = format!("{}-{}", base, n);
+            }
+            seen.push(name.clone());
+            dir.join(format!("{}.{}", name, ext))
+        })
+        .collect()
+}
+
+/// Ensure output path parent exists; with `mkdirs` (`--mkdirs`) a missing parent tree is created.
+fn validate_output_path(path: &Path, mkdirs: bool) -> Result<(), CliRunError> {
     if let Some(parent) = path.parent() {
         if !parent.as_os_str().is_empty() && !parent.exists() {
-            return Err(CliRunError::InvalidInput(format!(
-                "Cannot write output: {}: parent directory does not exist.",
+            if mkdirs {
+                return std::fs::create_dir_all(parent).map_err(|e| {
+                    CliRunError::InvalidInput(format!(
+                        "Cannot write output: {}: could not create {} ({}).",
+                        path.display(),
+                        parent.display(),
+                        e
+                    ))
+                });
+            }
+            return Err(CliRunError::InvalidInput(format!(
+                "Cannot write output: {}: parent directory does not exist. Use --mkdirs to create it.",
                 path.display()
             )));
         }
@@ -269,21 +1022,640 @@ fn validate_output_path(path: &Path) -> Result<(), CliRunError> {
     Ok(())
 }
 
-/// Entry point for the CLI. Returns Ok(()) on success; Err with exit code and message on failure.
-pub fn run(args: &Args) -> Result<(), CliRunError> {
-    let site = resolve_site(&args.url, args.site).map_err(|e| match &e {
-        ScraperError::InvalidUrl { input, reason } => CliRunError::InvalidInput(format!(
-            "Expected a story URL. Example: https://www.royalroad.com/fiction/12345/... Invalid: {}: {}",
-            input, reason
-        )),
-        ScraperError::UnrecognizedHost { host } => CliRunError::InvalidInput(format!(
-            "Unsupported site: {}. Use --site royalroad or scribblehub to override, or provide a Royal Road / Scribble Hub URL.",
-            host
-        )),
-        _ => CliRunError::Scraper(e),
+/// Check that the output directory accepts new files by creating and removing a probe file.
+fn probe_output_writable(path: &Path) -> Result<(), CliRunError> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let probe = dir.join(format!(".rdrscrape-write-probe-{}", std::process::id()));
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| {
+            CliRunError::InvalidInput(format!(
+                "Cannot write output: {}: {} is not writable ({}).",
+                path.display(),
+                dir.display(),
+                e
+            ))
+        })
+}
+
+/// Set by the Ctrl-C handler and read by the scrape's cancel check between chapters.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// True while chapters are being fetched; outside that window Ctrl-C exits at once.
+static SCRAPING: AtomicBool = AtomicBool::new(false);
+
+/// First Ctrl-C while scraping: let the chapter in progress finish, then stop the scrape (the
+/// checkpoint is already written). A second Ctrl-C, or any Ctrl-C before or after the scrape,
+/// exits at once with code 130. Installed once per process.
+fn install_interrupt_handler() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let installed = ctrlc::set_handler(|| {
+            if !SCRAPING.load(Ordering::SeqCst) || INTERRUPTED.swap(true, Ordering::SeqCst) {
+                std::process::exit(130);
+            }
+            eprintln!(
+                "\nInterrupted: stopping after the current chapter (Ctrl-C again to quit now)."
+            );
+        });
+        if let Err(e) = installed {
+            eprintln!("Warning: could not install the Ctrl-C handler: {}", e);
+        }
+    });
+}
+
+/// `--resume` paths ending in `.gz` hold gzip-compressed JSON; any other path is plain JSON.
+fn is_gzip_checkpoint(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Temporary file next to `path` for an atomic write: written in full, then renamed over `path`.
+fn temp_path_for(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    PathBuf::from(tmp)
+}
+
+/// Write the resume checkpoint, gzip-compressed when `path` ends in `.gz`. The file is replaced
+/// atomically (temporary file and rename), so an interrupt mid-write keeps the previous one.
+fn write_checkpoint_file(path: &Path, book: &Book) -> std::io::Result<()> {
+    let tmp = temp_path_for(path);
+    let f = std::fs::File::create(&tmp)?;
+    let to_io = |e: serde_json::Error| std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+    let written = if is_gzip_checkpoint(path) {
+        let mut gz = GzEncoder::new(f, Compression::default());
+        serde_json::to_writer(&mut gz, book)
+            .map_err(to_io)
+            .and_then(|()| gz.finish().map(drop))
+    } else {
+        serde_json::to_writer(f, book).map_err(to_io)
+    };
+    match written {
+        Ok(()) => std::fs::rename(&tmp, path),
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// Read a resume checkpoint opened from `path`, decompressing `.gz` files.
+fn read_checkpoint(f: std::fs::File, path: &Path) -> Result<Book, serde_json::Error> {
+    if is_gzip_checkpoint(path) {
+        serde_json::from_reader(GzDecoder::new(f))
+    } else {
+        serde_json::from_reader(f)
+    }
+}
+
+/// `--toc-cache-ttl` file: the parsed TOC of `source_url`, written at `fetched_at` (Unix seconds).
+#[derive(Debug, Serialize, Deserialize)]
+struct TocCache {
+    #[serde(rename = "sourceUrl")]
+    source_url: String,
+    #[serde(rename = "fetchedAt")]
+    fetched_at: u64,
+    entries: Vec<TocEntry>,
+}
+
+/// TOC cache path for a saved book (--resume checkpoint or --update PATH): the path with
+/// `.toc.json` appended.
+fn toc_cache_path(saved: &Path) -> PathBuf {
+    let mut name = saved.as_os_str().to_os_string();
+    name.push(".toc.json");
+    PathBuf::from(name)
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Whether two URLs name the same story, compared in [Site::canonicalize_url] form. URLs that do
+/// not canonicalize (e.g. `--site` with a foreign path) fall back to ignoring a trailing slash.
+fn same_story_url(site: Site, a: &str, b: &str) -> bool {
+    match (site.canonicalize_url(a), site.canonicalize_url(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.trim_end_matches('/') == b.trim_end_matches('/'),
+    }
+}
+
+/// Cached TOC for `url` if the file exists, parses, names the same URL, and is at most
+/// `ttl_secs` old at `now`. Anything else means a fresh fetch.
+fn load_toc_cache(
+    path: &Path,
+    site: Site,
+    url: &str,
+    ttl_secs: u64,
+    now: u64,
+) -> Option<Vec<TocEntry>> {
+    let f = std::fs::File::open(path).ok()?;
+    let cache: TocCache = serde_json::from_reader(f).ok()?;
+    let same_url = same_story_url(site, &cache.source_url, url);
+    let fresh = now.saturating_sub(cache.fetched_at) <= ttl_secs;
+    (same_url && fresh).then_some(cache.entries)
+}
+
+/// Write the TOC cache through a temporary file and a rename, so a concurrent run never reads a
+/// half-written cache.
+fn write_toc_cache(path: &Path, url: &str, entries: &[TocEntry], now: u64) -> std::io::Result<()> {
+    let cache = TocCache {
+        source_url: url.to_string(),
+        fetched_at: now,
+        entries: entries.to_vec(),
+    };
+    let tmp = temp_path_for(path);
+    let f = std::fs::File::create(&tmp)?;
+    serde_json::to_writer(f, &cache)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    std::fs::rename(&tmp, path)
+}
+
+/// `--from-json` input: a Book saved with `--format json` or as a checkpoint (gzip when the name
+/// ends in `.gz`). Anything that does not match the Book shape is an input error.
+fn load_book_json(path: &Path) -> Result<Book, CliRunError> {
+    let f = std::fs::File::open(path).map_err(|e| {
+        CliRunError::InvalidInput(format!(
+            "Cannot read --from-json file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    read_checkpoint(f, path).map_err(|e| {
+        CliRunError::InvalidInput(format!(
+            "--from-json file {} is not a book (see OUTPUT_SHAPE.md): {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// `--update` input: the previously saved Book, which must exist and be for the same story as
+/// `url` (compared with [same_story_url]).
+fn load_update_book(path: &Path, site: Site, url: &str) -> Result<Book, CliRunError> {
+    let f = std::fs::File::open(path).map_err(|e| {
+        CliRunError::InvalidInput(format!(
+            "Cannot read --update file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let book = read_checkpoint(f, path).map_err(|e| {
+        CliRunError::InvalidInput(format!(
+            "--update file {} is not a book (see OUTPUT_SHAPE.md): {}",
+            path.display(),
+            e
+        ))
+    })?;
+    if let Some(ref surl) = book.source_url {
+        if !same_story_url(site, surl, url) {
+            return Err(CliRunError::InvalidInput(format!(
+                "--update file is for a different URL ({}). Use the same URL as the original run ({}).",
+                surl, url
+            )));
+        }
+    }
+    Ok(book)
+}
+
+/// `url` in [Site::canonicalize_url] form, for matching chapter URLs across runs. A URL that does
+/// not canonicalize (e.g. `--site` with a foreign path) only loses its trailing slash.
+fn comparable_url(site: Site, url: &str) -> String {
+    site.canonicalize_url(url)
+        .unwrap_or_else(|_| url.trim_end_matches('/').to_string())
+}
+
+/// Chapters whose URL is not in the fresh TOC (deleted on the site). `toc_urls` are in
+/// [comparable_url] form. Chapters without a recorded URL never count, since they cannot be
+/// matched.
+fn chapters_not_in_toc<'a>(
+    book: &'a Book,
+    toc_urls: &HashSet<String>,
+    site: Site,
+) -> Vec<&'a Chapter> {
+    book.chapters
+        .iter()
+        .filter(|c| {
+            c.url
+                .as_deref()
+                .is_some_and(|u| !toc_urls.contains(&comparable_url(site, u)))
+        })
+        .collect()
+}
+
+/// Chapters in `book` that `previous` did not have, matched by URL in [comparable_url] form like
+/// [chapters_not_in_toc] (by index when either side has no URL). A chapter whose URL changed at
+/// the same index counts as new.
+fn count_new_chapters(book: &Book, previous: &Book, site: Site) -> usize {
+    let known_urls: HashSet<String> = previous
+        .chapters
+        .iter()
+        .filter_map(|c| c.url.as_deref())
+        .map(|u| comparable_url(site, u))
+        .collect();
+    book.chapters
+        .iter()
+        .filter(|c| match c.url.as_deref() {
+            Some(u) => !known_urls.contains(&comparable_url(site, u)),
+            None => !previous.chapters.iter().any(|p| p.index == c.index),
+        })
+        .count()
+}
+
+/// End of an `--update` run: warn about chapters the TOC no longer lists (and drop them when
+/// `prune` is set), report the new chapters, and save the updated book back to `path`.
+fn finish_update(
+    mut book: Book,
+    previous: &Book,
+    toc_urls: Option<HashSet<String>>,
+    site: Site,
+    prune: bool,
+    path: &Path,
+) -> Result<Book, CliRunError> {
+    if let Some(ref toc_urls) = toc_urls {
+        let mut gone = Vec::new();
+        for ch in chapters_not_in_toc(&book, toc_urls, site) {
+            if prune {
+                eprintln!(
+                    "Warning: dropped chapter {} ({:?}): no longer in the TOC.",
+                    ch.index, ch.title
+                );
+            } else {
+                eprintln!(
+                    "Warning: chapter {} ({:?}) is no longer in the TOC; kept it (--prune-deleted drops it).",
+                    ch.index, ch.title
+                );
+            }
+            gone.push(ch.index);
+        }
+        if prune {
+            book.chapters.retain(|c| !gone.contains(&c.index));
+        }
+    }
+    eprintln!(
+        "{} new chapter(s) since {}.",
+        count_new_chapters(&book, previous, site),
+        path.display()
+    );
+    write_checkpoint_file(path, &book).map_err(|source| FormatError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(book)
+}
+
+/// The `--resume` checkpoint as the finished book for `--no-fetch`. Offline there is no TOC to
+/// compare against, so this checks what a writer needs: title, author, and at least one chapter.
+fn checkpoint_book(loaded: Option<&Book>, path: &Path) -> Result<Book, CliRunError> {
+    let book = loaded.ok_or_else(|| {
+        CliRunError::InvalidInput(format!(
+            "--no-fetch: resume file {} does not exist.",
+            path.display()
+        ))
+    })?;
+    let problem = if book.title.trim().is_empty() {
+        Some("it has no title")
+    } else if book.author.trim().is_empty() {
+        Some("it has no author")
+    } else if book.chapters.is_empty() {
+        Some("it has no chapters")
+    } else {
+        None
+    };
+    if let Some(problem) = problem {
+        return Err(CliRunError::InvalidInput(format!(
+            "--no-fetch: cannot build output from {}: {}. Rerun without --no-fetch to finish the scrape.",
+            path.display(),
+            problem
+        )));
+    }
+    Ok(book.clone())
+}
+
+/// Book as JSON for `--format json`, with `contentHash` ([Book::content_hash]) added for dedup tools.
+fn book_json_with_hash(book: &Book) -> serde_json::Value {
+    let mut value = serde_json::to_value(book).unwrap_or(serde_json::Value::Null);
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert(
+            "contentHash".to_string(),
+            serde_json::Value::String(book.content_hash()),
+        );
+    }
+    value
+}
+
+/// Apply `--description` / `--no-description` to the scraped book. An empty override clears it, like `--no-description`.
+fn apply_description_override(book: &mut Book, description: Option<&str>, no_description: bool) {
+    if no_description {
+        book.description = None;
+    } else if let Some(d) = description {
+        let d = d.trim();
+        book.description = (!d.is_empty()).then(|| d.to_string());
+    }
+}
+
+/// `--title-with-author` title: "Title — Author". The author is left off when it is empty or the
+/// [UNKNOWN_AUTHOR] placeholder, which would not tell anything apart.
+fn title_with_author(title: &str, author: &str) -> String {
+    let author = author.trim();
+    if author.is_empty() || author == UNKNOWN_AUTHOR {
+        return title.to_string();
+    }
+    format!("{} \u{2014} {}", title.trim(), author)
+}
+
+/// Zip entries for `--bundle`: the written output file, plus `book.json` (with `contentHash`) unless the output is already JSON.
+fn bundle_entries(
+    book: &Book,
+    output_path: &Path,
+    format: OutputFormat,
+) -> Result<Vec<(String, Vec<u8>)>, CliRunError> {
+    let name = output_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| format!("book.{}", extension_for_format(format)));
+    let bytes = std::fs::read(output_path).map_err(|e| {
+        CliRunError::Format(FormatError::Io {
+            path: output_path.to_path_buf(),
+            source: e,
+        })
+    })?;
+    let mut entries = vec![(name, bytes)];
+    if format != OutputFormat::Json {
+        let json = serde_json::to_vec(&book_json_with_hash(book))
+            .map_err(|e| CliRunError::InvalidInput(format!("Failed to write JSON: {}", e)))?;
+        entries.push(("book.json".to_string(), json));
+    }
+    Ok(entries)
+}
+
+/// Read a `--ca-cert` file. It must be PEM: a DER or empty file would otherwise be accepted
+/// with no certificate added.
+fn read_ca_cert(path: &Path) -> Result<Vec<u8>, CliRunError> {
+    let pem = std::fs::read(path).map_err(|e| {
+        CliRunError::InvalidInput(format!("Cannot read --ca-cert {}: {}", path.display(), e))
     })?;
+    if !String::from_utf8_lossy(&pem).contains("-----BEGIN CERTIFICATE-----") {
+        return Err(CliRunError::InvalidInput(format!(
+            "--ca-cert {} is not a PEM certificate (expected -----BEGIN CERTIFICATE-----).",
+            path.display()
+        )));
+    }
+    Ok(pem)
+}
+
+/// Contents of the `--css` file, which must be UTF-8 text.
+fn read_css(path: &Path) -> Result<String, CliRunError> {
+    std::fs::read_to_string(path).map_err(|e| {
+        CliRunError::InvalidInput(format!("Cannot read --css file {}: {}", path.display(), e))
+    })
+}
+
+/// `--opds-entry` acquisition link: the EPUB path relative to the entry file's directory (with
+/// `..` steps when the EPUB is elsewhere), so the feed works wherever it is served from. Falls
+/// back to the absolute EPUB path when the two share no root (e.g. different Windows drives).
+/// Always uses `/` separators.
+fn opds_href(epub_path: &Path, entry_path: &Path) -> String {
+    // Absolute with `.` and `..` resolved lexically; the files need not exist yet.
+    let normalized = |path: &Path| {
+        let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        let mut out = PathBuf::new();
+        for component in absolute.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    out.pop();
+                }
+                other => out.push(other),
+            }
+        }
+        out
+    };
+    let epub = normalized(epub_path);
+    let entry = normalized(entry_path);
+    let entry_dir: Vec<Component> = entry
+        .parent()
+        .map_or(Vec::new(), |d| d.components().collect());
+    let epub_parts: Vec<Component> = epub.components().collect();
+    let common = entry_dir
+        .iter()
+        .zip(&epub_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return epub.to_string_lossy().replace('\\', "/");
+    }
+    std::iter::repeat_n("..".into(), entry_dir.len() - common)
+        .chain(
+            epub_parts[common..]
+                .iter()
+                .map(|c| c.as_os_str().to_string_lossy()),
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Byte count for the success message: "512 B", "1.5 KB", "2.3 MB", "1.1 GB" (1 KB = 1024 B).
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Wall-clock time for the success message: "4.2s", "3m 05s", "1h 02m 03s".
+fn human_duration(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        return format!("{:.1}s", elapsed.as_secs_f64());
+    }
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 {
+        format!("{}h {:02}m {:02}s", h, m, s)
+    } else {
+        format!("{}m {:02}s", m, s)
+    }
+}
+
+/// Rate-limit responses (429/503) in one run at which [rate_limit_advice] speaks up.
+const RATE_LIMIT_ADVICE_THRESHOLD: u32 = 3;
+
+/// End-of-scrape hint when the site kept rate-limiting us, suggesting double the current delay.
+fn rate_limit_advice(count: u32, delay_secs: u64) -> Option<String> {
+    (count >= RATE_LIMIT_ADVICE_THRESHOLD).then(|| {
+        format!(
+            "Note: encountered {} rate-limit responses (HTTP 429/503); consider increasing --delay (e.g. --delay {}).",
+            count,
+            delay_secs.max(1) * 2
+        )
+    })
+}
+
+/// Write `book` to `output_path` in the `--format` chosen in `args`; `css` is the `--css` file.
+fn write_output(
+    book: &Book,
+    output_path: &Path,
+    args: &Args,
+    config: Option<&config::Config>,
+    css: Option<&str>,
+    client: &mut PoliteClient,
+) -> Result<(), CliRunError> {
+    match args.format {
+        OutputFormat::Json => {
+            let f = std::fs::File::create(output_path).map_err(|e| {
+                CliRunError::Epub(EpubError::CreateFile {
+                    path: output_path.to_path_buf(),
+                    source: e,
+                })
+            })?;
+            serde_json::to_writer(f, &book_json_with_hash(book))
+                .map_err(|e| CliRunError::InvalidInput(format!("Failed to write JSON: {}", e)))?;
+        }
+        OutputFormat::Epub => {
+            let version = if args.epub_2 {
+                EpubVersion::Epub2
+            } else {
+                EpubVersion::Epub3
+            };
+            let include_toc_page = config.and_then(|c| c.toc_page).unwrap_or(true);
+            let epub_options = EpubOptions {
+                version,
+                include_ncx: args.ncx,
+                include_toc_page,
+                nav_links: args.nav_links,
+                toc_title: args.toc_title.clone(),
+                verify_links: args.verify_links,
+                chapter_file_names: args.chapter_filenames,
+                description_page: args.description_page,
+                about_page: args.about_page,
+                cover_timeout_secs: config
+                    .and_then(|c| c.cover_timeout_secs)
+                    .unwrap_or(DEFAULT_COVER_TIMEOUT_SECS),
+                asset_delay_secs: args.asset_delay,
+                fetch_cover: !args.no_fetch,
+                include_cover: !args.no_cover,
+                css: css.map(String::from),
+                fetch_images: !args.no_fetch,
+                rights: args
+                    .rights
+                    .clone()
+                    .or_else(|| config.and_then(|c| c.rights.clone())),
+                chapter_title_template: args.chapter_title_template.clone(),
+                short_title_element: args.short_title_element,
+            };
+            write_epub(book, output_path, &epub_options, client)?;
+            if args.validate {
+                validate_epub(output_path)?;
+            }
+        }
+        OutputFormat::Html => write_html(book, output_path, args.nav_links)?,
+        OutputFormat::Markdown => {
+            write_markdown(book, output_path)?;
+            if args.validate {
+                validate_markdown(output_path)?;
+            }
+        }
+        OutputFormat::Text => write_text(book, output_path)?,
+        OutputFormat::Pdf => {
+            let toc_page = config.and_then(|c| c.toc_page).unwrap_or(true);
+            write_pdf(book, output_path, toc_page)?
+        }
+        OutputFormat::Fb2 => {
+            let cover = if args.no_cover || args.no_fetch {
+                None
+            } else {
+                fetch_cover_image(
+                    book,
+                    client,
+                    config
+                        .and_then(|c| c.cover_timeout_secs)
+                        .unwrap_or(DEFAULT_COVER_TIMEOUT_SECS),
+                    args.asset_delay,
+                )
+            };
+            let cover = cover.as_ref().map(|(data, ext)| (data.as_slice(), *ext));
+            write_fb2(book, output_path, cover)?
+        }
+    }
+    Ok(())
+}
 
+/// Entry point for the CLI. Returns Ok(()) on success; Err with exit code and message on failure.
+pub fn run(args: &Args) -> Result<(), CliRunError> {
+    let run_started = Instant::now();
     let config = config::load_config().map_err(CliRunError::InvalidInput)?;
+    let extra_hosts = config.as_ref().and_then(|c| c.extra_hosts.as_ref());
+
+    // --from-json has no URL: the site only matters for scraping.
+    let site = match args.url {
+        Some(ref url) => {
+            let site = resolve_site(url, args.site, extra_hosts).map_err(|e| match &e {
+                ScraperError::InvalidUrl { input, reason } => CliRunError::InvalidInput(format!(
+                    "Expected a story URL. Example: https://www.royalroad.com/fiction/12345/... Invalid: {}: {}",
+                    input, reason
+                )),
+                ScraperError::UnrecognizedHost { host } => CliRunError::InvalidInput(format!(
+                    "Unsupported site: {}. Use --site royalroad, scribblehub or ao3 to override, or provide a Royal Road / Scribble Hub / Archive of Our Own URL.",
+                    host
+                )),
+                _ => CliRunError::Scraper(e),
+            })?;
+
+            if let Some(detected) = site_override_mismatch(url, args.site, extra_hosts) {
+                eprintln!(
+                    "Warning: --site {} was given but the URL looks like a {} URL; using {} anyway.",
+                    site_flag_name(site),
+                    site_flag_name(detected),
+                    site_flag_name(site)
+                );
+            }
+
+            if let Some(blocked) = config.as_ref().and_then(|c| c.blocked_hosts.as_deref()) {
+                check_blocked_host(url, blocked)
+                    .map_err(|e| CliRunError::InvalidInput(e.to_string()))?;
+            }
+            Some(site)
+        }
+        None => None,
+    };
+
+    if args.opds_entry.is_some() && args.format != OutputFormat::Epub {
+        return Err(CliRunError::InvalidInput(
+            "--opds-entry links to the EPUB and requires --format epub.".to_string(),
+        ));
+    }
+
+    if let Some(ref output) = args.output {
+        if let Some(msg) = extension_mismatch(output, args.format) {
+            let strict = config
+                .as_ref()
+                .and_then(|c| c.strict_extension)
+                .unwrap_or(false);
+            if strict {
+                return Err(CliRunError::InvalidInput(msg));
+            }
+            eprintln!("Warning: {}", msg);
+        }
+    }
+
+    // Read before scraping so a bad path fails fast, and once for all split parts.
+    let css = args.css.as_deref().map(read_css).transpose()?;
 
     let effective_output_dir: PathBuf = config
         .as_ref()
@@ -293,6 +1665,7 @@ pub fn run(args: &Args) -> Result<(), CliRunError> {
     const DEFAULT_DELAY_SECS: u64 = 2;
     const DEFAULT_TIMEOUT_SECS: u64 = 30;
     const DEFAULT_RETRY_COUNT: u32 = 3;
+    const DEFAULT_CHAPTER_PARSE_RETRIES: u32 = 2;
     let delay_secs = args
         .delay
         .or_else(|| config.as_ref().and_then(|c| c.request_delay_secs))
@@ -301,15 +1674,20 @@ pub fn run(args: &Args) -> Result<(), CliRunError> {
         .timeout
         .or_else(|| config.as_ref().and_then(|c| c.timeout_secs))
         .unwrap_or(DEFAULT_TIMEOUT_SECS);
-    let retry_count = config
-        .as_ref()
-        .and_then(|c| c.retry_count)
+    let retry_count = args
+        .retries
+        .or_else(|| config.as_ref().and_then(|c| c.retry_count))
         .unwrap_or(DEFAULT_RETRY_COUNT)
         .max(1);
-    let retry_backoff_secs = config
-        .as_ref()
-        .and_then(|c| c.retry_backoff_secs.clone())
+    let retry_backoff_secs = args
+        .retry_backoff
+        .clone()
+        .or_else(|| config.as_ref().and_then(|c| c.retry_backoff_secs.clone()))
         .unwrap_or_else(|| vec![1, 2, 4]);
+    let chapter_parse_retries = config
+        .as_ref()
+        .and_then(|c| c.chapter_parse_retries)
+        .unwrap_or(DEFAULT_CHAPTER_PARSE_RETRIES);
     let user_agent = args
         .user_agent
         .clone()
@@ -319,41 +1697,129 @@ pub fn run(args: &Args) -> Result<(), CliRunError> {
         .delay_secs(delay_secs)
         .timeout_secs(timeout_secs)
         .retry_count(retry_count)
-        .retry_backoff_secs(retry_backoff_secs);
+        .retry_backoff_secs(retry_backoff_secs.clone());
     if let Some(ua) = user_agent {
         builder = builder.user_agent(ua);
     }
+    if let Some(ref suffix) = args.ua_suffix {
+        builder = builder.user_agent_suffix(suffix.clone());
+    }
+    if let Some(cap) = config.as_ref().and_then(|c| c.max_total_backoff_secs) {
+        builder = builder.max_total_backoff_secs(cap);
+    }
+    if let Some(ref path) = args.ca_cert {
+        builder = builder.add_root_certificate_pem(read_ca_cert(path)?);
+    }
+    if args.danger_accept_invalid_certs {
+        eprintln!("Warning: --danger-accept-invalid-certs: TLS certificates are not verified.");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
     let mut client = builder
         .build()
         .map_err(|e| CliRunError::InvalidInput(format!("Failed to create HTTP client: {}", e)))?;
 
+    let mut allowed_tags = BodyOptions::default();
+    if let Some(block) = config.as_ref().and_then(|c| c.allowed_block_tags.clone()) {
+        allowed_tags.block_tags = block;
+    }
+    if let Some(inline) = config.as_ref().and_then(|c| c.allowed_inline_tags.clone()) {
+        allowed_tags.inline_tags = inline;
+    }
+    let hidden_classes = config
+        .as_ref()
+        .and_then(|c| c.royalroad_hidden_classes.as_deref())
+        .unwrap_or_default()
+        .iter()
+        .map(|p| {
+            Regex::new(p).map_err(|e| {
+                CliRunError::InvalidInput(format!(
+                    "royalroad_hidden_classes: invalid regex '{}': {}",
+                    p, e
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let effective = EffectiveConfig {
+        site: site.map(site_flag_name),
+        format: extension_for_format(args.format),
+        output_dir: effective_output_dir.clone(),
+        request_delay_secs: delay_secs,
+        timeout_secs,
+        retry_count,
+        retry_backoff_secs,
+        max_total_backoff_secs: config.as_ref().and_then(|c| c.max_total_backoff_secs),
+        chapter_parse_retries,
+        user_agent: client.user_agent().to_string(),
+        allowed_block_tags: allowed_tags.block_tags.clone(),
+        allowed_inline_tags: allowed_tags.inline_tags.clone(),
+    };
+    if let Some(format) = args.print_config {
+        println!("{}", effective.render(format)?.trim_end());
+        return Ok(());
+    }
+
+    if let Some(ref path) = args.from_json {
+        return write_outputs(
+            load_book_json(path)?,
+            args,
+            config.as_ref(),
+            css.as_deref(),
+            &effective_output_dir,
+            &mut client,
+            run_started,
+        );
+    }
+    let (Some(site), Some(url)) = (site, args.url.as_deref()) else {
+        return Err(CliRunError::InvalidInput(
+            "A story URL is required unless --from-json is given.".to_string(),
+        ));
+    };
+
+    let progress_mode =
+        effective_progress_mode(args.progress, args.quiet, std::io::stderr().is_terminal());
+    let no_color = args.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let (tick_chars, progress_chars) = bar_chars(no_color);
     let progress_state: RefCell<Option<indicatif::ProgressBar>> = RefCell::new(None);
-    let progress_cb = |n: u32, total: u32| {
+    let progress_cb = |n: u32, total: u32, title: &str| {
         if total == 0 {
             return;
         }
+        if progress_mode != ProgressMode::Bar {
+            if let Some(line) = progress_line(progress_mode, n, total, title) {
+                eprintln!("{}", line);
+            }
+            return;
+        }
         let mut state = progress_state.borrow_mut();
         let pb = state.get_or_insert_with(|| {
-            let bar = indicatif::ProgressBar::new(total as u64);
-            bar.set_style(
-                indicatif::ProgressStyle::default_bar()
-                    .template("{spinner} {msg} [{bar:40}] {pos}/{len} ({elapsed})")
-                    .unwrap()
-                    .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏")
-                    .progress_chars("█▉▊▋▌▍▎▏ "),
+            let bar = indicatif::ProgressBar::with_draw_target(
+                Some(total as u64),
+                indicatif::ProgressDrawTarget::stderr(),
             );
+            bar.set_style(progress_style(
+                PROGRESS_TEMPLATE,
+                tick_chars,
+                progress_chars,
+            ));
             bar.enable_steady_tick(Duration::from_millis(80));
             bar
         });
         pb.set_position(n as u64);
         pb.set_message(format!("Fetching chapter {}/{}", n, total));
     };
-    let progress: Option<&dyn Fn(u32, u32)> = if args.quiet { None } else { Some(&progress_cb) };
+    let progress: Option<ProgressCallback> = if progress_mode == ProgressMode::None {
+        None
+    } else {
+        Some(&progress_cb)
+    };
 
-    let initial_book: Option<Book> = if let Some(ref resume_path) = args.resume {
+    let initial_book: Option<Book> = if let Some(ref update_path) = args.update {
+        Some(load_update_book(update_path, site, url)?)
+    } else if let Some(ref resume_path) = args.resume {
         match std::fs::File::open(resume_path) {
             Ok(f) => {
-                let loaded: Book = serde_json::from_reader(f).map_err(|e| {
+                let loaded: Book = read_checkpoint(f, resume_path).map_err(|e| {
                     CliRunError::InvalidInput(format!(
                         "Invalid resume file {}: {}",
                         resume_path.display(),
@@ -361,12 +1827,10 @@ pub fn run(args: &Args) -> Result<(), CliRunError> {
                     ))
                 })?;
                 if let Some(ref surl) = loaded.source_url {
-                    let a = surl.trim_end_matches('/');
-                    let b = args.url.trim_end_matches('/');
-                    if a != b {
+                    if !same_story_url(site, surl, url) {
                         return Err(CliRunError::InvalidInput(format!(
                             "Resume file is for a different URL ({}). Use the same URL as the original run ({}).",
-                            surl, args.url
+                            surl, url
                         )));
                     }
                 }
@@ -386,13 +1850,11 @@ pub fn run(args: &Args) -> Result<(), CliRunError> {
     };
     let initial_book_ref = initial_book.as_ref();
 
-    let resume_path = args.resume.clone();
-    let checkpoint_cb = |book: &Book| {
+    // --update saves progress to the book it updates, so an interrupted update can be rerun.
+    let resume_path = args.resume.clone().or_else(|| args.update.clone());
+    let write_checkpoint = |book: &Book| {
         if let Some(ref path) = resume_path {
-            if let Err(e) = std::fs::File::create(path).and_then(|f| {
-                serde_json::to_writer(f, book)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
-            }) {
+            if let Err(e) = write_checkpoint_file(path, book) {
                 eprintln!(
                     "Warning: could not write resume file {}: {}",
                     path.display(),
@@ -401,11 +1863,38 @@ pub fn run(args: &Args) -> Result<(), CliRunError> {
             }
         }
     };
-    let on_checkpoint: Option<&dyn Fn(&Book)> = if args.resume.is_some() {
+    // Chapters since the last write are flushed once the scrape finishes or stops.
+    let checkpoints_seen = Cell::new(0u32);
+    let unsaved_checkpoint = Cell::new(false);
+    let checkpoint_cb = |book: &Book| {
+        let n = checkpoints_seen.get() + 1;
+        checkpoints_seen.set(n);
+        let due = n.is_multiple_of(args.checkpoint_interval);
+        if due {
+            write_checkpoint(book);
+        }
+        unsaved_checkpoint.set(!due);
+    };
+    let on_checkpoint: Option<&dyn Fn(&Book)> = if resume_path.is_some() {
         Some(&checkpoint_cb)
     } else {
         None
     };
+    // --partial-ok keeps the book handed over by a stopped scrape so it can still be written.
+    let partial_book: RefCell<Option<Book>> = RefCell::new(None);
+    let stop_cb = |book: &Book| {
+        if unsaved_checkpoint.replace(false) {
+            write_checkpoint(book);
+        }
+        if args.partial_ok {
+            *partial_book.borrow_mut() = Some(book.clone());
+        }
+    };
+    let on_stop: Option<&dyn Fn(&Book)> = if resume_path.is_some() || args.partial_ok {
+        Some(&stop_cb)
+    } else {
+        None
+    };
 
     let empty_chapter_behavior = args
         .empty_chapters
@@ -417,94 +1906,477 @@ pub fn run(args: &Args) -> Result<(), CliRunError> {
         })
         .unwrap_or(EmptyChapterBehavior::Skip);
 
-    if args.dry_run {
-        let dry_run_opts = ScrapeOptions {
-            progress: None,
-            chapter_range: args.chapters,
-            initial_book: None,
-            on_checkpoint: None,
-            locked_behavior: Some(args.locked_chapters),
-            empty_chapter_behavior: Some(empty_chapter_behavior),
-            toc_only: true,
-        };
-        let book = scrape_book(site, &args.url, &mut client, &dry_run_opts)?;
-        let output_path = match &args.output {
-            Some(p) => p.clone(),
-            None => {
-                let base = sanitize_title(&book.title);
-                let ext = extension_for_format(args.format);
-                effective_output_dir.join(format!("{}.{}", base, ext))
-            }
-        };
-        eprintln!("Chapters: {}", book.chapters.len());
-        eprintln!("Output: {}", output_path.display());
-        return Ok(());
-    }
+    let scribblehub_toc_mode = args
+        .scribblehub_toc
+        .or_else(|| {
+            config
+                .as_ref()
+                .and_then(|c| c.scribblehub_toc_mode.as_deref())
+                .and_then(|s| parse_scribblehub_toc_mode(s).ok())
+        })
+        .unwrap_or_default();
 
-    let scrape_opts = ScrapeOptions {
-        progress,
-        chapter_range: args.chapters,
-        initial_book: initial_book_ref,
-        on_checkpoint,
-        locked_behavior: Some(args.locked_chapters),
-        empty_chapter_behavior: Some(empty_chapter_behavior),
-        toc_only: false,
+    let include_chapters = match args.include_chapters {
+        Some(ref path) => {
+            let text = std::fs::read_to_string(path).map_err(|e| {
+                CliRunError::InvalidInput(format!(
+                    "Cannot read --include-chapters file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            let allow = parse_chapter_allowlist(&text).map_err(|e| {
+                CliRunError::InvalidInput(format!("--include-chapters {}: {}", path.display(), e))
+            })?;
+            Some(allow)
+        }
+        None => None,
     };
-    let book = scrape_book(site, &args.url, &mut client, &scrape_opts)?;
-
-    if let Some(pb) = progress_state.borrow_mut().take() {
-        pb.disable_steady_tick();
-        pb.finish_and_clear();
-    }
 
-    let output_path = match &args.output {
-        Some(p) => p.clone(),
-        None => {
-            let base = sanitize_title(&book.title);
-            let ext = extension_for_format(args.format);
-            effective_output_dir.join(format!("{}.{}", base, ext))
+    let index_collision = args
+        .index_collision
+        .or_else(|| {
+            args.update
+                .is_some()
+                .then_some(IndexCollisionBehavior::PreferNew)
+        })
+        .or_else(|| {
+            config
+                .as_ref()
+                .and_then(|c| c.index_collision.as_deref())
+                .and_then(|s| parse_index_collision(s).ok())
+        })
+        .unwrap_or_default();
+
+    let toc_cache = args
+        .toc_cache_ttl
+        .zip(args.resume.as_deref().or(args.update.as_deref()))
+        .map(|(ttl, saved)| (ttl, toc_cache_path(saved)));
+    let cached_toc = toc_cache
+        .as_ref()
+        .and_then(|(ttl, path)| load_toc_cache(path, site, url, *ttl, unix_now()));
+    if let (Some((_, path)), Some(_)) = (&toc_cache, &cached_toc) {
+        if !args.quiet && initial_book.is_some() && !args.stats {
+            eprintln!("Using cached TOC from {}.", path.display());
+        }
+    }
+    let save_toc = |entries: &[TocEntry]| {
+        if let Some((_, ref path)) = toc_cache {
+            if let Err(e) = write_toc_cache(path, url, entries, unix_now()) {
+                eprintln!(
+                    "Warning: could not write TOC cache {}: {}",
+                    path.display(),
+                    e
+                );
+            }
         }
     };
+    // --update keeps the TOC's chapter URLs to find chapters the site no longer lists. A reused
+    // cached TOC is never reported through on_toc, so it seeds them up front.
+    let update_toc_urls = |entries: &[TocEntry]| -> Option<HashSet<String>> {
+        args.update.is_some().then(|| {
+            entries
+                .iter()
+                .map(|e| comparable_url(site, &e.url))
+                .collect()
+        })
+    };
+    let toc_urls: RefCell<Option<HashSet<String>>> = RefCell::new(
+        cached_toc
+            .as_deref()
+            .filter(|_| initial_book.is_some() && !args.stats)
+            .and_then(update_toc_urls),
+    );
+    let handle_toc = |entries: &[TocEntry]| {
+        save_toc(entries);
+        if let Some(urls) = update_toc_urls(entries) {
+            *toc_urls.borrow_mut() = Some(urls);
+        }
+    };
+    let on_toc: Option<TocCallback> = if toc_cache.is_some() || args.update.is_some() {
+        Some(&handle_toc)
+    } else {
+        None
+    };
 
-    validate_output_path(&output_path)?;
-
-    match args.format {
-        OutputFormat::Json => {
-            let f = std::fs::File::create(&output_path).map_err(|e| {
-                CliRunError::Epub(EpubError::CreateFile {
-                    path: output_path.clone(),
-                    source: e,
-                })
+    if args.dry_run || args.list_chapters {
+        let toc_locked: RefCell<HashSet<u32>> = RefCell::new(HashSet::new());
+        let count_locked = |entries: &[TocEntry]| {
+            *toc_locked.borrow_mut() = entries
+                .iter()
+                .filter(|e| !e.unlocked)
+                .map(|e| e.index)
+                .collect();
+        };
+        // Listing shows locked chapters too, as placeholders, whatever --locked-chapters says.
+        let locked_behavior = if args.list_chapters {
+            LockedChapterBehavior::Placeholder
+        } else {
+            args.locked_chapters
+        };
+        let dry_run_opts = ScrapeOptions {
+            progress: None,
+            chapter_range: args.chapters.clone(),
+            initial_book: None,
+            on_checkpoint: None,
+            on_stop: None,
+            locked_behavior: Some(locked_behavior),
+            empty_chapter_behavior: Some(empty_chapter_behavior),
+            toc_only: true,
+            cancel_check: None,
+            allow_missing_metadata: args.allow_missing_metadata,
+            title_from_url: args.title_from_url,
+            unlocked_first: args.unlocked_first,
+            single_chapter: args.single_chapter,
+            chapter_source: args.source,
+            min_chapter_words: args.min_chapter_words,
+            allowed_tags: allowed_tags.clone(),
+            scribblehub_toc_mode,
+            index_collision,
+            title_exclude: args.title_exclude.clone(),
+            include_chapters: include_chapters.clone(),
+            chapter_parse_retries,
+            toc_sort: args.toc_sort,
+            fail_fast: args.fail_fast,
+            stats: args.stats,
+            extra_hosts,
+            strict: args.strict,
+            transform_body: None,
+            cached_toc: None,
+            on_toc: Some(&count_locked),
+            concurrency: args.concurrency,
+            hidden_classes: hidden_classes.clone(),
+        };
+        let book = scrape_book(site, url, &mut client, &dry_run_opts)?;
+        if args.list_chapters {
+            let chapters = listed_chapters(&book, &toc_locked.borrow());
+            if args.format == OutputFormat::Json {
+                let json = serde_json::to_string_pretty(&chapters).map_err(|e| {
+                    CliRunError::InvalidInput(format!("Failed to print chapter list: {}", e))
+                })?;
+                println!("{}", json);
+            } else {
+                print!("{}", chapter_list_text(&chapters));
+            }
+            return Ok(());
+        }
+        let output_path = match &args.output {
+            Some(p) => p.clone(),
+            None => {
+                let base = sanitize_title(&book.title);
+                let ext = extension_for_format(args.format);
+                effective_output_dir.join(format!("{}.{}", base, ext))
+            }
+        };
+        let missing_dir = output_path
+            .parent()
+            .filter(|p| args.mkdirs && !p.as_os_str().is_empty() && !p.exists());
+        if let Some(dir) = missing_dir {
+            eprintln!("Directory {} will be created.", dir.display());
+        } else {
+            validate_output_path(&output_path, false)?;
+            probe_output_writable(&output_path)?;
+        }
+        let outputs: Vec<PlannedOutput> = match args.split_on_title {
+            Some(ref pattern) => {
+                let parts = book.split_on_title(pattern);
+                let paths = split_output_paths(&parts, &effective_output_dir, args.format);
+                parts
+                    .iter()
+                    .zip(paths)
+                    .map(|(part, path)| PlannedOutput {
+                        path,
+                        chapters: part.chapters.len(),
+                    })
+                    .collect()
+            }
+            None => vec![PlannedOutput {
+                path: output_path,
+                chapters: book.chapters.len(),
+            }],
+        };
+        if args.format == OutputFormat::Json {
+            let plan = DryRunPlan {
+                site: site_flag_name(site),
+                url: url.to_string(),
+                title: book.title.clone(),
+                chapters: book.chapters.len(),
+                locked_chapters: toc_locked.borrow().len(),
+                outputs,
+                settings: effective,
+            };
+            let json = serde_json::to_string_pretty(&plan).map_err(|e| {
+                CliRunError::InvalidInput(format!("Failed to print dry-run plan: {}", e))
             })?;
-            serde_json::to_writer(f, &book)
-                .map_err(|e| CliRunError::InvalidInput(format!("Failed to write JSON: {}", e)))?;
+            println!("{}", json);
+            return Ok(());
         }
-        OutputFormat::Epub => {
-            let version = if args.epub_2 {
-                EpubVersion::Epub2
+        eprintln!("Chapters: {}", book.chapters.len());
+        for output in &outputs {
+            if args.split_on_title.is_some() {
+                eprintln!(
+                    "Output: {} ({} chapters)",
+                    output.path.display(),
+                    output.chapters
+                );
             } else {
-                EpubVersion::Epub3
+                eprintln!("Output: {}", output.path.display());
+            }
+        }
+        return Ok(());
+    }
+
+    let started = Instant::now();
+    let deadline_hit = Cell::new(false);
+    let should_stop = || {
+        let hit = args
+            .deadline
+            .is_some_and(|secs| started.elapsed() >= Duration::from_secs(secs));
+        deadline_hit.set(hit);
+        hit || INTERRUPTED.load(Ordering::SeqCst)
+    };
+    install_interrupt_handler();
+    let cancel_check: Option<&dyn Fn() -> bool> = Some(&should_stop);
+    let chapters_done = Cell::new(0u32);
+    let counting_progress = |n: u32, total: u32, title: &str| {
+        chapters_done.set(n);
+        if let Some(p) = progress {
+            p(n, total, title);
+        }
+    };
+
+    let scrape_opts = ScrapeOptions {
+        progress: Some(&counting_progress),
+        chapter_range: args.chapters.clone(),
+        initial_book: initial_book_ref,
+        on_checkpoint,
+        on_stop,
+        locked_behavior: Some(args.locked_chapters),
+        empty_chapter_behavior: Some(empty_chapter_behavior),
+        toc_only: false,
+        cancel_check,
+        allow_missing_metadata: args.allow_missing_metadata,
+        title_from_url: args.title_from_url,
+        unlocked_first: args.unlocked_first,
+        single_chapter: args.single_chapter,
+        chapter_source: args.source,
+        min_chapter_words: args.min_chapter_words,
+        allowed_tags: allowed_tags.clone(),
+        scribblehub_toc_mode,
+        index_collision,
+        title_exclude: args.title_exclude.clone(),
+        include_chapters: include_chapters.clone(),
+        chapter_parse_retries,
+        toc_sort: args.toc_sort,
+        fail_fast: args.fail_fast,
+        stats: args.stats,
+        extra_hosts,
+        strict: args.strict,
+        transform_body: None,
+        cached_toc: cached_toc.as_deref(),
+        on_toc,
+        concurrency: args.concurrency,
+        hidden_classes,
+    };
+    SCRAPING.store(true, Ordering::SeqCst);
+    let scraped = match args.resume {
+        Some(ref path) if args.no_fetch => checkpoint_book(initial_book_ref, path).map(Ok),
+        _ => Ok(scrape_book(site, url, &mut client, &scrape_opts)),
+    };
+    SCRAPING.store(false, Ordering::SeqCst);
+    let scraped = scraped?;
+    if let Ok(ref book) = scraped {
+        if unsaved_checkpoint.replace(false) {
+            write_checkpoint(book);
+        }
+    }
+
+    if let Some(pb) = progress_state.borrow_mut().take() {
+        pb.disable_steady_tick();
+        pb.finish_and_clear();
+    }
+    if let Some(advice) = rate_limit_advice(client.rate_limited_responses(), delay_secs) {
+        eprintln!("{}", advice);
+    }
+
+    // --update reports (or with --prune-deleted drops) chapters the site no longer lists and saves
+    // the result to PATH, for a partial (--partial-ok) book as well as a finished one.
+    let finish_if_update = |book: Book| match (&args.update, initial_book_ref) {
+        (Some(path), Some(previous)) => finish_update(
+            book,
+            previous,
+            toc_urls.take(),
+            site,
+            args.prune_deleted,
+            path,
+        ),
+        _ => Ok(book),
+    };
+    let book = match scraped {
+        Err(ScraperError::Cancelled) => {
+            let hint = match resume_path {
+                Some(ref path) => format!(
+                    " Progress is saved in {}; rerun the same command to continue.",
+                    path.display()
+                ),
+                None => " Use --resume <file> to keep progress across runs.".to_string(),
             };
-            let include_toc_page = config.as_ref().and_then(|c| c.toc_page).unwrap_or(true);
-            write_epub(
-                &book,
-                &output_path,
-                version,
-                args.ncx,
-                include_toc_page,
-                &mut client,
-            )?;
-            if args.validate {
-                validate_epub(&output_path)?;
+            let err = if deadline_hit.get() {
+                CliRunError::DeadlineReached {
+                    secs: args.deadline.unwrap_or_default(),
+                    done: chapters_done.get(),
+                    hint,
+                }
+            } else {
+                CliRunError::Interrupted {
+                    done: chapters_done.get(),
+                    hint,
+                }
+            };
+            if args.partial_ok {
+                let partial = partial_book
+                    .take()
+                    .or_else(|| initial_book.clone())
+                    .filter(|b| !b.chapters.is_empty());
+                match partial {
+                    Some(book) => {
+                        let book = finish_if_update(book)?;
+                        eprintln!(
+                            "Writing the {} chapter(s) fetched so far (--partial-ok).",
+                            book.chapters.len()
+                        );
+                        write_outputs(
+                            book,
+                            args,
+                            config.as_ref(),
+                            css.as_deref(),
+                            &effective_output_dir,
+                            &mut client,
+                            run_started,
+                        )?;
+                    }
+                    None => eprintln!("--partial-ok: no chapters fetched yet; nothing written."),
+                }
             }
+            return Err(err);
+        }
+        other => other?,
+    };
+    let book = finish_if_update(book)?;
+    write_outputs(
+        book,
+        args,
+        config.as_ref(),
+        css.as_deref(),
+        &effective_output_dir,
+        &mut client,
+        run_started,
+    )
+}
+
+/// Apply the metadata flags to a finished book and write every requested output (the book or its
+/// `--split-on-title` parts, then `--index`, `--opds-entry` and `--bundle`).
+fn write_outputs(
+    mut book: Book,
+    args: &Args,
+    config: Option<&config::Config>,
+    css: Option<&str>,
+    effective_output_dir: &Path,
+    client: &mut PoliteClient,
+    run_started: Instant,
+) -> Result<(), CliRunError> {
+    apply_description_override(&mut book, args.description.as_deref(), args.no_description);
+    if args.title_with_author {
+        // A --from-json book combined before keeps its scraped title in original_title.
+        let raw = book
+            .original_title
+            .take()
+            .unwrap_or_else(|| book.title.clone());
+        book.title = title_with_author(&raw, &book.author);
+        book.original_title = Some(raw).filter(|raw| *raw != book.title);
+    }
+    // --language, then the language the site reports (AO3), then config.
+    book.language = args
+        .language
+        .clone()
+        .or(book.language)
+        .or_else(|| config.and_then(|c| c.language.clone()));
+    if !args.contributors.is_empty() {
+        book.contributors = args.contributors.clone();
+    }
+    if let Some(series) = &args.series {
+        book.series = Some(series.clone());
+        book.series_index = args.series_index;
+    }
+    if args.drop_nav_artifacts {
+        for ch in drop_nav_artifacts(&mut book) {
+            eprintln!(
+                "Warning: dropped chapter {} ({:?}): looks like navigation text, not content.",
+                ch.index, ch.title
+            );
+        }
+    }
+
+    let mut bytes_written = 0u64;
+    let mut report_written = |path: &Path| {
+        let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        bytes_written += size;
+        if !args.quiet {
+            eprintln!("Wrote {} ({})", path.display(), human_size(size));
+        }
+    };
+
+    let outputs: Vec<(Book, PathBuf)> = match args.split_on_title {
+        Some(ref pattern) => {
+            let parts = book.split_on_title(pattern);
+            let paths = split_output_paths(&parts, effective_output_dir, args.format);
+            parts.into_iter().zip(paths).collect()
+        }
+        None => {
+            let output_path = match &args.output {
+                Some(p) => p.clone(),
+                None => {
+                    let base = sanitize_title(&book.title);
+                    let ext = extension_for_format(args.format);
+                    effective_output_dir.join(format!("{}.{}", base, ext))
+                }
+            };
+            vec![(book, output_path)]
         }
-        OutputFormat::Html => write_html(&book, &output_path)?,
-        OutputFormat::Markdown => write_markdown(&book, &output_path)?,
-        OutputFormat::Text => write_text(&book, &output_path)?,
+    };
+    for (book, output_path) in &outputs {
+        validate_output_path(output_path, args.mkdirs)?;
+        write_output(book, output_path, args, config, css, client)?;
+        report_written(output_path);
+    }
+    // --index, --opds-entry and --bundle conflict with --split-on-title: one output from here on.
+    let (book, output_path) = &outputs[0];
+
+    if let Some(ref index_path) = args.index {
+        validate_output_path(index_path, args.mkdirs)?;
+        write_index(book, index_path)?;
+        report_written(index_path);
+    }
+
+    if let Some(ref entry_path) = args.opds_entry {
+        validate_output_path(entry_path, args.mkdirs)?;
+        write_opds_entry(book, &opds_href(output_path, entry_path), entry_path)?;
+        report_written(entry_path);
+    }
+
+    if let Some(ref bundle_path) = args.bundle {
+        validate_output_path(bundle_path, args.mkdirs)?;
+        let entries = bundle_entries(book, output_path, args.format)?;
+        write_bundle(bundle_path, &entries)?;
+        report_written(bundle_path);
     }
 
     if !args.quiet {
-        eprintln!("Wrote {}", output_path.display());
+        eprintln!(
+            "Done in {}: {} written.",
+            human_duration(run_started.elapsed()),
+            human_size(bytes_written)
+        );
     }
     Ok(())
 }
@@ -534,26 +2406,149 @@ mod tests {
     }
 
     #[test]
-    fn parse_chapter_range_valid() {
-        assert_eq!(parse_chapter_range("1-10").unwrap(), (1, 10));
-        assert_eq!(parse_chapter_range("5-5").unwrap(), (5, 5));
-        assert_eq!(parse_chapter_range("  3 - 7  ").unwrap(), (3, 7));
+    fn parse_chapter_selection_valid() {
+        let ranges = |s: &str| parse_chapter_selection(s).unwrap().ranges;
+        assert_eq!(ranges("1-10"), vec![(1, 10)]);
+        assert_eq!(ranges("5-5"), vec![(5, 5)]);
+        assert_eq!(ranges("  3 - 7  "), vec![(3, 7)]);
+    }
+
+    #[test]
+    fn parse_chapter_selection_open_ranges_and_single_chapters() {
+        let ranges = |s: &str| parse_chapter_selection(s).unwrap().ranges;
+        assert_eq!(ranges("5-"), vec![(5, u32::MAX)]);
+        assert_eq!(ranges("-10"), vec![(1, 10)]);
+        assert_eq!(ranges("7"), vec![(7, 7)]);
+        assert_eq!(ranges("1-3,7,20-"), vec![(1, 3), (7, 7), (20, u32::MAX)]);
+        assert_eq!(ranges(" 1 - 3 , 7 "), vec![(1, 3), (7, 7)]);
+        let selection = parse_chapter_selection("1-3,7,20-").unwrap();
+        assert!(selection.contains(2) && selection.contains(7) && selection.contains(500));
+        assert!(!selection.contains(4) && !selection.contains(19));
+    }
+
+    #[test]
+    fn parse_chapter_allowlist_indices_urls_and_comments() {
+        let allow = parse_chapter_allowlist(
+            "# picks\n3\n\n  12  \nhttps://www.royalroad.com/fiction/1/x/chapter/9/nine/\n",
+        )
+        .unwrap();
+        assert_eq!(allow.indices.into_iter().collect::<Vec<_>>(), vec![3, 12]);
+        assert!(allow
+            .urls
+            .contains("https://www.royalroad.com/fiction/1/x/chapter/9/nine"));
+        let err = parse_chapter_allowlist("1\nchapter two\n").unwrap_err();
+        assert!(err.contains("line 2"));
+    }
+
+    #[test]
+    fn include_chapters_conflicts_with_chapters() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        let both = [
+            "rdrscrape",
+            url,
+            "--include-chapters",
+            "picks.txt",
+            "--chapters",
+            "1-3",
+        ];
+        assert!(Args::try_parse_from(both).is_err());
+    }
+
+    #[test]
+    fn checkpoint_interval_requires_resume_and_is_positive() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        let args = Args::try_parse_from(["rdrscrape", url, "--resume", "b.json"]).unwrap();
+        assert_eq!(args.checkpoint_interval, 1);
+        let every_25 = [
+            "rdrscrape",
+            url,
+            "--resume",
+            "b.json",
+            "--checkpoint-interval",
+            "25",
+        ];
+        assert_eq!(
+            Args::try_parse_from(every_25).unwrap().checkpoint_interval,
+            25
+        );
+        let zero = [
+            "rdrscrape",
+            url,
+            "--resume",
+            "b.json",
+            "--checkpoint-interval",
+            "0",
+        ];
+        assert!(Args::try_parse_from(zero).is_err());
+        let no_resume = ["rdrscrape", url, "--checkpoint-interval", "5"];
+        assert!(Args::try_parse_from(no_resume).is_err());
+    }
+
+    #[test]
+    fn split_on_title_conflicts_with_single_output_flags() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        let split = ["rdrscrape", url, "--split-on-title", "^Story:"];
+        assert!(Args::try_parse_from(split).is_ok());
+        for flag in ["--output", "--index", "--opds-entry", "--bundle"] {
+            let args = ["rdrscrape", url, "--split-on-title", "^Story:", flag, "out"];
+            assert!(Args::try_parse_from(args).is_err(), "{}", flag);
+        }
+        assert!(parse_split_on_title("(unclosed").is_err());
+    }
+
+    #[test]
+    fn split_output_paths_dedupe_sanitized_names() {
+        let part = |title: &str| Book {
+            title: title.to_string(),
+            author: "A".to_string(),
+            ..Default::default()
+        };
+        let parts = [part("Story: Ashes"), part("Story - Ashes"), part("Coda")];
+        let paths = split_output_paths(&parts, Path::new("out"), OutputFormat::Epub);
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("out/story-ashes.epub"),
+                PathBuf::from("out/story-ashes-2.epub"),
+                PathBuf::from("out/coda.epub"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_title_exclude_valid_and_invalid() {
+        assert!(parse_title_exclude("(?i)author'?s note")
+            .unwrap()
+            .is_match("Author's Note"));
+        assert!(parse_title_exclude("(unclosed").is_err());
     }
 
     #[test]
-    fn parse_chapter_range_rejects_no_dash() {
-        assert!(parse_chapter_range("1").is_err());
+    fn chapters_flag_accepts_leading_dash_range() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        let args = Args::try_parse_from(["rdrscrape", url, "--chapters", "-10"]).unwrap();
+        assert_eq!(args.chapters.unwrap().ranges, vec![(1, 10)]);
     }
 
     #[test]
-    fn parse_chapter_range_rejects_non_numeric() {
-        assert!(parse_chapter_range("a-b").is_err());
-        assert!(parse_chapter_range("1-b").is_err());
+    fn parse_chapter_selection_rejects_non_numeric() {
+        for bad in ["a-b", "1-b", "x", "1-3,foo", "1.5", "-1-", "3--5"] {
+            assert!(parse_chapter_selection(bad).is_err(), "{}", bad);
+        }
     }
 
     #[test]
-    fn parse_chapter_range_rejects_from_gt_to() {
-        assert!(parse_chapter_range("10-1").is_err());
+    fn parse_chapter_selection_rejects_from_gt_to() {
+        assert!(parse_chapter_selection("10-1").is_err());
+        let err = parse_chapter_selection("1-2,3-1").unwrap_err();
+        assert!(err.contains("start (3) must be <= end (1)"), "{}", err);
+    }
+
+    #[test]
+    fn parse_chapter_selection_rejects_empty_entries() {
+        for bad in ["", " ", "-", "1-3,", ",7", "1,,2"] {
+            assert!(parse_chapter_selection(bad).is_err(), "{:?}", bad);
+        }
     }
 
     #[test]
@@ -576,6 +2571,7 @@ mod tests {
     fn parse_site_scribblehub() {
         assert_eq!(parse_site("scribblehub").unwrap(), Site::ScribbleHub);
         assert_eq!(parse_site("sh").unwrap(), Site::ScribbleHub);
+        assert_eq!(parse_site("AO3").unwrap(), Site::Ao3);
     }
 
     #[test]
@@ -592,12 +2588,224 @@ mod tests {
         assert_eq!(parse_format("md").unwrap(), OutputFormat::Markdown);
         assert_eq!(parse_format("text").unwrap(), OutputFormat::Text);
         assert_eq!(parse_format("txt").unwrap(), OutputFormat::Text);
+        assert_eq!(parse_format("pdf").unwrap(), OutputFormat::Pdf);
+        assert_eq!(parse_format("FB2").unwrap(), OutputFormat::Fb2);
         assert_eq!(parse_format("EPUB").unwrap(), OutputFormat::Epub);
     }
 
+    #[test]
+    fn parse_config_format_all() {
+        assert_eq!(parse_config_format("toml").unwrap(), ConfigFormat::Toml);
+        assert_eq!(parse_config_format("JSON").unwrap(), ConfigFormat::Json);
+        assert!(parse_config_format("yaml").is_err());
+    }
+
+    fn sample_effective_config() -> EffectiveConfig {
+        EffectiveConfig {
+            site: Some("royalroad"),
+            format: "epub",
+            output_dir: PathBuf::from("out"),
+            request_delay_secs: 2,
+            timeout_secs: 30,
+            retry_count: 3,
+            retry_backoff_secs: vec![1, 2],
+            max_total_backoff_secs: None,
+            chapter_parse_retries: 2,
+            user_agent: "UA/1.0".to_string(),
+            allowed_block_tags: vec!["p".to_string()],
+            allowed_inline_tags: vec![],
+        }
+    }
+
+    #[test]
+    fn effective_config_renders_as_config_keys() {
+        let effective = sample_effective_config();
+        let toml_out = effective.render(ConfigFormat::Toml).unwrap();
+        assert!(toml_out.contains("request_delay_secs = 2"), "{}", toml_out);
+        assert!(toml_out.contains("user_agent = \"UA/1.0\""), "{}", toml_out);
+        assert!(!toml_out.contains("max_total_backoff_secs"));
+        let parsed: crate::config::Config = toml::from_str(&toml_out).unwrap();
+        assert_eq!(parsed.timeout_secs, Some(30));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&effective.render(ConfigFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["retry_backoff_secs"], serde_json::json!([1, 2]));
+        assert_eq!(json["site"].as_str(), Some("royalroad"));
+    }
+
+    #[test]
+    fn dry_run_plan_serializes_outputs_and_settings() {
+        let plan = DryRunPlan {
+            site: "royalroad",
+            url: "https://www.royalroad.com/fiction/1/x".to_string(),
+            title: "X".to_string(),
+            chapters: 12,
+            locked_chapters: 3,
+            outputs: vec![
+                PlannedOutput {
+                    path: PathBuf::from("out/Book 1.epub"),
+                    chapters: 5,
+                },
+                PlannedOutput {
+                    path: PathBuf::from("out/Book 2.epub"),
+                    chapters: 7,
+                },
+            ],
+            settings: sample_effective_config(),
+        };
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["chapters"], 12);
+        assert_eq!(json["locked_chapters"], 3);
+        assert_eq!(json["outputs"][1]["path"], "out/Book 2.epub");
+        assert_eq!(json["outputs"][1]["chapters"], 7);
+        assert_eq!(json["settings"]["request_delay_secs"], 2);
+        assert_eq!(json["settings"]["user_agent"], "UA/1.0");
+    }
+
     #[test]
     fn parse_format_invalid() {
-        assert!(parse_format("pdf").is_err());
+        assert!(parse_format("docx").is_err());
+    }
+
+    #[test]
+    fn parse_error_format_all() {
+        assert_eq!(parse_error_format("human").unwrap(), ErrorFormat::Human);
+        assert_eq!(parse_error_format("JSON").unwrap(), ErrorFormat::Json);
+        assert!(parse_error_format("xml").is_err());
+    }
+
+    #[test]
+    fn parse_chapter_file_names_all() {
+        assert_eq!(
+            parse_chapter_file_names("position").unwrap(),
+            ChapterFileNames::Position
+        );
+        assert_eq!(
+            parse_chapter_file_names("Index").unwrap(),
+            ChapterFileNames::Index
+        );
+        assert!(parse_chapter_file_names("title").is_err());
+    }
+
+    #[test]
+    fn parse_index_collision_all() {
+        assert_eq!(
+            parse_index_collision("keep-existing").unwrap(),
+            IndexCollisionBehavior::KeepExisting
+        );
+        assert_eq!(
+            parse_index_collision("Prefer-New").unwrap(),
+            IndexCollisionBehavior::PreferNew
+        );
+        assert_eq!(
+            parse_index_collision("fail").unwrap(),
+            IndexCollisionBehavior::Fail
+        );
+        assert!(parse_index_collision("skip").is_err());
+    }
+
+    #[test]
+    fn parse_scribblehub_toc_mode_all() {
+        assert_eq!(
+            parse_scribblehub_toc_mode("auto").unwrap(),
+            ScribbleHubTocMode::Auto
+        );
+        assert_eq!(
+            parse_scribblehub_toc_mode("AJAX").unwrap(),
+            ScribbleHubTocMode::Ajax
+        );
+        assert_eq!(
+            parse_scribblehub_toc_mode("paginate").unwrap(),
+            ScribbleHubTocMode::Paginate
+        );
+        assert!(parse_scribblehub_toc_mode("scroll").is_err());
+    }
+
+    #[test]
+    fn parse_toc_sort_all() {
+        assert_eq!(parse_toc_sort("order").unwrap(), TocSort::Order);
+        assert_eq!(parse_toc_sort("Number").unwrap(), TocSort::Number);
+        assert!(parse_toc_sort("desc").is_err());
+    }
+
+    #[test]
+    fn parse_chapter_source_all() {
+        assert_eq!(parse_chapter_source("html").unwrap(), ChapterSource::Html);
+        assert_eq!(parse_chapter_source("API").unwrap(), ChapterSource::Api);
+        assert!(parse_chapter_source("rss").is_err());
+    }
+
+    #[test]
+    fn parse_progress_mode_all() {
+        assert_eq!(parse_progress_mode("bar").unwrap(), ProgressMode::Bar);
+        assert_eq!(parse_progress_mode("Plain").unwrap(), ProgressMode::Plain);
+        assert_eq!(parse_progress_mode("json").unwrap(), ProgressMode::Json);
+        assert_eq!(parse_progress_mode("none").unwrap(), ProgressMode::None);
+        assert!(parse_progress_mode("dots").is_err());
+    }
+
+    #[test]
+    fn effective_progress_mode_falls_back_without_terminal() {
+        assert_eq!(
+            effective_progress_mode(ProgressMode::Bar, false, true),
+            ProgressMode::Bar
+        );
+        assert_eq!(
+            effective_progress_mode(ProgressMode::Bar, false, false),
+            ProgressMode::Plain
+        );
+        assert_eq!(
+            effective_progress_mode(ProgressMode::Json, false, false),
+            ProgressMode::Json
+        );
+        assert_eq!(
+            effective_progress_mode(ProgressMode::Plain, true, true),
+            ProgressMode::None
+        );
+    }
+
+    #[test]
+    fn bar_chars_ascii_without_color() {
+        let (ticks, bar) = bar_chars(true);
+        assert!(ticks.is_ascii() && bar.is_ascii());
+        assert!(!bar_chars(false).0.is_ascii());
+    }
+
+    #[test]
+    fn progress_line_plain_and_json() {
+        assert_eq!(
+            progress_line(ProgressMode::Plain, 2, 10, "Two").as_deref(),
+            Some("Chapter 2/10: Two")
+        );
+        let json = progress_line(ProgressMode::Json, 2, 10, "Say \"hi\"").unwrap();
+        let v: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(v["done"].as_u64(), Some(2));
+        assert_eq!(v["total"].as_u64(), Some(10));
+        assert_eq!(v["title"].as_str(), Some("Say \"hi\""));
+        assert!(progress_line(ProgressMode::Bar, 1, 1, "x").is_none());
+        assert!(progress_line(ProgressMode::None, 1, 1, "x").is_none());
+    }
+
+    #[test]
+    fn cli_run_error_json_has_code_kind_message_and_url() {
+        let err = CliRunError::Scraper(ScraperError::HttpStatus {
+            status: 404,
+            url: "https://www.royalroad.com/fiction/1".into(),
+            context: None,
+        });
+        let v = err.to_json();
+        assert_eq!(v["code"].as_i64(), Some(2));
+        assert_eq!(v["kind"].as_str(), Some("http_status"));
+        assert!(v["message"].as_str().unwrap().contains("HTTP 404"));
+        assert_eq!(
+            v["url"].as_str(),
+            Some("https://www.royalroad.com/fiction/1")
+        );
+
+        let v = CliRunError::InvalidInput("bad".into()).to_json();
+        assert_eq!(v["code"].as_i64(), Some(1));
+        assert_eq!(v["kind"].as_str(), Some("invalid_input"));
+        assert!(v.get("url").is_none());
     }
 
     #[test]
@@ -628,24 +2836,494 @@ mod tests {
         assert_eq!(extension_for_format(OutputFormat::Html), "html");
         assert_eq!(extension_for_format(OutputFormat::Markdown), "md");
         assert_eq!(extension_for_format(OutputFormat::Text), "txt");
+        assert_eq!(extension_for_format(OutputFormat::Pdf), "pdf");
+        assert_eq!(extension_for_format(OutputFormat::Fb2), "fb2");
+    }
+
+    #[test]
+    fn extension_mismatch_matching_extension() {
+        assert!(extension_mismatch(Path::new("book.epub"), OutputFormat::Epub).is_none());
+        assert!(extension_mismatch(Path::new("out/Book.EPUB"), OutputFormat::Epub).is_none());
+        assert!(extension_mismatch(Path::new("book.md"), OutputFormat::Markdown).is_none());
+    }
+
+    #[test]
+    fn extension_mismatch_wrong_or_missing_extension() {
+        let msg = extension_mismatch(Path::new("mybook.txt"), OutputFormat::Epub).unwrap();
+        assert!(msg.contains(".txt") && msg.contains(".epub"));
+        assert!(extension_mismatch(Path::new("mybook"), OutputFormat::Json).is_some());
     }
 
     #[test]
     fn validate_output_path_parent_exists() {
         let path = std::env::temp_dir().join("rdrscrape_cli_test_output.epub");
-        assert!(validate_output_path(&path).is_ok());
+        assert!(validate_output_path(&path, false).is_ok());
     }
 
     #[test]
     fn validate_output_path_parent_missing() {
         let path = PathBuf::from("/nonexistent_dir_rdrscrape_xyz/output.epub");
-        let result = validate_output_path(&path);
+        let result = validate_output_path(&path, false);
         assert!(result.is_err());
         if let Err(CliRunError::InvalidInput(msg)) = result {
             assert!(msg.contains("parent directory does not exist"));
         }
     }
 
+    #[test]
+    fn validate_output_path_mkdirs_creates_nested_parents() {
+        let root = std::env::temp_dir().join(format!("rdrscrape_mkdirs_{}", std::process::id()));
+        let path = root.join("by-author").join("someone").join("book.epub");
+        assert!(validate_output_path(&path, false).is_err());
+        assert!(validate_output_path(&path, true).is_ok());
+        assert!(path.parent().unwrap().is_dir());
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn probe_output_writable_leaves_no_file() {
+        let dir = std::env::temp_dir().join(format!("rdrscrape_probe_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(probe_output_writable(&dir.join("book.epub")).is_ok());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn rate_limit_advice_above_threshold() {
+        assert_eq!(rate_limit_advice(2, 2), None);
+        let advice = rate_limit_advice(3, 2).unwrap();
+        assert!(advice.contains("3 rate-limit responses"));
+        assert!(advice.contains("--delay 4"));
+        assert!(rate_limit_advice(5, 0).unwrap().contains("--delay 2"));
+    }
+
+    #[test]
+    fn human_size_and_duration_formats() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KB");
+        assert_eq!(human_size(5 * 1024 * 1024 / 2), "2.5 MB");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+        assert_eq!(human_duration(Duration::from_millis(4200)), "4.2s");
+        assert_eq!(human_duration(Duration::from_secs(185)), "3m 05s");
+        assert_eq!(human_duration(Duration::from_secs(3723)), "1h 02m 03s");
+    }
+
+    #[test]
+    fn opds_href_is_relative_to_entry_dir() {
+        assert_eq!(
+            opds_href(Path::new("lib/books/a.epub"), Path::new("lib/a.xml")),
+            "books/a.epub"
+        );
+        assert_eq!(opds_href(Path::new("a.epub"), Path::new("a.xml")), "a.epub");
+        assert_eq!(
+            opds_href(Path::new("out/b.epub"), Path::new("opds/e.xml")),
+            "../out/b.epub"
+        );
+        assert_eq!(
+            opds_href(
+                Path::new("./lib/../out/b.epub"),
+                Path::new("lib/feeds/e.xml")
+            ),
+            "../../out/b.epub"
+        );
+        assert_eq!(
+            opds_href(Path::new("/srv/a.epub"), Path::new("/feeds/a.xml")),
+            "../srv/a.epub"
+        );
+    }
+
+    #[test]
+    fn read_ca_cert_requires_pem() {
+        let path = std::env::temp_dir().join(format!("rdrscrape_ca_{}.pem", std::process::id()));
+        std::fs::write(&path, "not a certificate").unwrap();
+        assert!(matches!(
+            read_ca_cert(&path),
+            Err(CliRunError::InvalidInput(msg)) if msg.contains("not a PEM certificate")
+        ));
+        std::fs::write(
+            &path,
+            "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n",
+        )
+        .unwrap();
+        assert!(read_ca_cert(&path).is_ok());
+        std::fs::remove_file(&path).unwrap();
+        assert!(read_ca_cert(&path).is_err());
+    }
+
+    #[test]
+    fn read_css_requires_utf8_file() {
+        let path = std::env::temp_dir().join(format!("rdrscrape_css_{}.css", std::process::id()));
+        std::fs::write(&path, "p { margin: 0; }").unwrap();
+        assert_eq!(read_css(&path).unwrap(), "p { margin: 0; }");
+        std::fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+        assert!(matches!(
+            read_css(&path),
+            Err(CliRunError::InvalidInput(msg)) if msg.contains("Cannot read --css file")
+        ));
+        std::fs::remove_file(&path).unwrap();
+        assert!(read_css(&path).is_err());
+    }
+
+    #[test]
+    fn probe_output_writable_missing_dir() {
+        let path = PathBuf::from("/nonexistent_dir_rdrscrape_xyz/output.epub");
+        match probe_output_writable(&path) {
+            Err(CliRunError::InvalidInput(msg)) => assert!(msg.contains("not writable")),
+            other => panic!("expected InvalidInput, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn checkpoint_book_requires_complete_metadata_and_chapters() {
+        let path = Path::new("book.json");
+        let mut book = Book {
+            title: "T".to_string(),
+            author: "A".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            checkpoint_book(None, path),
+            Err(CliRunError::InvalidInput(msg)) if msg.contains("does not exist")
+        ));
+        assert!(matches!(
+            checkpoint_book(Some(&book), path),
+            Err(CliRunError::InvalidInput(msg)) if msg.contains("no chapters")
+        ));
+        book.chapters.push(crate::model::Chapter {
+            title: "One".to_string(),
+            index: 1,
+            body: "<p>x</p>".to_string(),
+            reported_word_count: None,
+            url: None,
+        });
+        assert_eq!(
+            checkpoint_book(Some(&book), path).unwrap().chapters.len(),
+            1
+        );
+    }
+
+    #[test]
+    fn checkpoint_round_trips_plain_and_gzip() {
+        let book = Book {
+            title: "T".to_string(),
+            author: "A".to_string(),
+            chapters: vec![crate::model::Chapter {
+                title: "One".to_string(),
+                index: 1,
+                body: "<p>".to_string() + &"word ".repeat(200) + "</p>",
+                reported_word_count: None,
+                url: None,
+            }],
+            source_url: Some("https://www.royalroad.com/fiction/1/x".to_string()),
+            ..Default::default()
+        };
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let plain = dir.join(format!("rdrscrape_checkpoint_{}.json", id));
+        let gz = dir.join(format!("rdrscrape_checkpoint_{}.json.gz", id));
+        for path in [&plain, &gz] {
+            write_checkpoint_file(path, &book).unwrap();
+            let loaded = read_checkpoint(std::fs::File::open(path).unwrap(), path).unwrap();
+            assert_eq!(loaded.content_hash(), book.content_hash());
+            assert_eq!(loaded.source_url, book.source_url);
+            assert!(!temp_path_for(path).exists());
+        }
+        // A failed write (here: the temporary file cannot be created) leaves the old checkpoint.
+        std::fs::create_dir(temp_path_for(&plain)).unwrap();
+        assert!(write_checkpoint_file(&plain, &Book::default()).is_err());
+        let kept = read_checkpoint(std::fs::File::open(&plain).unwrap(), &plain).unwrap();
+        assert_eq!(kept.content_hash(), book.content_hash());
+        let _ = std::fs::remove_dir(temp_path_for(&plain));
+        let gz_bytes = std::fs::read(&gz).unwrap();
+        assert_eq!(&gz_bytes[..2], &[0x1f, 0x8b]);
+        assert!(gz_bytes.len() < std::fs::metadata(&plain).unwrap().len() as usize);
+        let _ = std::fs::remove_file(&plain);
+        let _ = std::fs::remove_file(&gz);
+    }
+
+    #[test]
+    fn toc_cache_round_trip_respects_url_and_ttl() {
+        let resume =
+            std::env::temp_dir().join(format!("rdrscrape_toc_cache_{}.json", std::process::id()));
+        let path = toc_cache_path(&resume);
+        assert!(path.to_string_lossy().ends_with(".json.toc.json"));
+        let url = "https://www.royalroad.com/fiction/1/x";
+        let entries = vec![TocEntry {
+            index: 1,
+            url: format!("{}/chapter/10/one", url),
+            title: "One".to_string(),
+            unlocked: true,
+        }];
+        write_toc_cache(&path, url, &entries, 1_000).unwrap();
+        assert_eq!(
+            load_toc_cache(
+                &path,
+                Site::RoyalRoad,
+                "http://royalroad.com/fiction/1/x/?ref=home",
+                60,
+                1_060
+            ),
+            Some(entries)
+        );
+        assert_eq!(load_toc_cache(&path, Site::RoyalRoad, url, 60, 1_061), None);
+        assert_eq!(
+            load_toc_cache(
+                &path,
+                Site::RoyalRoad,
+                "https://www.royalroad.com/fiction/2/y",
+                60,
+                1_000
+            ),
+            None
+        );
+        std::fs::remove_file(&path).ok();
+        assert_eq!(load_toc_cache(&path, Site::RoyalRoad, url, 60, 1_000), None);
+    }
+
+    #[test]
+    fn toc_cache_ttl_requires_resume_or_update() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        assert!(Args::try_parse_from(["rdrscrape", url, "--toc-cache-ttl", "60"]).is_err());
+        for saved in ["--resume", "--update"] {
+            let args =
+                Args::try_parse_from(["rdrscrape", url, saved, "b.json", "--toc-cache-ttl", "60"])
+                    .unwrap();
+            assert_eq!(args.toc_cache_ttl, Some(60));
+        }
+    }
+
+    #[test]
+    fn from_json_replaces_url() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        let args = Args::try_parse_from(["rdrscrape", "--from-json", "book.json"]).unwrap();
+        assert_eq!(args.url, None);
+        assert!(Args::try_parse_from(["rdrscrape"]).is_err());
+        assert!(Args::try_parse_from(["rdrscrape", url, "--from-json", "book.json"]).is_err());
+        assert!(Args::try_parse_from([
+            "rdrscrape",
+            "--from-json",
+            "book.json",
+            "--resume",
+            "b.json"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn load_book_json_reads_saved_book_and_rejects_other_json() {
+        let dir = std::env::temp_dir();
+        let good = dir.join("rdrscrape_test_from_json.json");
+        std::fs::write(
+            &good,
+            r#"{"title":"T","author":"A","chapters":[{"title":"One","index":1,"body":"<p>x</p>"}],"contentHash":"0123456789abcdef"}"#,
+        )
+        .unwrap();
+        let book = load_book_json(&good).unwrap();
+        assert_eq!((book.title.as_str(), book.chapters.len()), ("T", 1));
+        let bad = dir.join("rdrscrape_test_from_json_bad.json");
+        std::fs::write(&bad, r#"{"name":"T"}"#).unwrap();
+        assert!(matches!(
+            load_book_json(&bad),
+            Err(CliRunError::InvalidInput(msg)) if msg.contains("is not a book")
+        ));
+        std::fs::remove_file(&good).ok();
+        std::fs::remove_file(&bad).ok();
+        assert!(matches!(
+            load_book_json(&good),
+            Err(CliRunError::InvalidInput(msg)) if msg.contains("Cannot read --from-json")
+        ));
+    }
+
+    #[test]
+    fn concurrency_defaults_to_one_and_is_bounded() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        assert_eq!(
+            Args::try_parse_from(["rdrscrape", url])
+                .unwrap()
+                .concurrency,
+            1
+        );
+        let args = Args::try_parse_from(["rdrscrape", url, "--concurrency", "4"]).unwrap();
+        assert_eq!(args.concurrency, 4);
+        assert!(Args::try_parse_from(["rdrscrape", url, "--concurrency", "0"]).is_err());
+        assert!(Args::try_parse_from(["rdrscrape", url, "--concurrency", "17"]).is_err());
+    }
+
+    #[test]
+    fn no_fetch_requires_resume() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        assert!(Args::try_parse_from(["rdrscrape", url, "--no-fetch"]).is_err());
+        assert!(
+            Args::try_parse_from(["rdrscrape", url, "--no-fetch", "--resume", "b.json"]).is_ok()
+        );
+    }
+
+    #[test]
+    fn update_conflicts_with_resume_and_from_json() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        assert!(Args::try_parse_from(["rdrscrape", url, "--update", "b.json"]).is_ok());
+        for other in [["--resume", "c.json"], ["--from-json", "c.json"]] {
+            let argv = ["rdrscrape", url, "--update", "b.json", other[0], other[1]];
+            assert!(Args::try_parse_from(argv).is_err(), "{:?}", other);
+        }
+        assert!(
+            Args::try_parse_from(["rdrscrape", url, "--update", "b.json", "--dry-run"]).is_err()
+        );
+        assert!(Args::try_parse_from(["rdrscrape", url, "--prune-deleted"]).is_err());
+        let args =
+            Args::try_parse_from(["rdrscrape", url, "--update", "b.json", "--prune-deleted"])
+                .unwrap();
+        assert!(args.prune_deleted);
+    }
+
+    #[test]
+    fn list_chapters_conflicts_with_dry_run_and_update() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        assert!(Args::try_parse_from(["rdrscrape", url, "--list-chapters"]).is_ok());
+        for other in [&["--dry-run"][..], &["--update", "b.json"][..]] {
+            let mut argv = vec!["rdrscrape", url, "--list-chapters"];
+            argv.extend_from_slice(other);
+            assert!(Args::try_parse_from(argv).is_err(), "{:?}", other);
+        }
+    }
+
+    #[test]
+    fn chapter_list_marks_locked_chapters() {
+        let chapter = |index: u32, title: &str| Chapter {
+            title: title.to_string(),
+            index,
+            body: String::new(),
+            reported_word_count: None,
+            url: Some(format!("https://e.x/c/{}", index)),
+        };
+        let book = Book {
+            title: "T".to_string(),
+            author: "A".to_string(),
+            chapters: vec![
+                chapter(1, "Start\there"),
+                chapter(2, "Premium (locked)"),
+                chapter(3, "Ends (locked)"),
+            ],
+            ..Default::default()
+        };
+        let chapters = listed_chapters(&book, &HashSet::from([2]));
+        assert_eq!(chapters[1].title, "Premium");
+        assert!(chapters[1].locked);
+        assert_eq!(chapters[2].title, "Ends (locked)");
+        assert!(!chapters[2].locked);
+        assert_eq!(
+            chapter_list_text(&chapters),
+            "1\tStart here\n2\tPremium (locked)\n3\tEnds (locked)\n"
+        );
+        let json = serde_json::to_value(&chapters[1]).unwrap();
+        assert_eq!(json["index"], 2);
+        assert_eq!(json["locked"], true);
+        assert_eq!(json["url"], "https://e.x/c/2");
+    }
+
+    #[test]
+    fn count_new_chapters_matches_canonical_urls() {
+        let chapter = |index: u32, url: &str| Chapter {
+            title: format!("Chapter {}", index),
+            index,
+            body: "<p>x</p>".to_string(),
+            reported_word_count: None,
+            url: Some(url.to_string()),
+        };
+        let previous = Book {
+            chapters: vec![
+                chapter(1, "https://royalroad.com/fiction/1/old/chapter/10/one"),
+                chapter(2, "https://www.royalroad.com/fiction/1/old/chapter/20/two/"),
+            ],
+            ..Default::default()
+        };
+        // Same chapters under the www host, a new slug and a tracking query, plus one new chapter.
+        let book = Book {
+            chapters: vec![
+                chapter(1, "https://www.royalroad.com/fiction/1/new/chapter/10/one"),
+                chapter(
+                    2,
+                    "http://royalroad.com/fiction/1/new/chapter/20/edit?ref=home",
+                ),
+                chapter(
+                    3,
+                    "https://www.royalroad.com/fiction/1/new/chapter/30/three",
+                ),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(count_new_chapters(&book, &previous, Site::RoyalRoad), 1);
+        let toc: HashSet<String> = book
+            .chapters
+            .iter()
+            .filter_map(|c| c.url.as_deref())
+            .map(|u| comparable_url(Site::RoyalRoad, u))
+            .collect();
+        assert!(chapters_not_in_toc(&previous, &toc, Site::RoyalRoad).is_empty());
+    }
+
+    #[test]
+    fn update_keeps_chapters_missing_from_toc_unless_pruned() {
+        let rr = |path: &str| format!("https://www.royalroad.com/fiction/1/{}", path);
+        let chapter = |index: u32, url: Option<String>| Chapter {
+            title: format!("Chapter {}", index),
+            index,
+            body: "<p>x</p>".to_string(),
+            reported_word_count: None,
+            url,
+        };
+        let previous = Book {
+            title: "T".to_string(),
+            author: "A".to_string(),
+            chapters: vec![
+                chapter(1, Some(rr("old-title/chapter/10/one"))),
+                chapter(2, Some(rr("old-title/chapter/20/two"))),
+                chapter(3, Some(rr("old-title/chapter/30/three"))),
+            ],
+            ..Default::default()
+        };
+        // The story was renamed (new slugs), chapter 2 edited (new URL), chapter 3 deleted, and
+        // chapter 4 is new.
+        let mut book = previous.clone();
+        book.chapters[1].url = Some(rr("new-title/chapter/21/two"));
+        book.chapters.extend([
+            chapter(4, Some(rr("new-title/chapter/40/four"))),
+            chapter(5, None),
+        ]);
+        let toc: HashSet<String> = ["chapter/10/one/", "chapter/21/two", "chapter/40/four"]
+            .iter()
+            .map(|p| comparable_url(Site::RoyalRoad, &rr(&format!("new-title/{}", p))))
+            .collect();
+        let indices = |b: &Book| b.chapters.iter().map(|c| c.index).collect::<Vec<u32>>();
+        let missing: Vec<u32> = chapters_not_in_toc(&book, &toc, Site::RoyalRoad)
+            .iter()
+            .map(|c| c.index)
+            .collect();
+        assert_eq!(missing, vec![3]);
+        assert_eq!(count_new_chapters(&book, &previous, Site::RoyalRoad), 3);
+
+        let path =
+            std::env::temp_dir().join(format!("rdrscrape_update_{}.json", std::process::id()));
+        let site = Site::RoyalRoad;
+        let kept = finish_update(
+            book.clone(),
+            &previous,
+            Some(toc.clone()),
+            site,
+            false,
+            &path,
+        )
+        .unwrap();
+        assert_eq!(indices(&kept), vec![1, 2, 3, 4, 5]);
+        let pruned = finish_update(book, &previous, Some(toc), site, true, &path).unwrap();
+        assert_eq!(indices(&pruned), vec![1, 2, 4, 5]);
+        let saved = read_checkpoint(std::fs::File::open(&path).unwrap(), &path).unwrap();
+        assert_eq!(indices(&saved), vec![1, 2, 4, 5]);
+        let _ = std::fs::remove_file(&path);
+    }
+
     #[test]
     fn cli_run_error_exit_codes() {
         assert_eq!(CliRunError::InvalidInput("x".into()).exit_code(), 1);
@@ -660,4 +3338,301 @@ mod tests {
             3
         );
     }
+
+    #[test]
+    fn book_json_with_hash_adds_content_hash() {
+        let book = Book {
+            title: "T".to_string(),
+            author: "A".to_string(),
+            chapters: vec![crate::model::Chapter {
+                title: "One".to_string(),
+                index: 1,
+                body: "<p>x</p>".to_string(),
+                reported_word_count: None,
+                url: None,
+            }],
+            ..Default::default()
+        };
+        let v = book_json_with_hash(&book);
+        assert_eq!(
+            v["contentHash"].as_str(),
+            Some(book.content_hash().as_str())
+        );
+        assert_eq!(v["title"].as_str(), Some("T"));
+    }
+
+    #[test]
+    fn progress_style_falls_back_on_invalid_template() {
+        let (tick, fill) = bar_chars(true);
+        assert!(indicatif::ProgressStyle::default_bar()
+            .template(PROGRESS_TEMPLATE)
+            .is_ok());
+        let invalid = "{msg:x} [{bar:40}]";
+        assert!(indicatif::ProgressStyle::default_bar()
+            .template(invalid)
+            .is_err());
+        let bar = indicatif::ProgressBar::hidden();
+        bar.set_style(progress_style(invalid, tick, fill));
+        bar.inc(1);
+        assert_eq!(bar.position(), 1);
+    }
+
+    #[test]
+    fn language_flag_requires_bcp47_tag() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        let args = Args::try_parse_from(["rdrscrape", url, "--language", "pt-BR"]).unwrap();
+        assert_eq!(args.language.as_deref(), Some("pt-BR"));
+        assert!(Args::try_parse_from(["rdrscrape", url, "--language", "fr_FR"]).is_err());
+    }
+
+    #[test]
+    fn contributor_flag_parses_role_and_name() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        let args = Args::try_parse_from([
+            "rdrscrape",
+            url,
+            "--contributor",
+            "trl:Jane Doe",
+            "--contributor",
+            "aut: A. N. Other",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.contributors,
+            vec![
+                Contributor {
+                    role: "trl".to_string(),
+                    name: "Jane Doe".to_string()
+                },
+                Contributor {
+                    role: "aut".to_string(),
+                    name: "A. N. Other".to_string()
+                },
+            ]
+        );
+        for bad in ["Jane Doe", "translator:Jane", "trl:", "TRL:Jane"] {
+            assert!(
+                Args::try_parse_from(["rdrscrape", url, "--contributor", bad]).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn series_index_requires_series_and_a_number() {
+        let url = "https://www.royalroad.com/fiction/1/x";
+        let args = Args::try_parse_from([
+            "rdrscrape",
+            url,
+            "--series",
+            "Saga",
+            "--series-index",
+            "2.5",
+        ])
+        .unwrap();
+        assert_eq!(args.series.as_deref(), Some("Saga"));
+        assert_eq!(args.series_index, Some(2.5));
+        assert!(Args::try_parse_from(["rdrscrape", url, "--series-index", "2"]).is_err());
+        assert!(Args::try_parse_from([
+            "rdrscrape",
+            url,
+            "--series",
+            "Saga",
+            "--series-index",
+            "two"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn title_with_author_joins_with_em_dash() {
+        assert_eq!(
+            title_with_author("Mother of Learning ", "nobody103"),
+            "Mother of Learning \u{2014} nobody103"
+        );
+        assert_eq!(title_with_author("Orphan", UNKNOWN_AUTHOR), "Orphan");
+        assert_eq!(title_with_author("Orphan", " "), "Orphan");
+        assert_eq!(
+            sanitize_title(&title_with_author("Worm", "Wildbow")),
+            "worm-wildbow"
+        );
+    }
+
+    #[test]
+    fn title_with_author_keeps_the_scraped_title() {
+        let dir =
+            std::env::temp_dir().join(format!("rdrscrape_title_author_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (input, output) = (dir.join("in.json"), dir.join("out.json"));
+        std::fs::write(
+            &input,
+            r#"{"title":"Worm","author":"Wildbow","chapters":[{"title":"One","index":1,"body":"<p>x</p>"}]}"#,
+        )
+        .unwrap();
+        let convert = |from: &Path| {
+            let (from, to) = (from.to_string_lossy(), output.to_string_lossy());
+            let args = Args::try_parse_from([
+                "rdrscrape",
+                "--from-json",
+                from.as_ref(),
+                "--title-with-author",
+                "--format",
+                "json",
+                "-q",
+                "-o",
+                to.as_ref(),
+            ])
+            .unwrap();
+            run(&args).unwrap();
+            load_book_json(&output).unwrap()
+        };
+        let book = convert(&input);
+        assert_eq!(book.title, "Worm \u{2014} Wildbow");
+        assert_eq!(book.original_title.as_deref(), Some("Worm"));
+        // Converting the combined book again does not append the author twice.
+        std::fs::copy(&output, &input).unwrap();
+        let book = convert(&input);
+        assert_eq!(book.title, "Worm \u{2014} Wildbow");
+        assert_eq!(book.original_title.as_deref(), Some("Worm"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn apply_description_override_sets_and_clears() {
+        let mut book = Book {
+            title: "T".to_string(),
+            author: "A".to_string(),
+            description: Some("<b>Spoiler</b>".to_string()),
+            ..Default::default()
+        };
+        apply_description_override(&mut book, None, false);
+        assert_eq!(book.description.as_deref(), Some("<b>Spoiler</b>"));
+        apply_description_override(&mut book, Some("Clean blurb"), false);
+        assert_eq!(book.description.as_deref(), Some("Clean blurb"));
+        apply_description_override(&mut book, Some("  "), false);
+        assert!(book.description.is_none());
+        book.description = Some("x".to_string());
+        apply_description_override(&mut book, None, true);
+        assert!(book.description.is_none());
+    }
+
+    #[test]
+    fn retry_flags_parse_count_and_backoff_list() {
+        let args = Args::try_parse_from([
+            "rdrscrape",
+            "https://www.royalroad.com/fiction/1/x",
+            "--retries",
+            "5",
+            "--retry-backoff",
+            "1,2,4",
+        ])
+        .unwrap();
+        assert_eq!(args.retries, Some(5));
+        assert_eq!(args.retry_backoff, Some(vec![1, 2, 4]));
+        let args = Args::try_parse_from(["rdrscrape", "u", "--retry-backoff", "1,x"]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn cancelled_scrape_exits_130() {
+        let e = CliRunError::Scraper(ScraperError::Cancelled);
+        assert_eq!(e.exit_code(), 130);
+        assert_eq!(e.kind_name(), "cancelled");
+    }
+
+    #[test]
+    fn partial_ok_writes_output_when_scrape_is_cancelled() {
+        use crate::scraper::mock_server::MockServer;
+        let server = MockServer::start();
+        let fiction = "/fiction/1/mock-story";
+        let one = "/fiction/1/mock-story/chapter/11/one";
+        server.route(
+            fiction,
+            &[(
+                200,
+                r#"<html><head><script type="application/ld+json">{"@type":"Book","name":"Mock Story","author":{"name":"Tester"}}</script></head><body><script>
+window.chapters = [{"id":11,"title":"One","url":"/fiction/1/mock-story/chapter/11/one","order":0,"isUnlocked":true},{"id":12,"title":"Two","url":"/fiction/1/mock-story/chapter/12/two","order":1,"isUnlocked":true}];
+</script></body></html>"#,
+            )],
+        );
+        let dir = std::env::temp_dir().join(format!("rdrscrape_partial_ok_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (checkpoint, output) = (dir.join("book.json"), dir.join("partial.json"));
+        // Chapter 1 is already saved, so the scrape stops at chapter 2 with one chapter in hand.
+        let saved = Book {
+            title: "Mock Story".to_string(),
+            author: "Tester".to_string(),
+            chapters: vec![Chapter {
+                title: "1. One".to_string(),
+                index: 1,
+                body: "<p>First chapter.</p>".to_string(),
+                reported_word_count: None,
+                url: Some(server.url(one)),
+            }],
+            source_url: Some(server.url(fiction)),
+            ..Default::default()
+        };
+        write_checkpoint_file(&checkpoint, &saved).unwrap();
+        let url = server.url(fiction);
+        let checkpoint_arg = checkpoint.to_string_lossy().into_owned();
+        let output_arg = output.to_string_lossy().into_owned();
+        // --deadline 0 makes the cancel check return true before the first chapter fetch.
+        let args = Args::try_parse_from([
+            "rdrscrape",
+            url.as_str(),
+            "--site",
+            "royalroad",
+            "--delay",
+            "0",
+            "--quiet",
+            "--resume",
+            checkpoint_arg.as_str(),
+            "--deadline",
+            "0",
+            "--partial-ok",
+            "--format",
+            "json",
+            "-o",
+            output_arg.as_str(),
+        ])
+        .unwrap();
+        assert!(args.partial_ok);
+        let err = run(&args).unwrap_err();
+        assert_eq!(err.exit_code(), 4);
+        assert!(output.exists());
+        let written: Book =
+            serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(written.chapters.len(), 1);
+        assert_eq!(server.hits("/fiction/1/mock-story/chapter/12/two"), 0);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn interrupted_has_own_exit_code_and_kind() {
+        let e = CliRunError::Interrupted {
+            done: 2,
+            hint: String::new(),
+        };
+        assert_eq!(e.exit_code(), 130);
+        assert_eq!(e.kind_name(), "interrupted");
+        assert_eq!(e.to_string(), "Interrupted; stopped after 2 chapter(s).");
+    }
+
+    #[test]
+    fn deadline_reached_has_own_exit_code_and_kind() {
+        let e = CliRunError::DeadlineReached {
+            secs: 60,
+            done: 3,
+            hint: String::new(),
+        };
+        assert_eq!(e.exit_code(), 4);
+        assert_eq!(e.kind_name(), "deadline_reached");
+        assert_eq!(
+            e.to_string(),
+            "Deadline of 60s reached; stopped after 3 chapter(s)."
+        );
+        let args = Args::try_parse_from(["rdrscrape", "u", "--deadline", "60"]).unwrap();
+        assert_eq!(args.deadline, Some(60));
+    }
 }
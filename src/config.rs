@@ -1,9 +1,14 @@
 //! Optional config file loading. Search order: ./rdrscrape.toml, then
 //! $XDG_CONFIG_HOME/rdrscrape/config.toml (or ~/.config/rdrscrape/config.toml).
 
+use crate::model::is_language_tag;
+use crate::scraper::ExtraHosts;
 use serde::Deserialize;
 use std::path::PathBuf;
 
+/// Upper bound for `chapter_parse_retries`, so a broken site cannot multiply requests unboundedly.
+const MAX_CHAPTER_PARSE_RETRIES: u32 = 5;
+
 /// Config file contents. All fields optional; only present keys override defaults.
 #[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "snake_case", default)]
@@ -16,14 +21,117 @@ pub struct Config {
     pub request_delay_secs: Option<u64>,
     /// Request timeout in seconds.
     pub timeout_secs: Option<u64>,
+    /// Timeout in seconds for the EPUB cover image request (default 10), separate from timeout_secs.
+    pub cover_timeout_secs: Option<u64>,
     /// Include a visible table-of-contents page after the cover in EPUB (default: true). Set to false to disable.
     pub toc_page: Option<bool>,
     /// Number of HTTP attempts for transient failures (default 3). Only used when retry_backoff_secs is not set or is non-empty.
     pub retry_count: Option<u32>,
     /// Delay in seconds before each retry (e.g. [1, 2, 4]). Length should be retry_count - 1. If not set, default [1, 2, 4] is used.
     pub retry_backoff_secs: Option<Vec<u64>>,
+    /// Cap on cumulative retry sleep per request in seconds (including HTTP 429 backoff). If not set, no cap.
+    pub max_total_backoff_secs: Option<u64>,
     /// How to handle chapters with empty body or missing content: skip (default), placeholder, or fail.
     pub empty_chapters: Option<String>,
+    /// Resume: TOC entry reusing a checkpoint chapter's index with another URL: keep-existing (default), prefer-new, or fail.
+    pub index_collision: Option<String>,
+    /// Fail instead of warning when the -o extension does not match --format (default: false).
+    pub strict_extension: Option<bool>,
+    /// Hosts that must never be scraped (e.g. known mirrors). Suffix match: "example.org" also blocks "www.example.org".
+    pub blocked_hosts: Option<Vec<String>>,
+    /// Block tags kept as chapter paragraphs, lists, subheadings and scene breaks (default ["p", "ul", "ol", "h1"…"h6", "hr"]). Replaces the built-in list.
+    pub allowed_block_tags: Option<Vec<String>>,
+    /// Inline tags kept inside chapter paragraphs (default ["em", "strong", "i", "b", "br", "img"]). Replaces the built-in list.
+    pub allowed_inline_tags: Option<Vec<String>>,
+    /// ScribbleHub chapter list source: auto (default, AJAX then pagination), ajax, or paginate.
+    pub scribblehub_toc_mode: Option<String>,
+    /// EPUB `<dc:rights>` statement. Empty string omits it; unset uses "© author; see source URL".
+    pub rights: Option<String>,
+    /// EPUB `<dc:language>` as a BCP 47 tag, e.g. "fr" or "pt-BR" (default "en").
+    pub language: Option<String>,
+    /// Refetch attempts for a chapter page that returns 200 but looks truncated (default 2, max 5).
+    pub chapter_parse_retries: Option<u32>,
+    /// Additional hosts mapped to a site (`"host" = "royalroad"`, `"scribblehub"` or `"ao3"`), e.g. mirrors.
+    /// Suffix match like blocked_hosts; built-in Royal Road / Scribble Hub hosts are always known.
+    pub extra_hosts: Option<ExtraHosts>,
+    /// Royal Road: regexes for class names whose chapter paragraphs are hidden anti-theft text,
+    /// on top of classes the page's own stylesheet hides. Matched against each class name.
+    pub royalroad_hidden_classes: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Check values that TOML types alone cannot: tag names must be lowercase ASCII alphanumeric,
+    /// language must look like a BCP 47 tag, chapter_parse_retries is bounded,
+    /// cover_timeout_secs is positive, index_collision and scribblehub_toc_mode must be known
+    /// values, and royalroad_hidden_classes must be valid regexes.
+    fn validate(&self) -> Result<(), String> {
+        if let Some(ref language) = self.language {
+            if !is_language_tag(language) {
+                return Err(format!(
+                    "language: '{}' is not a BCP 47 language tag (e.g. \"fr\" or \"pt-BR\")",
+                    language
+                ));
+            }
+        }
+        if let Some(n) = self.chapter_parse_retries {
+            if n > MAX_CHAPTER_PARSE_RETRIES {
+                return Err(format!(
+                    "chapter_parse_retries: {} is above the maximum of {}",
+                    n, MAX_CHAPTER_PARSE_RETRIES
+                ));
+            }
+        }
+        if self.cover_timeout_secs == Some(0) {
+            return Err(
+                "cover_timeout_secs: must be at least 1 (0 would time out every cover request)"
+                    .to_string(),
+            );
+        }
+        if let Some(ref behavior) = self.index_collision {
+            if !["keep-existing", "prefer-new", "fail"].contains(&behavior.to_lowercase().as_str())
+            {
+                return Err(format!(
+                    "index_collision: '{}' is not one of keep-existing, prefer-new, fail",
+                    behavior
+                ));
+            }
+        }
+        if let Some(ref mode) = self.scribblehub_toc_mode {
+            if !["auto", "ajax", "paginate"].contains(&mode.to_lowercase().as_str()) {
+                return Err(format!(
+                    "scribblehub_toc_mode: '{}' is not one of auto, ajax, paginate",
+                    mode
+                ));
+            }
+        }
+        for pattern in self.royalroad_hidden_classes.iter().flatten() {
+            if let Err(e) = regex::Regex::new(pattern) {
+                return Err(format!(
+                    "royalroad_hidden_classes: invalid regex '{}': {}",
+                    pattern, e
+                ));
+            }
+        }
+        let tag_lists = [
+            ("allowed_block_tags", &self.allowed_block_tags),
+            ("allowed_inline_tags", &self.allowed_inline_tags),
+        ];
+        for (key, tags) in tag_lists {
+            for tag in tags.iter().flatten() {
+                let valid = !tag.is_empty()
+                    && tag
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+                if !valid {
+                    return Err(format!(
+                        "{}: invalid tag name '{}' (use lowercase letters and digits, e.g. \"em\")",
+                        key, tag
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Search order: (1) ./rdrscrape.toml, (2) $XDG_CONFIG_HOME/rdrscrape/config.toml.
@@ -41,6 +149,9 @@ pub fn load_config() -> Result<Option<Config>, String> {
                 .map_err(|e| format!("Cannot read config {}: {}", path.display(), e))?;
             let config: Config = toml::from_str(&s)
                 .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+            config
+                .validate()
+                .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
             return Ok(Some(config));
         }
     }
@@ -50,6 +161,7 @@ pub fn load_config() -> Result<Option<Config>, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scraper::Site;
 
     #[test]
     fn parse_empty_config() {
@@ -58,10 +170,23 @@ mod tests {
         assert!(c.user_agent.is_none());
         assert!(c.request_delay_secs.is_none());
         assert!(c.timeout_secs.is_none());
+        assert!(c.cover_timeout_secs.is_none());
         assert!(c.toc_page.is_none());
         assert!(c.retry_count.is_none());
         assert!(c.retry_backoff_secs.is_none());
+        assert!(c.max_total_backoff_secs.is_none());
         assert!(c.empty_chapters.is_none());
+        assert!(c.index_collision.is_none());
+        assert!(c.strict_extension.is_none());
+        assert!(c.blocked_hosts.is_none());
+        assert!(c.allowed_block_tags.is_none());
+        assert!(c.allowed_inline_tags.is_none());
+        assert!(c.scribblehub_toc_mode.is_none());
+        assert!(c.rights.is_none());
+        assert!(c.language.is_none());
+        assert!(c.chapter_parse_retries.is_none());
+        assert!(c.extra_hosts.is_none());
+        assert!(c.royalroad_hidden_classes.is_none());
     }
 
     #[test]
@@ -71,23 +196,139 @@ mod tests {
             user_agent = "Custom/1.0"
             request_delay_secs = 3
             timeout_secs = 60
+            cover_timeout_secs = 5
             toc_page = true
             retry_count = 5
             retry_backoff_secs = [1, 2, 4, 8]
+            max_total_backoff_secs = 90
             empty_chapters = "placeholder"
+            index_collision = "prefer-new"
+            strict_extension = true
+            blocked_hosts = ["mirror.example"]
+            allowed_block_tags = ["p", "blockquote"]
+            allowed_inline_tags = ["em", "sup"]
+            scribblehub_toc_mode = "paginate"
+            rights = "CC BY-NC 4.0"
+            language = "pt-BR"
+            chapter_parse_retries = 3
+            royalroad_hidden_classes = ["^cj[A-Za-z0-9]{20,}$"]
+
+            [extra_hosts]
+            "sh.mirror.example" = "scribblehub"
+            "rr.mirror.example" = "rr"
         "#;
         let c: Config = toml::from_str(s).unwrap();
         assert_eq!(c.output_dir.as_deref(), Some(std::path::Path::new("out")));
         assert_eq!(c.user_agent.as_deref(), Some("Custom/1.0"));
         assert_eq!(c.request_delay_secs, Some(3));
         assert_eq!(c.timeout_secs, Some(60));
+        assert_eq!(c.cover_timeout_secs, Some(5));
         assert_eq!(c.toc_page, Some(true));
         assert_eq!(c.retry_count, Some(5));
         assert_eq!(
             c.retry_backoff_secs.as_deref(),
             Some([1, 2, 4, 8].as_slice())
         );
+        assert_eq!(c.max_total_backoff_secs, Some(90));
         assert_eq!(c.empty_chapters.as_deref(), Some("placeholder"));
+        assert_eq!(c.index_collision.as_deref(), Some("prefer-new"));
+        assert_eq!(c.strict_extension, Some(true));
+        assert_eq!(
+            c.blocked_hosts.as_deref(),
+            Some(["mirror.example".to_string()].as_slice())
+        );
+        assert_eq!(
+            c.allowed_block_tags,
+            Some(vec!["p".to_string(), "blockquote".to_string()])
+        );
+        assert_eq!(
+            c.allowed_inline_tags,
+            Some(vec!["em".to_string(), "sup".to_string()])
+        );
+        assert_eq!(c.scribblehub_toc_mode.as_deref(), Some("paginate"));
+        assert_eq!(c.rights.as_deref(), Some("CC BY-NC 4.0"));
+        assert_eq!(c.language.as_deref(), Some("pt-BR"));
+        assert_eq!(c.chapter_parse_retries, Some(3));
+        assert_eq!(
+            c.royalroad_hidden_classes,
+            Some(vec!["^cj[A-Za-z0-9]{20,}$".to_string()])
+        );
+        let extra = c.extra_hosts.as_ref().unwrap();
+        assert_eq!(extra.get("sh.mirror.example"), Some(&Site::ScribbleHub));
+        assert_eq!(extra.get("rr.mirror.example"), Some(&Site::RoyalRoad));
+        assert!(c.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_zero_cover_timeout() {
+        let c: Config = toml::from_str("cover_timeout_secs = 1").unwrap();
+        assert!(c.validate().is_ok());
+        let c: Config = toml::from_str("cover_timeout_secs = 0").unwrap();
+        assert!(c.validate().unwrap_err().contains("cover_timeout_secs"));
+    }
+
+    #[test]
+    fn validate_rejects_bad_index_collision() {
+        let c: Config = toml::from_str(r#"index_collision = "Prefer-New""#).unwrap();
+        assert!(c.validate().is_ok());
+        let c: Config = toml::from_str(r#"index_collision = "prefer_new""#).unwrap();
+        assert!(c.validate().unwrap_err().contains("index_collision"));
+    }
+
+    #[test]
+    fn extra_hosts_rejects_unknown_site() {
+        let err = toml::from_str::<Config>("[extra_hosts]\n\"x.example\" = \"wattpad\"")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("wattpad"), "{}", err);
+    }
+
+    #[test]
+    fn validate_bounds_chapter_parse_retries() {
+        let c: Config = toml::from_str("chapter_parse_retries = 5").unwrap();
+        assert!(c.validate().is_ok());
+        let c: Config = toml::from_str("chapter_parse_retries = 6").unwrap();
+        assert!(c.validate().unwrap_err().contains("maximum of 5"));
+    }
+
+    #[test]
+    fn validate_rejects_bad_language() {
+        let c: Config = toml::from_str(r#"language = "fr""#).unwrap();
+        assert!(c.validate().is_ok());
+        let c: Config = toml::from_str(r#"language = "fr_FR""#).unwrap();
+        assert!(c.validate().unwrap_err().contains("BCP 47"));
+    }
+
+    #[test]
+    fn validate_rejects_bad_scribblehub_toc_mode() {
+        let c: Config = toml::from_str(r#"scribblehub_toc_mode = "Paginate""#).unwrap();
+        assert!(c.validate().is_ok());
+        let c: Config = toml::from_str(r#"scribblehub_toc_mode = "scroll""#).unwrap();
+        assert!(c.validate().unwrap_err().contains("scribblehub_toc_mode"));
+    }
+
+    #[test]
+    fn validate_rejects_bad_hidden_class_regex() {
+        let c: Config = toml::from_str(r#"royalroad_hidden_classes = ["^anti-"]"#).unwrap();
+        assert!(c.validate().is_ok());
+        let c: Config = toml::from_str(r#"royalroad_hidden_classes = ["(unclosed"]"#).unwrap();
+        assert!(c
+            .validate()
+            .unwrap_err()
+            .contains("royalroad_hidden_classes"));
+    }
+
+    #[test]
+    fn validate_rejects_bad_tag_names() {
+        for bad in [
+            r#"allowed_inline_tags = ["EM"]"#,
+            r#"allowed_block_tags = ["p", "<p>"]"#,
+        ] {
+            let c: Config = toml::from_str(bad).unwrap();
+            assert!(c.validate().is_err(), "{}", bad);
+        }
+        let c: Config = toml::from_str(r#"allowed_inline_tags = ["h2", "sub"]"#).unwrap();
+        assert!(c.validate().is_ok());
     }
 
     #[test]
@@ -1,9 +1,14 @@
 //! EPUB writer. Consumes canonical `Book` and writes EPUB 2 or EPUB 3 (mimetype, container, OPF, nav/NCX, chapters).
 
-use crate::model::Book;
+use crate::formats::{chapter_nav_links, image_media_type, modified_timestamp, utc_datetime_now};
+use crate::model::{clean_tags, identifier, Book, Chapter};
 use crate::scraper::PoliteClient;
+use regex::Regex;
+use std::collections::BTreeMap;
 use std::io::{Seek, Write};
 use std::path::Path;
+use std::sync::LazyLock;
+use std::time::Duration;
 use thiserror::Error;
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
@@ -51,6 +56,24 @@ pub enum EpubError {
 
     #[error("Failed to write EPUB archive: {0}")]
     Zip(#[from] zip::result::ZipError),
+
+    #[error("EPUB link check failed: {document} references {target}, which is not in the manifest or was not written.")]
+    BrokenLink { document: String, target: String },
+}
+
+impl EpubError {
+    /// Stable snake_case name of the variant, for machine-readable output.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            EpubError::EmptyTitle => "epub_empty_title",
+            EpubError::EmptyAuthor => "epub_empty_author",
+            EpubError::NoChapters => "epub_no_chapters",
+            EpubError::Io { .. } => "epub_io",
+            EpubError::CreateFile { .. } => "epub_create_file",
+            EpubError::Zip(_) => "epub_zip",
+            EpubError::BrokenLink { .. } => "epub_broken_link",
+        }
+    }
 }
 
 impl From<std::io::Error> for EpubError {
@@ -61,6 +84,100 @@ impl From<std::io::Error> for EpubError {
 
 const MIMETYPE: &[u8] = b"application/epub+zip";
 const OEBPS_PREFIX: &str = "OEBPS/";
+/// Manifest id of the cover image; the EPUB 2 `<meta name="cover">` must name it.
+const COVER_IMAGE_ID: &str = "cover-img";
+/// Default [EpubOptions::cover_timeout_secs]: shorter than the 30s page timeout.
+pub const DEFAULT_COVER_TIMEOUT_SECS: u64 = 10;
+
+/// Start of every XHTML document (nav, cover, TOC page, chapters) for the given version.
+///
+/// Policy: all XHTML files carry the XML declaration. EPUB 3 uses the HTML5 doctype; EPUB 2 uses
+/// the XHTML 1.1 doctype required by OPS 2.0.1.
+fn xhtml_prolog(version: EpubVersion) -> &'static str {
+    match version {
+        EpubVersion::Epub3 => "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n",
+        EpubVersion::Epub2 => concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.1//EN\" ",
+            "\"http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd\">\n"
+        ),
+    }
+}
+
+/// Charset declaration and stylesheet link for `<head>`: `<meta charset>` for EPUB 3; XHTML 1.1
+/// has no `charset` attribute, so EPUB 2 uses the equivalent `http-equiv` form. Every document
+/// sits next to `style.css` in `OEBPS/`.
+fn head_meta(version: EpubVersion) -> &'static str {
+    match version {
+        EpubVersion::Epub3 => concat!(
+            r#"<meta charset="UTF-8"/>"#,
+            "\n  ",
+            r#"<link rel="stylesheet" type="text/css" href="style.css"/>"#
+        ),
+        EpubVersion::Epub2 => concat!(
+            r#"<meta http-equiv="Content-Type" content="application/xhtml+xml; charset=utf-8"/>"#,
+            "\n  ",
+            r#"<link rel="stylesheet" type="text/css" href="style.css"/>"#
+        ),
+    }
+}
+
+/// Built-in `style.css`, used unless [EpubOptions::css] replaces it: serif justified body text,
+/// indented blockquotes, and a short centred rule or centred divider line for scene breaks.
+pub const DEFAULT_EPUB_CSS: &str = "body { font-family: serif; margin: 0 5%; }
+p { text-align: justify; margin: 0 0 0.8em 0; }
+h1, h2, h3, h4, h5, h6 { font-family: sans-serif; text-align: left; page-break-after: avoid; }
+blockquote { margin: 1em 2em; font-style: italic; }
+hr { border: 0; border-top: 1px solid; margin: 1.5em 35%; }
+img { max-width: 100%; height: auto; }
+.chapter-nav { text-align: center; font-size: 0.9em; }
+.scene-break { text-align: center; margin: 1.5em 0; }
+";
+
+/// How chapter files inside the EPUB are named.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChapterFileNames {
+    /// `chapter-1.xhtml`, `chapter-2.xhtml`, ... by position in the book.
+    #[default]
+    Position,
+    /// `chapter-0050.xhtml` by [Chapter::index](crate::model::Chapter::index), zero-padded to at
+    /// least four digits, so files match the source TOC after range filtering.
+    Index,
+}
+
+/// Chapter file names (relative to OEBPS/) in book order, per `naming`.
+fn chapter_file_names(book: &Book, naming: ChapterFileNames) -> Vec<String> {
+    match naming {
+        ChapterFileNames::Position => (1..=book.chapters.len())
+            .map(|n| format!("chapter-{}.xhtml", n))
+            .collect(),
+        ChapterFileNames::Index => {
+            let max = book.chapters.iter().map(|c| c.index).max().unwrap_or(0);
+            let width = max.to_string().len().max(4);
+            book.chapters
+                .iter()
+                .map(|c| format!("chapter-{:0width$}.xhtml", c.index, width = width))
+                .collect()
+        }
+    }
+}
+
+/// A chapter image downloaded for the EPUB, stored at `OEBPS/{href}` (`images/ch{N}-{M}.{ext}`
+/// for the M-th image first seen in the N-th chapter).
+#[derive(Debug)]
+struct ChapterImage {
+    href: String,
+    data: Vec<u8>,
+    ext: &'static str,
+}
+
+impl ChapterImage {
+    /// Manifest id, e.g. `img-ch3-1`.
+    fn id(&self) -> String {
+        let stem = self.href.trim_start_matches("images/");
+        format!("img-{}", stem.rsplit_once('.').map_or(stem, |(s, _)| s))
+    }
+}
 
 /// Result of cover handling: none, title-only (fetch failed), or image.
 #[derive(Debug)]
@@ -70,28 +187,141 @@ enum CoverOutcome {
     Image { data: Vec<u8>, ext: &'static str },
 }
 
+/// Options for [write_epub]: version, optional NCX/TOC page, and chapter navigation links.
+#[derive(Debug, Clone)]
+pub struct EpubOptions {
+    pub version: EpubVersion,
+    /// Include toc.ncx in EPUB 3 for legacy readers (EPUB 2 always includes it).
+    pub include_ncx: bool,
+    /// Insert a visible table-of-contents page after the cover.
+    pub include_toc_page: bool,
+    /// Add Previous/Next links at the top and bottom of each chapter.
+    pub nav_links: bool,
+    /// Heading for nav.xhtml and the TOC page. None keeps "Table of Contents" (nav heading "Contents").
+    pub toc_title: Option<String>,
+    /// Before closing the archive, check that spine, guide, nav, and NCX references resolve to written manifest items.
+    pub verify_links: bool,
+    /// Name chapter files by position (default) or by chapter index.
+    pub chapter_file_names: ChapterFileNames,
+    /// Insert a "Description" page after the cover, the book description split into paragraphs
+    /// on blank lines. Skipped when the book has no description; `<dc:description>` is written
+    /// either way.
+    pub description_page: bool,
+    /// Append an "About" page (source URL, scrape date, tool version, chapter count) after the last chapter.
+    pub about_page: bool,
+    /// Per-attempt timeout for the cover image request; on timeout the cover page is title-only.
+    pub cover_timeout_secs: u64,
+    /// Politeness delay before the cover request. None uses the client's page delay.
+    pub asset_delay_secs: Option<u64>,
+    /// Download `book.cover_url`. When false, no request is made and the cover page is title-only.
+    pub fetch_cover: bool,
+    /// Write a cover at all. When false, `book.cover_url` is ignored: no request, no cover page.
+    pub include_cover: bool,
+    /// Contents of `style.css`, linked from every page. None uses [DEFAULT_EPUB_CSS].
+    pub css: Option<String>,
+    /// Download the `<img>` sources in chapter bodies into `images/` (same timeout and delay as
+    /// the cover). When false, no request is made and the images are left out.
+    pub fetch_images: bool,
+    /// `<dc:rights>` statement. None uses "© author; see source URL" when the book has a source
+    /// URL; an empty string omits the element.
+    pub rights: Option<String>,
+    /// Template for chapter titles in nav labels and headings (see [chapter_display_title]). None
+    /// uses the scraped titles as-is.
+    pub chapter_title_template: Option<String>,
+    /// Use a short "Chapter N" (the chapter index) as each chapter page's `<title>` element, for
+    /// readers that show it in a cramped header bar. Nav, NCX, and the TOC page keep the full title.
+    pub short_title_element: bool,
+}
+
+impl Default for EpubOptions {
+    fn default() -> Self {
+        Self {
+            version: EpubVersion::Epub3,
+            include_ncx: false,
+            include_toc_page: true,
+            nav_links: false,
+            toc_title: None,
+            verify_links: false,
+            chapter_file_names: ChapterFileNames::Position,
+            description_page: false,
+            about_page: false,
+            cover_timeout_secs: DEFAULT_COVER_TIMEOUT_SECS,
+            asset_delay_secs: None,
+            fetch_cover: true,
+            include_cover: true,
+            css: None,
+            fetch_images: true,
+            rights: None,
+            chapter_title_template: None,
+            short_title_element: false,
+        }
+    }
+}
+
+/// Chapter title as shown in the EPUB: `template` with `{num}` (the chapter index), `{volume}`,
+/// and `{title}` (the scraped title) filled in, then trimmed. Chapters carry no volume yet, so
+/// `{volume}` expands to nothing. An empty result falls back to the scraped title.
+pub fn chapter_display_title(template: &str, chapter: &Chapter) -> String {
+    let shown = template
+        .replace("{num}", &chapter.index.to_string())
+        .replace("{volume}", "")
+        .replace("{title}", &chapter.title);
+    match shown.trim() {
+        "" => chapter.title.clone(),
+        t => t.to_string(),
+    }
+}
+
 /// Write a canonical [Book](crate::model::Book) to an EPUB file.
 ///
 /// Fetches cover image using `client` if `book.cover_url` is set. On cover fetch failure,
 /// emits a title-only cover page (no image) and warns to stderr; does not fail the write.
-/// Set `epub3_include_ncx` to true to include toc.ncx in EPUB 3 for legacy readers.
-/// Set `include_toc_page` to true to insert a visible table-of-contents page after the cover. Output is intended to pass epubcheck.
+/// Chapter images are fetched the same way; one that fails is left out with a warning.
+/// See [EpubOptions] for NCX, TOC page, navigation link, and link-check settings. Output is intended to pass epubcheck.
 pub fn write_epub(
     book: &Book,
     path: &Path,
-    version: EpubVersion,
-    epub3_include_ncx: bool,
-    include_toc_page: bool,
+    options: &EpubOptions,
     client: &mut PoliteClient,
 ) -> Result<(), EpubError> {
+    // Validate before creating the file so a rejected book leaves nothing behind.
     validate_book(book)?;
-
     let path = path.to_path_buf();
     let file = std::fs::File::create(&path).map_err(|e| EpubError::CreateFile {
         path: path.clone(),
         source: e,
     })?;
-    let mut zip = ZipWriter::new(file);
+    write_epub_to(book, file, options, client)?;
+    Ok(())
+}
+
+/// Write a canonical [Book](crate::model::Book) as an EPUB archive into any seekable sink, such
+/// as a `File` or an in-memory `Cursor<Vec<u8>>`, and return the sink once the archive is closed.
+///
+/// Behaves exactly like [write_epub], which is a thin wrapper that opens the file and calls this.
+pub fn write_epub_to<W: Write + Seek>(
+    book: &Book,
+    writer: W,
+    options: &EpubOptions,
+    client: &mut PoliteClient,
+) -> Result<W, EpubError> {
+    validate_book(book)?;
+
+    // Headings, nav, and NCX all read `ch.title`; template a copy so the caller's book is untouched.
+    let templated;
+    let book = match options.chapter_title_template.as_deref() {
+        Some(template) => {
+            let mut copy = book.clone();
+            for ch in &mut copy.chapters {
+                ch.title = chapter_display_title(template, ch);
+            }
+            templated = copy;
+            &templated
+        }
+        None => book,
+    };
+
+    let mut zip = EpubZip::new(writer);
 
     let options_stored = SimpleFileOptions::default()
         .compression_method(zip::CompressionMethod::Stored)
@@ -108,37 +338,95 @@ pub fn write_epub(
     zip.start_file("META-INF/container.xml", options_deflate)?;
     zip.write_all(CONTAINER_XML)?;
 
+    // 3. Stylesheet shared by every page
+    zip.start_file(format!("{}style.css", OEBPS_PREFIX), options_deflate)?;
+    zip.write_all(
+        options
+            .css
+            .as_deref()
+            .unwrap_or(DEFAULT_EPUB_CSS)
+            .as_bytes(),
+    )?;
+
     // Cover: try to fetch; on failure use title-only cover page
-    let cover = fetch_cover(book, client);
+    let cover = match &book.cover_url {
+        _ if !options.include_cover => CoverOutcome::NoCover,
+        Some(u) if !u.is_empty() && !options.fetch_cover => CoverOutcome::TitleOnly,
+        _ => fetch_cover(book, client, options),
+    };
+    let (embedded, images) = embed_chapter_images(book, client, options);
+    let book = embedded.as_ref().unwrap_or(book);
+    let files = chapter_file_names(book, options.chapter_file_names);
 
-    match version {
+    match options.version {
         EpubVersion::Epub3 => {
             write_opf3(
                 book,
                 &cover,
-                epub3_include_ncx,
-                include_toc_page,
+                &images,
+                options,
+                &files,
                 &mut zip,
                 options_deflate,
             )?;
-            write_nav_xhtml(book, &mut zip, options_deflate)?;
-            if epub3_include_ncx {
-                write_ncx(book, &mut zip, options_deflate)?;
+            write_nav_xhtml(
+                book,
+                &files,
+                options.toc_title.as_deref(),
+                &mut zip,
+                options_deflate,
+            )?;
+            if options.include_ncx {
+                write_ncx(book, &files, &mut zip, options_deflate)?;
             }
-            write_cover_xhtml(book, &cover, &mut zip, options_deflate)?;
-            if include_toc_page {
-                write_toc_page_xhtml(book, &mut zip, options_deflate)?;
+            write_cover_xhtml(book, options.version, &cover, &mut zip, options_deflate)?;
+            if has_description_page(book, options) {
+                write_description_xhtml(book, options.version, &mut zip, options_deflate)?;
+            }
+            if options.include_toc_page {
+                write_toc_page_xhtml(
+                    book,
+                    &files,
+                    options.version,
+                    options.toc_title.as_deref(),
+                    &mut zip,
+                    options_deflate,
+                )?;
+            }
+            write_chapters_html5(book, &files, options, &mut zip, options_deflate)?;
+            if options.about_page {
+                write_about_xhtml(book, options.version, &mut zip, options_deflate)?;
             }
-            write_chapters_html5(book, &mut zip, options_deflate)?;
         }
         EpubVersion::Epub2 => {
-            write_opf2(book, &cover, include_toc_page, &mut zip, options_deflate)?;
-            write_ncx(book, &mut zip, options_deflate)?;
-            write_cover_xhtml(book, &cover, &mut zip, options_deflate)?;
-            if include_toc_page {
-                write_toc_page_xhtml(book, &mut zip, options_deflate)?;
+            write_opf2(
+                book,
+                &cover,
+                &images,
+                options,
+                &files,
+                &mut zip,
+                options_deflate,
+            )?;
+            write_ncx(book, &files, &mut zip, options_deflate)?;
+            write_cover_xhtml(book, options.version, &cover, &mut zip, options_deflate)?;
+            if has_description_page(book, options) {
+                write_description_xhtml(book, options.version, &mut zip, options_deflate)?;
+            }
+            if options.include_toc_page {
+                write_toc_page_xhtml(
+                    book,
+                    &files,
+                    options.version,
+                    options.toc_title.as_deref(),
+                    &mut zip,
+                    options_deflate,
+                )?;
+            }
+            write_chapters_xhtml11(book, &files, options, &mut zip, options_deflate)?;
+            if options.about_page {
+                write_about_xhtml(book, options.version, &mut zip, options_deflate)?;
             }
-            write_chapters_xhtml11(book, &mut zip, options_deflate)?;
         }
     }
 
@@ -147,8 +435,172 @@ pub fn write_epub(
         zip.start_file(name, options_deflate)?;
         zip.write_all(data)?;
     }
+    for image in &images {
+        zip.start_file(format!("{}{}", OEBPS_PREFIX, image.href), options_deflate)?;
+        zip.write_all(&image.data)?;
+    }
+
+    if options.verify_links {
+        verify_links(&zip.written, &zip.documents)?;
+    }
+
+    Ok(zip.inner.finish()?)
+}
+
+/// Zip writer that records every entry name and keeps a copy of the documents that carry links
+/// (OPF, nav.xhtml, toc.ncx) so [verify_links] can cross-check them before the archive is closed.
+struct EpubZip<W: Write + Seek> {
+    inner: ZipWriter<W>,
+    written: Vec<String>,
+    documents: BTreeMap<String, String>,
+    current: Option<String>,
+}
+
+impl<W: Write + Seek> EpubZip<W> {
+    fn new(w: W) -> Self {
+        Self {
+            inner: ZipWriter::new(w),
+            written: Vec::new(),
+            documents: BTreeMap::new(),
+            current: None,
+        }
+    }
+
+    fn start_file(
+        &mut self,
+        name: impl Into<String>,
+        options: SimpleFileOptions,
+    ) -> Result<(), EpubError> {
+        let name = name.into();
+        self.inner.start_file(name.clone(), options)?;
+        let is_link_document =
+            name.ends_with(".opf") || name.ends_with("/nav.xhtml") || name.ends_with(".ncx");
+        if is_link_document {
+            self.documents.insert(name.clone(), String::new());
+        }
+        self.current = is_link_document.then(|| name.clone());
+        self.written.push(name);
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> Write for EpubZip<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        if let Some(doc) = self
+            .current
+            .as_ref()
+            .and_then(|c| self.documents.get_mut(c))
+        {
+            doc.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+static XML_COMMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").expect("static regex"));
+/// An XML start or empty-element tag: name (group 1, with any namespace prefix) and attributes.
+static XML_START_TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<([A-Za-z_][-\w.:]*)((?:\s+[^\s=/>]+\s*=\s*(?:"[^"]*"|'[^']*'))*)\s*/?>"#)
+        .expect("static regex")
+});
+static XML_ATTR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"([^\s=/>]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("static regex")
+});
+
+/// Attributes of every `element` in the XML document `doc`, matched case-sensitively on the local
+/// name (`opf:item` counts as `item`), with entity references in values decoded. Comments are
+/// skipped.
+fn xml_elements(doc: &str, element: &str) -> Vec<BTreeMap<String, String>> {
+    let doc = XML_COMMENT.replace_all(doc, "");
+    XML_START_TAG
+        .captures_iter(&doc)
+        .filter(|caps| caps[1].rsplit(':').next() == Some(element))
+        .map(|caps| {
+            XML_ATTR
+                .captures_iter(&caps[2])
+                .map(|a| {
+                    let value = a.get(2).or_else(|| a.get(3)).map_or("", |m| m.as_str());
+                    (a[1].to_string(), xml_unescape(value))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Values of `attr` on every `element` in the XML document `doc` (see [xml_elements]).
+fn attr_values(doc: &str, element: &str, attr: &str) -> Vec<String> {
+    xml_elements(doc, element)
+        .into_iter()
+        .filter_map(|mut attrs| attrs.remove(attr))
+        .collect()
+}
+
+/// Decode the five predefined XML entities.
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Lightweight internal-reference check (`--verify-links`): every manifest href was written to
+/// the zip, and every spine idref, guide reference, nav link, and NCX target resolves to a
+/// manifest item. Paths are relative to the OPF directory; fragments are ignored.
+fn verify_links(written: &[String], documents: &BTreeMap<String, String>) -> Result<(), EpubError> {
+    let opf_name = format!("{}content.opf", OEBPS_PREFIX);
+    let broken = |document: &str, target: &str| EpubError::BrokenLink {
+        document: document.to_string(),
+        target: target.to_string(),
+    };
+    let opf = documents
+        .get(&opf_name)
+        .ok_or_else(|| broken("META-INF/container.xml", &opf_name))?;
+
+    let mut ids = Vec::new();
+    let mut hrefs = Vec::new();
+    for item in xml_elements(opf, "item") {
+        let (Some(id), Some(href)) = (item.get("id"), item.get("href")) else {
+            continue;
+        };
+        if !written
+            .iter()
+            .any(|w| *w == format!("{}{}", OEBPS_PREFIX, href))
+        {
+            return Err(broken(&opf_name, href));
+        }
+        ids.push(id.clone());
+        hrefs.push(href.clone());
+    }
+
+    let mut idrefs = attr_values(opf, "itemref", "idref");
+    idrefs.extend(attr_values(opf, "spine", "toc"));
+    if let Some(idref) = idrefs.iter().find(|r| !ids.contains(r)) {
+        return Err(broken(&opf_name, idref));
+    }
 
-    zip.finish()?;
+    let mut links = vec![(opf_name.clone(), attr_values(opf, "reference", "href"))];
+    for (name, doc) in documents {
+        if name.ends_with("/nav.xhtml") {
+            links.push((name.clone(), attr_values(doc, "a", "href")));
+        } else if name.ends_with(".ncx") {
+            links.push((name.clone(), attr_values(doc, "content", "src")));
+        }
+    }
+    for (name, targets) in &links {
+        for target in targets {
+            let path = target.split('#').next().unwrap_or_default();
+            if !hrefs.iter().any(|h| h == path) {
+                return Err(broken(name, target));
+            }
+        }
+    }
     Ok(())
 }
 
@@ -166,13 +618,22 @@ fn validate_book(book: &Book) -> Result<(), EpubError> {
 }
 
 /// Fetch cover image. On failure (or no URL), returns TitleOnly so a title-only cover page is still emitted when a URL was set.
-fn fetch_cover(book: &Book, client: &mut PoliteClient) -> CoverOutcome {
+fn fetch_cover(book: &Book, client: &mut PoliteClient, options: &EpubOptions) -> CoverOutcome {
+    let timeout_secs = options.cover_timeout_secs;
+    let delay = options.asset_delay_secs.map(Duration::from_secs);
     let url = match &book.cover_url {
         Some(u) if !u.is_empty() => u.as_str(),
         _ => return CoverOutcome::NoCover,
     };
-    let response = match client.get_with_retry(url) {
+    let response = match client.get_asset(url, Duration::from_secs(timeout_secs), delay) {
         Ok(r) => r,
+        Err(e) if e.is_timeout() => {
+            eprintln!(
+                "Cover image timed out after {}s ({}). Using title-only cover page.",
+                timeout_secs, url
+            );
+            return CoverOutcome::TitleOnly;
+        }
         Err(e) => {
             eprintln!(
                 "Cover image could not be fetched ({}): {}. Using title-only cover page.",
@@ -189,18 +650,13 @@ fn fetch_cover(book: &Book, client: &mut PoliteClient) -> CoverOutcome {
         );
         return CoverOutcome::TitleOnly;
     }
-    let ext = response
-        .headers()
-        .get("content-type")
-        .and_then(|v| v.to_str().ok())
-        .map(|ct| {
-            if ct.contains("jpeg") || ct.contains("jpg") {
-                "jpg"
-            } else {
-                "png"
-            }
-        })
-        .unwrap_or("png");
+    let ext = image_ext(
+        response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok()),
+        url,
+    );
     match response.bytes() {
         Ok(b) => CoverOutcome::Image {
             data: b.to_vec(),
@@ -216,21 +672,282 @@ fn fetch_cover(book: &Book, client: &mut PoliteClient) -> CoverOutcome {
     }
 }
 
-fn identifier(book: &Book) -> String {
-    book.source_url
+/// Fetch the cover image for formats other than EPUB, with the same timeout, delay, and
+/// warnings as the EPUB cover. Returns the image bytes and file extension, or None.
+pub(crate) fn fetch_cover_image(
+    book: &Book,
+    client: &mut PoliteClient,
+    cover_timeout_secs: u64,
+    asset_delay_secs: Option<u64>,
+) -> Option<(Vec<u8>, &'static str)> {
+    let options = EpubOptions {
+        cover_timeout_secs,
+        asset_delay_secs,
+        ..EpubOptions::default()
+    };
+    match fetch_cover(book, client, &options) {
+        CoverOutcome::Image { data, ext } => Some((data, ext)),
+        CoverOutcome::NoCover | CoverOutcome::TitleOnly => None,
+    }
+}
+
+/// File extension for a downloaded image: from the Content-Type, else from the URL path, else png.
+fn image_ext(content_type: Option<&str>, url: &str) -> &'static str {
+    let from_name = |name: &str| match name.to_ascii_lowercase().as_str() {
+        n if n.contains("jpeg") || n.contains("jpg") => Some("jpg"),
+        n if n.contains("png") => Some("png"),
+        n if n.contains("gif") => Some("gif"),
+        n if n.contains("webp") => Some("webp"),
+        n if n.contains("svg") => Some("svg"),
+        _ => None,
+    };
+    let url_ext = || {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        path.rsplit_once('.')
+            .filter(|(_, ext)| !ext.contains('/'))
+            .and_then(|(_, ext)| from_name(ext))
+    };
+    content_type
+        .filter(|ct| ct.starts_with("image/"))
+        .and_then(from_name)
+        .or_else(url_ext)
+        .unwrap_or("png")
+}
+
+/// An `<img>` with its `src` (group 2), plus the `<p>` around it (groups 1 and 3) when present.
+static IMG_TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(<p>\s*)?<img\b[^>]*?\ssrc="([^"]*)"[^>]*>(\s*</p>)?"#).expect("static regex")
+});
+
+/// Download the images referenced by `<img src>` in chapter bodies and point each `src` at its
+/// copy under `images/`. An image that cannot be fetched (or any image, when
+/// [EpubOptions::fetch_images] is off) is removed from the body, together with a `<p>` that held
+/// only that image; failures warn to stderr. The same URL is fetched once. Returns None for the
+/// book when no chapter has an image.
+fn embed_chapter_images(
+    book: &Book,
+    client: &mut PoliteClient,
+    options: &EpubOptions,
+) -> (Option<Book>, Vec<ChapterImage>) {
+    if !book.chapters.iter().any(|ch| ch.body.contains("<img")) {
+        return (None, Vec::new());
+    }
+    let timeout = Duration::from_secs(options.cover_timeout_secs);
+    let delay = options.asset_delay_secs.map(Duration::from_secs);
+    let mut copy = book.clone();
+    let mut images: Vec<ChapterImage> = Vec::new();
+    let mut seen: BTreeMap<String, Option<String>> = BTreeMap::new();
+    for (i, ch) in copy.chapters.iter_mut().enumerate() {
+        let mut count = 0;
+        let body = IMG_TAG.replace_all(&ch.body, |caps: &regex::Captures| {
+            // The `<p>` wrapper goes with a dropped image only when the image was all it held.
+            let dropped = || match (caps.get(1), caps.get(3)) {
+                (Some(_), Some(_)) => String::new(),
+                (open, close) => format!(
+                    "{}{}",
+                    open.map_or("", |m| m.as_str()),
+                    close.map_or("", |m| m.as_str())
+                ),
+            };
+            if !options.fetch_images {
+                return dropped();
+            }
+            let url = caps[2].replace("&amp;", "&");
+            let href = seen.entry(url.clone()).or_insert_with(|| {
+                let (data, ext) = fetch_image(&url, client, timeout, delay)?;
+                count += 1;
+                let href = format!("images/ch{}-{}.{}", i + 1, count, ext);
+                images.push(ChapterImage {
+                    href: href.clone(),
+                    data,
+                    ext,
+                });
+                Some(href)
+            });
+            match href {
+                Some(href) => caps[0].replacen(
+                    &format!(r#"src="{}""#, &caps[2]),
+                    &format!(r#"src="{}""#, href),
+                    1,
+                ),
+                None => dropped(),
+            }
+        });
+        ch.body = body.into_owned();
+    }
+    (Some(copy), images)
+}
+
+/// One chapter image as (bytes, extension). None, with a warning, when it cannot be fetched.
+fn fetch_image(
+    url: &str,
+    client: &mut PoliteClient,
+    timeout: Duration,
+    delay: Option<Duration>,
+) -> Option<(Vec<u8>, &'static str)> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        eprintln!("Chapter image skipped (not an http(s) URL): {}", url);
+        return None;
+    }
+    let response = match client.get_asset(url, timeout, delay) {
+        Ok(r) if r.status().is_success() => r,
+        Ok(r) => {
+            eprintln!(
+                "Chapter image could not be fetched (HTTP {}): {}. Leaving it out.",
+                r.status().as_u16(),
+                url
+            );
+            return None;
+        }
+        Err(e) => {
+            eprintln!(
+                "Chapter image could not be fetched ({}): {}. Leaving it out.",
+                url, e
+            );
+            return None;
+        }
+    };
+    let ext = image_ext(
+        response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok()),
+        url,
+    );
+    match response.bytes() {
+        Ok(b) => Some((b.to_vec(), ext)),
+        Err(e) => {
+            eprintln!(
+                "Chapter image could not be read ({}): {}. Leaving it out.",
+                url, e
+            );
+            None
+        }
+    }
+}
+
+/// `<dc:rights>` text: the explicit statement if non-empty, else a credit to the author pointing
+/// at the source page. None (element omitted) for a blank override or a book without a source URL.
+fn rights_statement(book: &Book, rights: Option<&str>) -> Option<String> {
+    match rights.map(str::trim) {
+        Some(r) => (!r.is_empty()).then(|| r.to_string()),
+        None => book
+            .source_url
+            .as_deref()
+            .map(|url| format!("© {}; see {}", book.author, url)),
+    }
+}
+
+/// `<dc:contributor>` lines (each prefixed with a newline) for contributors with a name. The MARC
+/// relator role goes in an `opf:role` attribute for EPUB 2 and a refining `role` meta for EPUB 3.
+fn contributors(book: &Book, version: EpubVersion) -> String {
+    let mut out = String::new();
+    let named = book
+        .contributors
+        .iter()
+        .filter(|c| !c.name.trim().is_empty());
+    for (i, c) in named.enumerate() {
+        let name = xml_escape(c.name.trim());
+        let role = xml_escape(c.role.trim());
+        match version {
+            EpubVersion::Epub3 => {
+                let id = format!("contributor-{}", i + 1);
+                out.push_str(&format!(
+                    "\n    <dc:contributor id=\"{}\">{}</dc:contributor>",
+                    id, name
+                ));
+                if !role.is_empty() {
+                    out.push_str(&format!(
+                        "\n    <meta refines=\"#{}\" property=\"role\" scheme=\"marc:relators\">{}</meta>",
+                        id, role
+                    ));
+                }
+            }
+            EpubVersion::Epub2 if role.is_empty() => {
+                out.push_str(&format!("\n    <dc:contributor>{}</dc:contributor>", name));
+            }
+            EpubVersion::Epub2 => out.push_str(&format!(
+                "\n    <dc:contributor opf:role=\"{}\">{}</dc:contributor>",
+                role, name
+            )),
+        }
+    }
+    out
+}
+
+/// One `<dc:subject>` per genre tag (blank and repeated tags dropped), each line prefixed with a
+/// newline. Empty when the book has no tags.
+fn subjects(book: &Book) -> String {
+    clean_tags(&book.tags)
+        .iter()
+        .map(|t| format!("\n    <dc:subject>{}</dc:subject>", xml_escape(t)))
+        .collect()
+}
+
+/// Series metadata for the OPF, each line prefixed with a newline to follow `<dc:language>`:
+/// EPUB 3 `belongs-to-collection` (with `group-position` when the index is set) or the legacy
+/// `calibre:series` / `calibre:series_index` metas for EPUB 2. Empty when the book has no series.
+fn series_meta(book: &Book, version: EpubVersion) -> String {
+    let Some(series) = book.series.as_deref() else {
+        return String::new();
+    };
+    let series = xml_escape(series);
+    match version {
+        EpubVersion::Epub3 => {
+            let mut meta = format!(
+                "\n    <meta property=\"belongs-to-collection\" id=\"series\">{}</meta>\n    <meta refines=\"#series\" property=\"collection-type\">series</meta>",
+                series
+            );
+            if let Some(index) = book.series_index {
+                meta.push_str(&format!(
+                    "\n    <meta refines=\"#series\" property=\"group-position\">{}</meta>",
+                    index
+                ));
+            }
+            meta
+        }
+        EpubVersion::Epub2 => {
+            let mut meta = format!(
+                "\n    <meta name=\"calibre:series\" content=\"{}\"/>",
+                series
+            );
+            if let Some(index) = book.series_index {
+                meta.push_str(&format!(
+                    "\n    <meta name=\"calibre:series_index\" content=\"{}\"/>",
+                    index
+                ));
+            }
+            meta
+        }
+    }
+}
+
+/// `--title-with-author`: the scraped title as a legacy `<meta>` (valid in EPUB 2 and 3), prefixed
+/// with a newline like [series_meta]. Empty when the title was not changed.
+fn original_title_meta(book: &Book) -> String {
+    book.original_title
         .as_deref()
-        .unwrap_or("urn:rdrscrape:book")
-        .to_string()
+        .map(|raw| {
+            format!(
+                "\n    <meta name=\"rdrscrape:original-title\" content=\"{}\"/>",
+                xml_escape(raw)
+            )
+        })
+        .unwrap_or_default()
 }
 
 fn write_opf3(
     book: &Book,
     cover: &CoverOutcome,
-    include_ncx: bool,
-    include_toc_page: bool,
-    zip: &mut ZipWriter<impl Write + Seek>,
+    images: &[ChapterImage],
+    epub: &EpubOptions,
+    files: &[String],
+    zip: &mut EpubZip<impl Write + Seek>,
     options: SimpleFileOptions,
 ) -> Result<(), EpubError> {
+    let (include_ncx, include_toc_page) = (epub.include_ncx, epub.include_toc_page);
+    let include_about_page = epub.about_page;
+    let include_description_page = has_description_page(book, epub);
     let id = xml_escape(&identifier(book));
     let title = xml_escape(&book.title);
     let creator = xml_escape(&book.author);
@@ -239,10 +956,14 @@ fn write_opf3(
         .as_ref()
         .map(|d| xml_escape(d))
         .unwrap_or_default();
+    let rights_el = rights_statement(book, epub.rights.as_deref())
+        .map(|r| format!("\n    <dc:rights>{}</dc:rights>", xml_escape(&r)))
+        .unwrap_or_default();
 
     let mut manifest = String::from(
         r#"<item id="content-opf" href="content.opf" media-type="application/oebps-package+xml"/>
   <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+  <item id="css" href="style.css" media-type="text/css"/>
 "#,
     );
     if include_ncx {
@@ -254,15 +975,31 @@ fn write_opf3(
     let has_cover_page = !matches!(cover, CoverOutcome::NoCover);
     if let CoverOutcome::Image { ext, .. } = cover {
         manifest.push_str(&format!(
-            r#"  <item id="cover-img" href="images/cover.{}" media-type="{}" properties="cover-image"/>
+            r#"  <item id="{}" href="images/cover.{}" media-type="{}" properties="cover-image"/>
 "#,
+            COVER_IMAGE_ID,
             ext,
-            cover_media_type(ext)
+            image_media_type(ext)
+        ));
+    }
+    for image in images {
+        manifest.push_str(&format!(
+            r#"  <item id="{}" href="{}" media-type="{}"/>
+"#,
+            image.id(),
+            image.href,
+            image_media_type(image.ext)
         ));
     }
     if has_cover_page {
         manifest.push_str(
             r#"  <item id="cover" href="cover.xhtml" media-type="application/xhtml+xml"/>
+"#,
+        );
+    }
+    if include_description_page {
+        manifest.push_str(
+            r#"  <item id="description" href="description.xhtml" media-type="application/xhtml+xml"/>
 "#,
         );
     }
@@ -272,20 +1009,32 @@ fn write_opf3(
 "#,
         );
     }
-    for (i, _) in book.chapters.iter().enumerate() {
+    for (i, file) in files.iter().enumerate() {
         manifest.push_str(&format!(
-            r#"  <item id="chapter-{}" href="chapter-{}.xhtml" media-type="application/xhtml+xml"/>
+            r#"  <item id="chapter-{}" href="{}" media-type="application/xhtml+xml"/>
 "#,
             i + 1,
-            i + 1
+            file
         ));
     }
+    if include_about_page {
+        manifest.push_str(
+            r#"  <item id="about" href="about.xhtml" media-type="application/xhtml+xml"/>
+"#,
+        );
+    }
 
-    // Spine: reading order only (cover, optional toc page, then chapters). Nav is not in spine.
+    // Spine: reading order only (cover, optional description and toc pages, then chapters). Nav is not in spine.
     let mut spine = String::new();
     if has_cover_page {
         spine.push_str(r#"  <itemref idref="cover"/>"#);
     }
+    if include_description_page {
+        if !spine.is_empty() {
+            spine.push_str("\n  ");
+        }
+        spine.push_str(r#"<itemref idref="description"/>"#);
+    }
     if include_toc_page {
         if !spine.is_empty() {
             spine.push_str("\n  ");
@@ -298,6 +1047,9 @@ fn write_opf3(
         }
         spine.push_str(&format!("<itemref idref=\"chapter-{}\"/>", i + 1));
     }
+    if include_about_page {
+        spine.push_str("\n  <itemref idref=\"about\"/>");
+    }
     if spine.is_empty() {
         spine.push_str(r#"  <itemref idref="chapter-1"/>"#);
     }
@@ -315,8 +1067,9 @@ fn write_opf3(
   <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
     <dc:identifier id="book-id">{id}</dc:identifier>
     <dc:title>{title}</dc:title>
-    <dc:creator>{creator}</dc:creator>
-    <dc:language>en</dc:language>
+    <dc:creator>{creator}</dc:creator>{contributors_el}
+    <dc:language>{language}</dc:language>{rights_el}{subjects_el}{series_el}{original_title_el}
+    <meta property="dcterms:modified">{modified}</meta>
     {description_el}
   </metadata>
   <manifest>
@@ -332,6 +1085,13 @@ fn write_opf3(
         id = id,
         title = title,
         creator = creator,
+        language = xml_escape(book.language.as_deref().unwrap_or("en")),
+        rights_el = rights_el,
+        contributors_el = contributors(book, EpubVersion::Epub3),
+        subjects_el = subjects(book),
+        series_el = series_meta(book, EpubVersion::Epub3),
+        original_title_el = original_title_meta(book),
+        modified = modified_timestamp(),
         description_el = if description.is_empty() {
             String::new()
         } else {
@@ -350,10 +1110,14 @@ fn write_opf3(
 fn write_opf2(
     book: &Book,
     cover: &CoverOutcome,
-    include_toc_page: bool,
-    zip: &mut ZipWriter<impl Write + Seek>,
+    images: &[ChapterImage],
+    epub: &EpubOptions,
+    files: &[String],
+    zip: &mut EpubZip<impl Write + Seek>,
     options: SimpleFileOptions,
 ) -> Result<(), EpubError> {
+    let (include_toc_page, include_about_page) = (epub.include_toc_page, epub.about_page);
+    let include_description_page = has_description_page(book, epub);
     let id = xml_escape(&identifier(book));
     let title = xml_escape(&book.title);
     let creator = xml_escape(&book.author);
@@ -362,24 +1126,44 @@ fn write_opf2(
         .as_ref()
         .map(|d| xml_escape(d))
         .unwrap_or_default();
+    let rights_el = rights_statement(book, epub.rights.as_deref())
+        .map(|r| format!("\n    <dc:rights>{}</dc:rights>", xml_escape(&r)))
+        .unwrap_or_default();
 
     let mut manifest = String::from(
         r#"<item id="content-opf" href="content.opf" media-type="application/oebps-package+xml"/>
   <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+  <item id="css" href="style.css" media-type="text/css"/>
 "#,
     );
     let has_cover_page = !matches!(cover, CoverOutcome::NoCover);
     if let CoverOutcome::Image { ext, .. } = cover {
         manifest.push_str(&format!(
-            r#"  <item id="cover-img" href="images/cover.{}" media-type="{}"/>
+            r#"  <item id="{}" href="images/cover.{}" media-type="{}"/>
 "#,
+            COVER_IMAGE_ID,
             ext,
-            cover_media_type(ext)
+            image_media_type(ext)
+        ));
+    }
+    for image in images {
+        manifest.push_str(&format!(
+            r#"  <item id="{}" href="{}" media-type="{}"/>
+"#,
+            image.id(),
+            image.href,
+            image_media_type(image.ext)
         ));
     }
     if has_cover_page {
         manifest.push_str(
             r#"  <item id="cover" href="cover.xhtml" media-type="application/xhtml+xml"/>
+"#,
+        );
+    }
+    if include_description_page {
+        manifest.push_str(
+            r#"  <item id="description" href="description.xhtml" media-type="application/xhtml+xml"/>
 "#,
         );
     }
@@ -389,20 +1173,33 @@ fn write_opf2(
 "#,
         );
     }
-    for (i, _) in book.chapters.iter().enumerate() {
+    for (i, file) in files.iter().enumerate() {
         manifest.push_str(&format!(
-            r#"  <item id="chapter-{}" href="chapter-{}.xhtml" media-type="application/xhtml+xml"/>
+            r#"  <item id="chapter-{}" href="{}" media-type="application/xhtml+xml"/>
 "#,
             i + 1,
-            i + 1
+            file
         ));
     }
+    if include_about_page {
+        manifest.push_str(
+            r#"  <item id="about" href="about.xhtml" media-type="application/xhtml+xml"/>
+"#,
+        );
+    }
 
-    // EPUB 2 spine: toc="ncx" references manifest; spine is cover, optional toc page, then chapters.
+    // EPUB 2 spine: toc="ncx" references manifest; spine is cover, optional description and toc
+    // pages, then chapters.
     let mut spine = String::new();
     if has_cover_page {
         spine.push_str(r#"  <itemref idref="cover"/>"#);
     }
+    if include_description_page {
+        if !spine.is_empty() {
+            spine.push_str("\n  ");
+        }
+        spine.push_str(r#"<itemref idref="description"/>"#);
+    }
     if include_toc_page {
         if !spine.is_empty() {
             spine.push_str("\n  ");
@@ -415,6 +1212,9 @@ fn write_opf2(
         }
         spine.push_str(&format!("<itemref idref=\"chapter-{}\"/>", i + 1));
     }
+    if include_about_page {
+        spine.push_str("\n  <itemref idref=\"about\"/>");
+    }
     if spine.is_empty() {
         spine.push_str(r#"  <itemref idref="chapter-1"/>"#);
     }
@@ -425,22 +1225,25 @@ fn write_opf2(
         ""
     };
 
+    // EPUB 2 readers (Kindle, older Kobo) only find the cover image through this meta.
     let cover_meta = if matches!(cover, CoverOutcome::Image { .. }) {
-        r#"    <meta name="cover" content="cover-img"/>
-"#
+        format!(
+            "\n    <meta name=\"cover\" content=\"{}\"/>",
+            COVER_IMAGE_ID
+        )
     } else {
-        ""
+        String::new()
     };
 
     let opf = format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
 <package xmlns="http://www.idpf.org/2007/opf" unique-identifier="book-id" version="2.0"
   xmlns:dc="http://purl.org/dc/elements/1.1/">
-  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
     <dc:identifier id="book-id">{id}</dc:identifier>
     <dc:title>{title}</dc:title>
-    <dc:creator>{creator}</dc:creator>
-    <dc:language>en</dc:language>
+    <dc:creator>{creator}</dc:creator>{contributors_el}
+    <dc:language>{language}</dc:language>{rights_el}{subjects_el}{series_el}{original_title_el}
     {description_el}{cover_meta}
   </metadata>
   <manifest>
@@ -456,6 +1259,12 @@ fn write_opf2(
         id = id,
         title = title,
         creator = creator,
+        language = xml_escape(book.language.as_deref().unwrap_or("en")),
+        rights_el = rights_el,
+        contributors_el = contributors(book, EpubVersion::Epub2),
+        subjects_el = subjects(book),
+        series_el = series_meta(book, EpubVersion::Epub2),
+        original_title_el = original_title_meta(book),
         description_el = if description.is_empty() {
             String::new()
         } else {
@@ -472,38 +1281,34 @@ fn write_opf2(
     Ok(())
 }
 
-fn cover_media_type(ext: &str) -> &'static str {
-    match ext {
-        "jpg" => "image/jpeg",
-        _ => "image/png",
-    }
-}
-
 fn write_nav_xhtml(
     book: &Book,
-    zip: &mut ZipWriter<impl Write + Seek>,
+    files: &[String],
+    toc_title: Option<&str>,
+    zip: &mut EpubZip<impl Write + Seek>,
     options: SimpleFileOptions,
 ) -> Result<(), EpubError> {
     let mut nav_links = String::new();
     for (i, ch) in book.chapters.iter().enumerate() {
         let title = html_escape_attr(&ch.title);
         nav_links.push_str(&format!(
-            r#"    <li><a href="chapter-{}.xhtml">{}</a></li>
+            r#"    <li id="nav-{}"><a href="{}#{}">{}</a></li>
 "#,
-            i + 1,
+            chapter_anchor_id(ch),
+            files[i],
+            chapter_anchor_id(ch),
             title
         ));
     }
     let nav = format!(
-        r#"<!DOCTYPE html>
-<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+        r#"{}<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
 <head>
-  <meta charset="UTF-8"/>
-  <title>Table of Contents</title>
+  {}
+  <title>{}</title>
 </head>
 <body>
   <nav epub:type="toc">
-    <h1>Contents</h1>
+    <h1>{}</h1>
     <ol>
 {}
     </ol>
@@ -511,6 +1316,10 @@ fn write_nav_xhtml(
 </body>
 </html>
 "#,
+        xhtml_prolog(EpubVersion::Epub3),
+        head_meta(EpubVersion::Epub3),
+        html_escape_attr(toc_title.unwrap_or("Table of Contents")),
+        html_escape_attr(toc_title.unwrap_or("Contents")),
         nav_links
     );
     zip.start_file(format!("{}nav.xhtml", OEBPS_PREFIX), options)?;
@@ -521,34 +1330,40 @@ fn write_nav_xhtml(
 /// Writes a visible table-of-contents page (toc.xhtml) for the reading spine. Placed after the cover.
 fn write_toc_page_xhtml(
     book: &Book,
-    zip: &mut ZipWriter<impl Write + Seek>,
+    files: &[String],
+    version: EpubVersion,
+    toc_title: Option<&str>,
+    zip: &mut EpubZip<impl Write + Seek>,
     options: SimpleFileOptions,
 ) -> Result<(), EpubError> {
     let mut items = String::new();
     for (i, ch) in book.chapters.iter().enumerate() {
         let title = html_escape_attr(&ch.title);
         items.push_str(&format!(
-            r#"    <li><a href="chapter-{}.xhtml">{}</a></li>
+            r#"    <li><a href="{}">{}</a></li>
 "#,
-            i + 1,
-            title
+            files[i], title
         ));
     }
+    let heading = html_escape_attr(toc_title.unwrap_or("Table of Contents"));
     let toc_xhtml = format!(
-        r#"<!DOCTYPE html>
-<html xmlns="http://www.w3.org/1999/xhtml">
+        r#"{}<html xmlns="http://www.w3.org/1999/xhtml">
 <head>
-  <meta charset="UTF-8"/>
-  <title>Table of Contents</title>
+  {}
+  <title>{}</title>
 </head>
 <body>
-  <h1>Table of Contents</h1>
+  <h1>{}</h1>
   <ol>
 {}
   </ol>
 </body>
 </html>
 "#,
+        xhtml_prolog(version),
+        head_meta(version),
+        heading,
+        heading,
         items
     );
     zip.start_file(format!("{}toc.xhtml", OEBPS_PREFIX), options)?;
@@ -558,7 +1373,8 @@ fn write_toc_page_xhtml(
 
 fn write_ncx(
     book: &Book,
-    zip: &mut ZipWriter<impl Write + Seek>,
+    files: &[String],
+    zip: &mut EpubZip<impl Write + Seek>,
     options: SimpleFileOptions,
 ) -> Result<(), EpubError> {
     let title = xml_escape(&book.title);
@@ -568,13 +1384,14 @@ fn write_ncx(
         nav_points.push_str(&format!(
             r#"    <navPoint id="navpoint-{}" playOrder="{}">
       <navLabel><text>{}</text></navLabel>
-      <content src="chapter-{}.xhtml"/>
+      <content src="{}#{}"/>
     </navPoint>
 "#,
-            i + 1,
+            ch.index,
             i + 1,
             label,
-            i + 1
+            files[i],
+            chapter_anchor_id(ch)
         ));
     }
     let ncx = format!(
@@ -602,8 +1419,9 @@ fn write_ncx(
 
 fn write_cover_xhtml(
     book: &Book,
+    version: EpubVersion,
     cover: &CoverOutcome,
-    zip: &mut ZipWriter<impl Write + Seek>,
+    zip: &mut EpubZip<impl Write + Seek>,
     options: SimpleFileOptions,
 ) -> Result<(), EpubError> {
     let body = match cover {
@@ -627,11 +1445,9 @@ fn write_cover_xhtml(
         ),
     };
     let cover_xhtml = format!(
-        r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE html>
-<html xmlns="http://www.w3.org/1999/xhtml">
+        r#"{}<html xmlns="http://www.w3.org/1999/xhtml">
 <head>
-  <meta charset="UTF-8"/>
+  {}
   <title>Cover</title>
 </head>
 <body>
@@ -639,6 +1455,8 @@ fn write_cover_xhtml(
 </body>
 </html>
 "#,
+        xhtml_prolog(version),
+        head_meta(version),
         body
     );
     zip.start_file(format!("{}cover.xhtml", OEBPS_PREFIX), options)?;
@@ -646,19 +1464,206 @@ fn write_cover_xhtml(
     Ok(())
 }
 
+/// Whether [write_epub] adds description.xhtml: requested and the book has a non-blank description.
+fn has_description_page(book: &Book, epub: &EpubOptions) -> bool {
+    epub.description_page
+        && book
+            .description
+            .as_deref()
+            .is_some_and(|d| !d.trim().is_empty())
+}
+
+/// Description text as `<p>` elements: paragraphs are separated by blank lines, and the lines
+/// within one paragraph are joined with a space.
+fn description_paragraphs(description: &str) -> String {
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for line in description.lines().map(str::trim) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(current.join(" "));
+                current.clear();
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current.join(" "));
+    }
+    paragraphs
+        .iter()
+        .map(|p| format!("  <p>{}</p>", html_escape_attr(p)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes the "Description" page (description.xhtml) shown after the cover with `--description-page`.
+fn write_description_xhtml(
+    book: &Book,
+    version: EpubVersion,
+    zip: &mut EpubZip<impl Write + Seek>,
+    options: SimpleFileOptions,
+) -> Result<(), EpubError> {
+    let description_xhtml = format!(
+        r#"{}<html xmlns="http://www.w3.org/1999/xhtml">
+<head>
+  {}
+  <title>Description</title>
+</head>
+<body>
+  <h1>{}</h1>
+{}
+</body>
+</html>
+"#,
+        xhtml_prolog(version),
+        head_meta(version),
+        html_escape_attr(&book.title),
+        description_paragraphs(book.description.as_deref().unwrap_or_default())
+    );
+    zip.start_file(format!("{}description.xhtml", OEBPS_PREFIX), options)?;
+    zip.write_all(description_xhtml.as_bytes())?;
+    Ok(())
+}
+
+/// Writes the closing "About" page (about.xhtml): source URL, scrape date (UTC), tool version, and chapter count.
+fn write_about_xhtml(
+    book: &Book,
+    version: EpubVersion,
+    zip: &mut EpubZip<impl Write + Seek>,
+    options: SimpleFileOptions,
+) -> Result<(), EpubError> {
+    let source = match book.source_url.as_deref() {
+        Some(url) => {
+            let url = html_escape_attr(url);
+            format!(r#"<a href="{}">{}</a>"#, url, url)
+        }
+        None => "unknown".to_string(),
+    };
+    let about_xhtml = format!(
+        r#"{}<html xmlns="http://www.w3.org/1999/xhtml">
+<head>
+  {}
+  <title>About this book</title>
+</head>
+<body>
+  <h1>About this book</h1>
+  <p>{} by {}</p>
+  <p>Source: {}</p>
+  <p>Scraped on {} with rdrscrape {}.</p>
+  <p>Chapters: {}</p>
+</body>
+</html>
+"#,
+        xhtml_prolog(version),
+        head_meta(version),
+        html_escape_attr(&book.title),
+        html_escape_attr(&book.author),
+        source,
+        &modified_timestamp()[..10],
+        env!("CARGO_PKG_VERSION"),
+        book.chapters.len()
+    );
+    zip.start_file(format!("{}about.xhtml", OEBPS_PREFIX), options)?;
+    zip.write_all(about_xhtml.as_bytes())?;
+    Ok(())
+}
+
+/// Write an OPDS 1.2 acquisition entry for `book` to `path`: a standalone Atom `<entry>` with
+/// title, author, summary (description), and an acquisition link to `epub_href`. Meant for
+/// personal OPDS catalog servers that build their feed from per-book entry files.
+pub fn write_opds_entry(book: &Book, epub_href: &str, path: &Path) -> Result<(), EpubError> {
+    std::fs::write(path, opds_entry(book, epub_href, &utc_datetime_now())).map_err(|e| {
+        EpubError::Io {
+            path: path.to_path_buf(),
+            source: e,
+        }
+    })
+}
+
+fn opds_entry(book: &Book, epub_href: &str, updated: &str) -> String {
+    let summary = book
+        .description
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| {
+            format!(
+                "  <summary type=\"text\">{}</summary>\n",
+                xml_escape(d.trim())
+            )
+        })
+        .unwrap_or_default();
+    let alternate = book
+        .source_url
+        .as_deref()
+        .map(|u| {
+            format!(
+                "  <link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>\n",
+                xml_escape(u)
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<entry xmlns="http://www.w3.org/2005/Atom" xmlns:dc="http://purl.org/dc/terms/">
+  <id>{}</id>
+  <title>{}</title>
+  <author><name>{}</name></author>
+  <updated>{}</updated>
+  <dc:language>{}</dc:language>
+{}  <link rel="http://opds-spec.org/acquisition" type="application/epub+zip" href="{}"/>
+{}</entry>
+"#,
+        xml_escape(&identifier(book)),
+        xml_escape(&book.title),
+        xml_escape(&book.author),
+        updated,
+        xml_escape(book.language.as_deref().unwrap_or("en")),
+        summary,
+        xml_escape(epub_href),
+        alternate
+    )
+}
+
+/// Stable fragment id for a chapter, derived from its 1-based TOC index (e.g. `ch12`).
+/// Each chapter file starts with an anchor carrying it; nav.xhtml and toc.ncx link to it.
+fn chapter_anchor_id(ch: &Chapter) -> String {
+    format!("ch{}", ch.index)
+}
+
+/// Chapter body with a top `<a id="ch{index}">` anchor, wrapped in Previous/Next links when `nav_links` is set.
+fn chapter_body_with_nav(ch: &Chapter, files: &[String], i: usize, nav_links: bool) -> String {
+    let anchor = format!(r#"<div><a id="{}"></a></div>"#, chapter_anchor_id(ch));
+    match chapter_nav_links(files, i).filter(|_| nav_links) {
+        Some(nav) => format!("{}\n{}\n{}\n{}", anchor, nav, ch.body, nav),
+        None => format!("{}\n{}", anchor, ch.body),
+    }
+}
+
+/// Text for a chapter page's `<title>`: "Chapter N" with `short`, else the (templated) title.
+fn chapter_title_element(ch: &Chapter, short: bool) -> String {
+    if short {
+        format!("Chapter {}", ch.index)
+    } else {
+        ch.title.clone()
+    }
+}
+
 fn write_chapters_html5(
     book: &Book,
-    zip: &mut ZipWriter<impl Write + Seek>,
+    files: &[String],
+    epub: &EpubOptions,
+    zip: &mut EpubZip<impl Write + Seek>,
     options: SimpleFileOptions,
 ) -> Result<(), EpubError> {
     for (i, ch) in book.chapters.iter().enumerate() {
-        let title = html_escape_attr(&ch.title);
-        let body = &ch.body;
+        let title = html_escape_attr(&chapter_title_element(ch, epub.short_title_element));
+        let body = chapter_body_with_nav(ch, files, i, epub.nav_links);
         let html = format!(
-            r#"<!DOCTYPE html>
-<html xmlns="http://www.w3.org/1999/xhtml">
+            r#"{}<html xmlns="http://www.w3.org/1999/xhtml">
 <head>
-  <meta charset="UTF-8"/>
+  {}
   <title>{}</title>
 </head>
 <body>
@@ -666,9 +1671,12 @@ fn write_chapters_html5(
 </body>
 </html>
 "#,
-            title, body
+            xhtml_prolog(EpubVersion::Epub3),
+            head_meta(EpubVersion::Epub3),
+            title,
+            body
         );
-        let name = format!("{}chapter-{}.xhtml", OEBPS_PREFIX, i + 1);
+        let name = format!("{}{}", OEBPS_PREFIX, files[i]);
         zip.start_file(name, options)?;
         zip.write_all(html.as_bytes())?;
     }
@@ -677,18 +1685,18 @@ fn write_chapters_html5(
 
 fn write_chapters_xhtml11(
     book: &Book,
-    zip: &mut ZipWriter<impl Write + Seek>,
+    files: &[String],
+    epub: &EpubOptions,
+    zip: &mut EpubZip<impl Write + Seek>,
     options: SimpleFileOptions,
 ) -> Result<(), EpubError> {
     for (i, ch) in book.chapters.iter().enumerate() {
-        let title = xml_escape(&ch.title);
-        let body = &ch.body;
+        let title = xml_escape(&chapter_title_element(ch, epub.short_title_element));
+        let body = chapter_body_with_nav(ch, files, i, epub.nav_links);
         let html = format!(
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">
-<html xmlns="http://www.w3.org/1999/xhtml">
+            r#"{}<html xmlns="http://www.w3.org/1999/xhtml">
 <head>
-  <meta charset="UTF-8"/>
+  {}
   <title>{}</title>
 </head>
 <body>
@@ -696,9 +1704,12 @@ fn write_chapters_xhtml11(
 </body>
 </html>
 "#,
-            title, body
+            xhtml_prolog(EpubVersion::Epub2),
+            head_meta(EpubVersion::Epub2),
+            title,
+            body
         );
-        let name = format!("{}chapter-{}.xhtml", OEBPS_PREFIX, i + 1);
+        let name = format!("{}{}", OEBPS_PREFIX, files[i]);
         zip.start_file(name, options)?;
         zip.write_all(html.as_bytes())?;
     }
@@ -723,7 +1734,7 @@ fn html_escape_attr(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::Chapter;
+    use crate::model::Contributor;
     use std::io::Read;
     use zip::read::ZipArchive;
 
@@ -731,24 +1742,182 @@ mod tests {
         Book {
             title: "Test Book".to_string(),
             author: "Test Author".to_string(),
-            description: None,
-            cover_url: None,
             chapters: vec![Chapter {
                 title: "Chapter 1".to_string(),
                 index: 1,
                 body: "<p>First paragraph.</p>".to_string(),
+                reported_word_count: None,
+                url: None,
             }],
-            source_url: None,
+            ..Default::default()
+        }
+    }
+
+    fn book_with_chapters(indices: &[u32]) -> Book {
+        let mut book = minimal_book();
+        book.chapters = indices
+            .iter()
+            .map(|&index| Chapter {
+                title: format!("Chapter {}", index),
+                index,
+                body: format!("<p>Body {}.</p>", index),
+                reported_word_count: None,
+                url: None,
+            })
+            .collect();
+        book
+    }
+
+    #[test]
+    fn write_epub2_names_cover_image_in_metadata() {
+        let server = crate::scraper::mock_server::MockServer::start();
+        server.route("/cover.jpg", &[(200, "JPEGDATA")]);
+        let mut book = minimal_book();
+        book.cover_url = Some(server.url("/cover.jpg"));
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_epub2_cover.epub");
+        let mut client = PoliteClient::builder()
+            .delay_secs(0)
+            .retry_count(1)
+            .build()
+            .unwrap();
+        let options = EpubOptions {
+            version: EpubVersion::Epub2,
+            ..Default::default()
+        };
+        write_epub(&book, &path, &options, &mut client).unwrap();
+        let mut zip = ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut opf = String::new();
+        zip.by_name("OEBPS/content.opf")
+            .unwrap()
+            .read_to_string(&mut opf)
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        let metadata = &opf[opf.find("<metadata").unwrap()..opf.find("</metadata>").unwrap()];
+        assert!(metadata.contains(r#"<meta name="cover" content="cover-img"/>"#));
+        assert!(opf.contains(r#"<item id="cover-img" href="images/cover."#));
+    }
+
+    #[test]
+    fn write_epub_embeds_chapter_images_and_drops_failed_ones() {
+        let server = crate::scraper::mock_server::MockServer::start();
+        server.route("/map.gif", &[(200, "GIF89a")]);
+        let mut book = minimal_book();
+        book.chapters[0].body = format!(
+            r#"<p>Map: <img src="{0}" alt="Map"/></p><p><img src="{1}" alt=""/></p><p>Again <img src="{0}" alt=""/></p>"#,
+            server.url("/map.gif"),
+            server.url("/missing.png")
+        );
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_images.epub");
+        let mut client = PoliteClient::builder()
+            .delay_secs(0)
+            .retry_count(1)
+            .build()
+            .unwrap();
+        let options = EpubOptions {
+            verify_links: true,
+            ..Default::default()
+        };
+        write_epub(&book, &path, &options, &mut client).unwrap();
+        assert_eq!(server.hits("/map.gif"), 1);
+        let mut zip = ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut read = |name: &str| {
+            let mut content = String::new();
+            zip.by_name(name)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+        assert_eq!(read("OEBPS/images/ch1-1.gif"), "GIF89a");
+        assert!(read("OEBPS/content.opf")
+            .contains(r#"<item id="img-ch1-1" href="images/ch1-1.gif" media-type="image/gif"/>"#));
+        let chapter = read("OEBPS/chapter-1.xhtml");
+        assert!(chapter.contains(
+            r#"<p>Map: <img src="images/ch1-1.gif" alt="Map"/></p><p>Again <img src="images/ch1-1.gif" alt=""/></p>"#
+        ));
+        assert!(chapter.contains(r#"Again <img src="images/ch1-1.gif" alt=""/>"#));
+        assert!(!chapter.contains("127.0.0.1"));
+        std::fs::remove_file(&path).ok();
+
+        let offline = EpubOptions {
+            fetch_images: false,
+            ..Default::default()
+        };
+        write_epub(&book, &path, &offline, &mut client).unwrap();
+        assert_eq!(server.hits("/map.gif"), 1);
+        let mut zip = ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert!(!zip.file_names().any(|n| n.contains("images/")));
+        let mut chapter = String::new();
+        zip.by_name("OEBPS/chapter-1.xhtml")
+            .unwrap()
+            .read_to_string(&mut chapter)
+            .unwrap();
+        assert!(!chapter.contains("<img"));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn write_epub_links_stylesheet_from_every_page() {
+        let book = minimal_book();
+        let mut client = crate::PoliteClient::new().unwrap();
+        for (version, custom) in [
+            (EpubVersion::Epub3, None),
+            (EpubVersion::Epub2, Some("p { margin: 0; }")),
+        ] {
+            let path = std::env::temp_dir().join("rdrscrape_epub_test_css.epub");
+            let options = EpubOptions {
+                version,
+                css: custom.map(String::from),
+                verify_links: true,
+                ..Default::default()
+            };
+            write_epub(&book, &path, &options, &mut client).unwrap();
+            let mut zip = ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+            let mut read = |name: &str| {
+                let mut content = String::new();
+                zip.by_name(name)
+                    .unwrap()
+                    .read_to_string(&mut content)
+                    .unwrap();
+                content
+            };
+            assert_eq!(read("OEBPS/style.css"), custom.unwrap_or(DEFAULT_EPUB_CSS));
+            assert!(read("OEBPS/content.opf")
+                .contains(r#"<item id="css" href="style.css" media-type="text/css"/>"#));
+            let link = r#"<link rel="stylesheet" type="text/css" href="style.css"/>"#;
+            for page in ["chapter-1.xhtml", "toc.xhtml"] {
+                assert!(
+                    read(&format!("OEBPS/{}", page)).contains(link),
+                    "{:?} {}",
+                    version,
+                    page
+                );
+            }
+            if version == EpubVersion::Epub3 {
+                assert!(read("OEBPS/nav.xhtml").contains(link));
+            }
+            std::fs::remove_file(&path).ok();
         }
     }
 
+    #[test]
+    fn image_ext_prefers_content_type_then_url() {
+        assert_eq!(image_ext(Some("image/jpeg"), "https://e.x/a.png"), "jpg");
+        assert_eq!(
+            image_ext(Some("text/html"), "https://e.x/a.webp?w=1"),
+            "webp"
+        );
+        assert_eq!(image_ext(None, "https://e.x/img.d/file"), "png");
+        assert_eq!(image_ext(Some("image/svg+xml"), "https://e.x/x"), "svg");
+    }
+
     #[test]
     fn validate_book_rejects_empty_title() {
         let mut book = minimal_book();
         book.title.clear();
         let path = std::env::temp_dir().join("rdrscrape_epub_void.epub");
         let mut client = crate::PoliteClient::new().unwrap();
-        let result = write_epub(&book, &path, EpubVersion::Epub3, false, true, &mut client);
+        let result = write_epub(&book, &path, &EpubOptions::default(), &mut client);
         assert!(matches!(result, Err(EpubError::EmptyTitle)));
     }
 
@@ -758,7 +1927,7 @@ mod tests {
         book.author.clear();
         let path = std::env::temp_dir().join("rdrscrape_epub_void.epub");
         let mut client = crate::PoliteClient::new().unwrap();
-        let result = write_epub(&book, &path, EpubVersion::Epub3, false, true, &mut client);
+        let result = write_epub(&book, &path, &EpubOptions::default(), &mut client);
         assert!(matches!(result, Err(EpubError::EmptyAuthor)));
     }
 
@@ -768,7 +1937,7 @@ mod tests {
         book.chapters.clear();
         let path = std::env::temp_dir().join("rdrscrape_epub_void.epub");
         let mut client = crate::PoliteClient::new().unwrap();
-        let result = write_epub(&book, &path, EpubVersion::Epub3, false, true, &mut client);
+        let result = write_epub(&book, &path, &EpubOptions::default(), &mut client);
         assert!(matches!(result, Err(EpubError::NoChapters)));
     }
 
@@ -777,7 +1946,7 @@ mod tests {
         let book = minimal_book();
         let path = std::env::temp_dir().join("rdrscrape_epub_test_epub3.epub");
         let mut client = crate::PoliteClient::new().unwrap();
-        write_epub(&book, &path, EpubVersion::Epub3, false, true, &mut client).unwrap();
+        write_epub(&book, &path, &EpubOptions::default(), &mut client).unwrap();
         let file = std::fs::File::open(&path).unwrap();
         let mut zip = ZipArchive::new(file).unwrap();
         let names: Vec<String> = zip.file_names().map(String::from).collect();
@@ -790,12 +1959,133 @@ mod tests {
         std::fs::remove_file(&path).ok();
     }
 
+    #[test]
+    fn write_epub_to_cursor_produces_valid_zip_in_memory() {
+        let book = minimal_book();
+        let mut client = crate::PoliteClient::new().unwrap();
+        let cursor = write_epub_to(
+            &book,
+            std::io::Cursor::new(Vec::new()),
+            &EpubOptions::default(),
+            &mut client,
+        )
+        .unwrap();
+        let bytes = cursor.into_inner();
+        assert_eq!(&bytes[30..38], b"mimetype");
+        let mut zip = ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let mut mimetype = String::new();
+        zip.by_name("mimetype")
+            .unwrap()
+            .read_to_string(&mut mimetype)
+            .unwrap();
+        assert_eq!(mimetype, "application/epub+zip");
+        assert!(zip.by_name("META-INF/container.xml").is_ok());
+        assert!(zip.by_name("OEBPS/content.opf").is_ok());
+        assert!(zip.by_name("OEBPS/chapter-1.xhtml").is_ok());
+    }
+
+    #[test]
+    fn write_epub_to_rejects_invalid_book_without_writing() {
+        let mut book = minimal_book();
+        book.title = String::new();
+        let mut client = crate::PoliteClient::new().unwrap();
+        let mut buf = std::io::Cursor::new(Vec::new());
+        let result = write_epub_to(&book, &mut buf, &EpubOptions::default(), &mut client);
+        assert!(matches!(result, Err(EpubError::EmptyTitle)));
+        assert!(buf.get_ref().is_empty());
+    }
+
+    #[test]
+    fn chapter_display_title_templates() {
+        let ch = &book_with_chapters(&[7]).chapters[0];
+        assert_eq!(chapter_display_title("{num}. {title}", ch), "7. Chapter 7");
+        assert_eq!(chapter_display_title("Part {num}", ch), "Part 7");
+        assert_eq!(chapter_display_title("{volume} {title}", ch), "Chapter 7");
+        assert_eq!(chapter_display_title("{volume}", ch), "Chapter 7");
+    }
+
+    #[test]
+    fn write_epub_applies_chapter_title_template_without_changing_book() {
+        let book = minimal_book();
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_title_template.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        let options = EpubOptions {
+            chapter_title_template: Some("{num}. {title}".to_string()),
+            include_ncx: true,
+            ..EpubOptions::default()
+        };
+        write_epub(&book, &path, &options, &mut client).unwrap();
+        let mut zip = ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        for name in ["OEBPS/nav.xhtml", "OEBPS/toc.ncx", "OEBPS/chapter-1.xhtml"] {
+            let mut content = String::new();
+            zip.by_name(name)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            assert!(content.contains("1. Chapter 1"), "{}: {}", name, content);
+        }
+        std::fs::remove_file(&path).ok();
+        assert_eq!(book.chapters[0].title, "Chapter 1");
+    }
+
+    #[test]
+    fn write_epub_short_title_element_keeps_full_title_in_nav() {
+        let mut book = minimal_book();
+        book.chapters[0].title = "1. A Very Long Chapter Title".to_string();
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_short_title.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        let options = EpubOptions {
+            short_title_element: true,
+            ..EpubOptions::default()
+        };
+        write_epub(&book, &path, &options, &mut client).unwrap();
+        let mut zip = ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut read = |name: &str| {
+            let mut content = String::new();
+            zip.by_name(name)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+        let chapter = read("OEBPS/chapter-1.xhtml");
+        assert!(chapter.contains("<title>Chapter 1</title>"), "{}", chapter);
+        assert!(read("OEBPS/nav.xhtml").contains("1. A Very Long Chapter Title"));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn write_epub_without_cover_skips_cover_page_and_request() {
+        let mut book = minimal_book();
+        book.cover_url = Some("http://10.255.255.1/cover.jpg".to_string());
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_no_cover.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        let options = EpubOptions {
+            include_cover: false,
+            cover_timeout_secs: 1,
+            ..EpubOptions::default()
+        };
+        write_epub(&book, &path, &options, &mut client).unwrap();
+        let zip = ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert!(!zip.file_names().any(|n| n.contains("cover")));
+        std::fs::remove_file(&path).ok();
+    }
+
     #[test]
     fn write_epub_epub3_with_ncx_includes_toc_ncx() {
         let book = minimal_book();
         let path = std::env::temp_dir().join("rdrscrape_epub_test_epub3_ncx.epub");
         let mut client = crate::PoliteClient::new().unwrap();
-        write_epub(&book, &path, EpubVersion::Epub3, true, true, &mut client).unwrap();
+        write_epub(
+            &book,
+            &path,
+            &EpubOptions {
+                include_ncx: true,
+                ..EpubOptions::default()
+            },
+            &mut client,
+        )
+        .unwrap();
         let file = std::fs::File::open(&path).unwrap();
         let zip = ZipArchive::new(file).unwrap();
         let names: Vec<String> = zip.file_names().map(String::from).collect();
@@ -808,7 +2098,16 @@ mod tests {
         let book = minimal_book();
         let path = std::env::temp_dir().join("rdrscrape_epub_test_epub2.epub");
         let mut client = crate::PoliteClient::new().unwrap();
-        write_epub(&book, &path, EpubVersion::Epub2, false, true, &mut client).unwrap();
+        write_epub(
+            &book,
+            &path,
+            &EpubOptions {
+                version: EpubVersion::Epub2,
+                ..EpubOptions::default()
+            },
+            &mut client,
+        )
+        .unwrap();
         let file = std::fs::File::open(&path).unwrap();
         let mut zip = ZipArchive::new(file).unwrap();
         let names: Vec<String> = zip.file_names().map(String::from).collect();
@@ -829,7 +2128,16 @@ mod tests {
         let book = minimal_book();
         let path = std::env::temp_dir().join("rdrscrape_epub_test_no_toc_page.epub");
         let mut client = crate::PoliteClient::new().unwrap();
-        write_epub(&book, &path, EpubVersion::Epub3, false, false, &mut client).unwrap();
+        write_epub(
+            &book,
+            &path,
+            &EpubOptions {
+                include_toc_page: false,
+                ..EpubOptions::default()
+            },
+            &mut client,
+        )
+        .unwrap();
         let file = std::fs::File::open(&path).unwrap();
         let mut zip = ZipArchive::new(file).unwrap();
         let names: Vec<String> = zip.file_names().map(String::from).collect();
@@ -846,7 +2154,7 @@ mod tests {
         let book = minimal_book();
         let path = std::env::temp_dir().join("rdrscrape_epub_test_with_toc_page.epub");
         let mut client = crate::PoliteClient::new().unwrap();
-        write_epub(&book, &path, EpubVersion::Epub3, false, true, &mut client).unwrap();
+        write_epub(&book, &path, &EpubOptions::default(), &mut client).unwrap();
         let file = std::fs::File::open(&path).unwrap();
         let mut zip_archive = ZipArchive::new(file).unwrap();
         let names: Vec<String> = zip_archive.file_names().map(String::from).collect();
@@ -857,4 +2165,564 @@ mod tests {
         assert!(opf_content.contains("toc-page") && opf_content.contains("toc.xhtml"));
         std::fs::remove_file(&path).ok();
     }
+
+    #[test]
+    fn rights_statement_default_override_and_omit() {
+        let mut book = minimal_book();
+        assert_eq!(rights_statement(&book, None), None);
+        assert_eq!(rights_statement(&book, Some("  ")), None);
+        assert_eq!(
+            rights_statement(&book, Some("CC BY 4.0")).as_deref(),
+            Some("CC BY 4.0")
+        );
+        book.source_url = Some("https://www.royalroad.com/fiction/1/x".to_string());
+        assert_eq!(
+            rights_statement(&book, None).as_deref(),
+            Some("© Test Author; see https://www.royalroad.com/fiction/1/x")
+        );
+        assert_eq!(rights_statement(&book, Some("")), None);
+    }
+
+    #[test]
+    fn write_epub2_includes_escaped_rights() {
+        let book = minimal_book();
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_rights.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        let options = EpubOptions {
+            version: EpubVersion::Epub2,
+            rights: Some("All rights reserved <Author & Co>".to_string()),
+            ..EpubOptions::default()
+        };
+        write_epub(&book, &path, &options, &mut client).unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let mut zip = ZipArchive::new(file).unwrap();
+        let mut opf = String::new();
+        zip.by_name("OEBPS/content.opf")
+            .unwrap()
+            .read_to_string(&mut opf)
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(opf.contains("<dc:rights>All rights reserved &lt;Author &amp; Co&gt;</dc:rights>"));
+    }
+
+    #[test]
+    fn write_epub_uses_book_language() {
+        let mut book = minimal_book();
+        book.language = Some("fr".to_string());
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_language.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        write_epub(&book, &path, &EpubOptions::default(), &mut client).unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let mut zip = ZipArchive::new(file).unwrap();
+        let mut opf = String::new();
+        zip.by_name("OEBPS/content.opf")
+            .unwrap()
+            .read_to_string(&mut opf)
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(opf.contains("<dc:language>fr</dc:language>"));
+        assert!(!opf.contains("<dc:language>en</dc:language>"));
+    }
+
+    #[test]
+    fn contributors_follow_epub_version() {
+        let mut book = minimal_book();
+        assert_eq!(contributors(&book, EpubVersion::Epub3), "");
+        book.contributors = vec![
+            Contributor {
+                role: "trl".to_string(),
+                name: "Jane <Doe>".to_string(),
+            },
+            Contributor {
+                role: "aut".to_string(),
+                name: " ".to_string(),
+            },
+        ];
+        assert_eq!(
+            contributors(&book, EpubVersion::Epub3),
+            "\n    <dc:contributor id=\"contributor-1\">Jane &lt;Doe&gt;</dc:contributor>\n    <meta refines=\"#contributor-1\" property=\"role\" scheme=\"marc:relators\">trl</meta>"
+        );
+        assert_eq!(
+            contributors(&book, EpubVersion::Epub2),
+            "\n    <dc:contributor opf:role=\"trl\">Jane &lt;Doe&gt;</dc:contributor>"
+        );
+    }
+
+    #[test]
+    fn subjects_one_per_distinct_tag() {
+        let mut book = minimal_book();
+        assert_eq!(subjects(&book), "");
+        book.tags = vec![
+            "Fantasy".into(),
+            "Sci-fi & Space".into(),
+            "fantasy".into(),
+            "".into(),
+        ];
+        assert_eq!(
+            subjects(&book),
+            "\n    <dc:subject>Fantasy</dc:subject>\n    <dc:subject>Sci-fi &amp; Space</dc:subject>"
+        );
+    }
+
+    #[test]
+    fn series_meta_follows_epub_version() {
+        let mut book = minimal_book();
+        assert_eq!(series_meta(&book, EpubVersion::Epub3), "");
+        assert_eq!(series_meta(&book, EpubVersion::Epub2), "");
+        book.series = Some("Saga & Co".to_string());
+        let opf3 = series_meta(&book, EpubVersion::Epub3);
+        assert!(opf3.contains(
+            r#"<meta property="belongs-to-collection" id="series">Saga &amp; Co</meta>"#
+        ));
+        assert!(!opf3.contains("group-position"));
+        book.series_index = Some(2.5);
+        assert!(series_meta(&book, EpubVersion::Epub3)
+            .contains(r##"<meta refines="#series" property="group-position">2.5</meta>"##));
+        let opf2 = series_meta(&book, EpubVersion::Epub2);
+        assert!(opf2.contains(r#"<meta name="calibre:series" content="Saga &amp; Co"/>"#));
+        assert!(opf2.contains(r#"<meta name="calibre:series_index" content="2.5"/>"#));
+    }
+
+    #[test]
+    fn original_title_meta_only_when_title_was_combined() {
+        let mut book = minimal_book();
+        assert_eq!(original_title_meta(&book), "");
+        book.original_title = Some("Worm & Co".to_string());
+        assert_eq!(
+            original_title_meta(&book),
+            "\n    <meta name=\"rdrscrape:original-title\" content=\"Worm &amp; Co\"/>"
+        );
+    }
+
+    #[test]
+    fn identifier_without_source_url_is_stable_uuid() {
+        let mut book = minimal_book();
+        book.source_url = None;
+        let id = identifier(&book);
+        assert!(id.starts_with("urn:uuid:"), "{}", id);
+        assert_eq!(id.len(), "urn:uuid:".len() + 36);
+        let mut other = book.clone();
+        other.title = "Another Story".to_string();
+        assert_ne!(identifier(&other), id);
+
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_identifier.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        let mut written = Vec::new();
+        for _ in 0..2 {
+            write_epub(&book, &path, &EpubOptions::default(), &mut client).unwrap();
+            let mut zip = ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+            let mut opf = String::new();
+            zip.by_name("OEBPS/content.opf")
+                .unwrap()
+                .read_to_string(&mut opf)
+                .unwrap();
+            written.push(opf);
+        }
+        std::fs::remove_file(&path).ok();
+        let expected = format!(r#"<dc:identifier id="book-id">{}</dc:identifier>"#, id);
+        assert!(written.iter().all(|opf| opf.contains(&expected)));
+    }
+
+    #[test]
+    fn chapter_nav_links_omit_missing_direction() {
+        let files = chapter_file_names(&book_with_chapters(&[1, 2, 3]), ChapterFileNames::Position);
+        assert_eq!(chapter_nav_links(&files[..1], 0), None);
+        let first = chapter_nav_links(&files, 0).unwrap();
+        assert!(first.contains(r#"<a href="chapter-2.xhtml">Next</a>"#));
+        assert!(!first.contains("Previous"));
+        let middle = chapter_nav_links(&files, 1).unwrap();
+        assert!(middle.contains(r#"<a href="chapter-1.xhtml">Previous</a>"#));
+        assert!(middle.contains(r#"<a href="chapter-3.xhtml">Next</a>"#));
+        let last = chapter_nav_links(&files, 2).unwrap();
+        assert!(last.contains("Previous"));
+        assert!(!last.contains("Next"));
+    }
+
+    #[test]
+    fn write_epub_chapter_anchors_follow_index() {
+        let mut book = minimal_book();
+        book.chapters[0].index = 7;
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_anchors.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        write_epub(
+            &book,
+            &path,
+            &EpubOptions {
+                include_ncx: true,
+                ..EpubOptions::default()
+            },
+            &mut client,
+        )
+        .unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let mut zip = ZipArchive::new(file).unwrap();
+        let mut read = |name: &str| {
+            let mut content = String::new();
+            zip.by_name(name)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+        let chapter = read("OEBPS/chapter-1.xhtml");
+        let nav = read("OEBPS/nav.xhtml");
+        let ncx = read("OEBPS/toc.ncx");
+        std::fs::remove_file(&path).ok();
+        assert!(chapter.contains(r#"<a id="ch7"></a>"#));
+        assert!(nav.contains(r#"<li id="nav-ch7"><a href="chapter-1.xhtml#ch7">"#));
+        assert!(ncx.contains(r#"<navPoint id="navpoint-7" playOrder="1">"#));
+        assert!(ncx.contains(r#"<content src="chapter-1.xhtml#ch7"/>"#));
+    }
+
+    #[test]
+    fn write_epub_custom_toc_title_in_nav_and_toc_page() {
+        let book = minimal_book();
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_toc_title.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        write_epub(
+            &book,
+            &path,
+            &EpubOptions {
+                toc_title: Some("Índice & más".to_string()),
+                ..EpubOptions::default()
+            },
+            &mut client,
+        )
+        .unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let mut zip = ZipArchive::new(file).unwrap();
+        for name in ["OEBPS/nav.xhtml", "OEBPS/toc.xhtml"] {
+            let mut content = String::new();
+            zip.by_name(name)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            assert!(content.contains("<h1>Índice &amp; más</h1>"), "{}", name);
+            assert!(!content.contains("Contents"), "{}", name);
+        }
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn write_epub_xhtml_prolog_is_consistent_per_version() {
+        let book = minimal_book();
+        for (version, doctype, charset) in [
+            (
+                EpubVersion::Epub3,
+                "<!DOCTYPE html>\n",
+                r#"<meta charset="UTF-8"/>"#,
+            ),
+            (
+                EpubVersion::Epub2,
+                "<!DOCTYPE html PUBLIC",
+                r#"<meta http-equiv="Content-Type""#,
+            ),
+        ] {
+            let path =
+                std::env::temp_dir().join(format!("rdrscrape_epub_test_prolog_{:?}.epub", version));
+            let mut client = crate::PoliteClient::new().unwrap();
+            let options = EpubOptions {
+                version,
+                ..EpubOptions::default()
+            };
+            write_epub(&book, &path, &options, &mut client).unwrap();
+            let file = std::fs::File::open(&path).unwrap();
+            let mut zip = ZipArchive::new(file).unwrap();
+            let names: Vec<String> = zip
+                .file_names()
+                .filter(|n| n.ends_with(".xhtml"))
+                .map(String::from)
+                .collect();
+            assert!(names.iter().any(|n| n.contains("chapter-")));
+            for name in &names {
+                let mut content = String::new();
+                zip.by_name(name)
+                    .unwrap()
+                    .read_to_string(&mut content)
+                    .unwrap();
+                assert!(
+                    content.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"),
+                    "{}",
+                    name
+                );
+                assert!(content.contains(doctype), "{}", name);
+                assert!(content.contains(charset), "{}", name);
+                assert!(content.contains(r#"href="style.css""#), "{}", name);
+            }
+            std::fs::remove_file(&path).ok();
+        }
+    }
+
+    #[test]
+    fn write_epub_verify_links_passes_for_both_versions() {
+        let book = minimal_book();
+        for version in [EpubVersion::Epub3, EpubVersion::Epub2] {
+            let path = std::env::temp_dir().join(format!(
+                "rdrscrape_epub_test_verify_links_{:?}.epub",
+                version
+            ));
+            let mut client = crate::PoliteClient::new().unwrap();
+            let options = EpubOptions {
+                version,
+                include_ncx: true,
+                verify_links: true,
+                ..EpubOptions::default()
+            };
+            write_epub(&book, &path, &options, &mut client).unwrap();
+            std::fs::remove_file(&path).ok();
+        }
+    }
+
+    #[test]
+    fn verify_links_reports_spine_item_not_written() {
+        let opf = r#"<package><manifest>
+  <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+  <item id="chapter-1" href="chapter-1.xhtml" media-type="application/xhtml+xml"/>
+</manifest><spine><itemref idref="chapter-1"/></spine></package>"#;
+        let nav = r#"<nav><ol><li><a href="chapter-1.xhtml#ch1">One</a></li></ol></nav>"#;
+        let documents: BTreeMap<String, String> = [
+            ("OEBPS/content.opf".to_string(), opf.to_string()),
+            ("OEBPS/nav.xhtml".to_string(), nav.to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let mut written = vec![
+            "OEBPS/content.opf".to_string(),
+            "OEBPS/nav.xhtml".to_string(),
+        ];
+        let err = verify_links(&written, &documents).unwrap_err();
+        assert_eq!(err.kind_name(), "epub_broken_link");
+        assert!(err.to_string().contains("chapter-1.xhtml"));
+        written.push("OEBPS/chapter-1.xhtml".to_string());
+        assert!(verify_links(&written, &documents).is_ok());
+    }
+
+    #[test]
+    fn verify_links_reports_spine_idref_missing_from_manifest() {
+        let opf = r#"<package><manifest>
+  <item id="chapter-1" href="chapter-1.xhtml" media-type="application/xhtml+xml"/>
+</manifest><spine><itemref idref="chapter-2"/></spine></package>"#;
+        let documents: BTreeMap<String, String> =
+            [("OEBPS/content.opf".to_string(), opf.to_string())]
+                .into_iter()
+                .collect();
+        let written = vec![
+            "OEBPS/content.opf".to_string(),
+            "OEBPS/chapter-1.xhtml".to_string(),
+        ];
+        match verify_links(&written, &documents).unwrap_err() {
+            EpubError::BrokenLink { document, target } => {
+                assert_eq!(document, "OEBPS/content.opf");
+                assert_eq!(target, "chapter-2");
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn verify_links_reports_nav_target_missing_from_manifest() {
+        let opf = r#"<?xml version="1.0"?><package><manifest>
+  <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+  <!-- <item id="chapter-2" href="chapter-2.xhtml"/> -->
+  <opf:item id="chapter-1" href='chapter-1.xhtml' media-type="application/xhtml+xml"/>
+</manifest><spine><itemref idref="chapter-1"/></spine></package>"#;
+        let nav = r#"<nav><ol>
+<li><a href="chapter-1.xhtml#ch1">One</a></li>
+<li><a href="chapter-2.xhtml#ch2">Two &amp; more</a></li>
+</ol></nav>"#;
+        let documents: BTreeMap<String, String> = [
+            ("OEBPS/content.opf".to_string(), opf.to_string()),
+            ("OEBPS/nav.xhtml".to_string(), nav.to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let written = vec![
+            "OEBPS/content.opf".to_string(),
+            "OEBPS/nav.xhtml".to_string(),
+            "OEBPS/chapter-1.xhtml".to_string(),
+        ];
+        match verify_links(&written, &documents).unwrap_err() {
+            EpubError::BrokenLink { document, target } => {
+                assert_eq!(document, "OEBPS/nav.xhtml");
+                assert_eq!(target, "chapter-2.xhtml#ch2");
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn write_epub_index_file_names_follow_chapter_index() {
+        let book = book_with_chapters(&[50, 51, 52]);
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_index_file_names.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        let options = EpubOptions {
+            include_ncx: true,
+            nav_links: true,
+            verify_links: true,
+            chapter_file_names: ChapterFileNames::Index,
+            ..EpubOptions::default()
+        };
+        write_epub(&book, &path, &options, &mut client).unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let mut zip = ZipArchive::new(file).unwrap();
+        let names: Vec<String> = zip.file_names().map(String::from).collect();
+        for n in ["0050", "0051", "0052"] {
+            assert!(
+                names.contains(&format!("OEBPS/chapter-{}.xhtml", n)),
+                "{:?}",
+                names
+            );
+        }
+        assert!(!names.contains(&"OEBPS/chapter-1.xhtml".to_string()));
+        let mut read = |name: &str| {
+            let mut content = String::new();
+            zip.by_name(name)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+        let opf = read("OEBPS/content.opf");
+        let nav = read("OEBPS/nav.xhtml");
+        let ncx = read("OEBPS/toc.ncx");
+        let middle = read("OEBPS/chapter-0051.xhtml");
+        std::fs::remove_file(&path).ok();
+        assert!(opf.contains(r#"href="chapter-0050.xhtml""#));
+        assert!(nav.contains(r#"<a href="chapter-0052.xhtml#ch52">"#));
+        assert!(ncx.contains(r#"<content src="chapter-0051.xhtml#ch51"/>"#));
+        assert!(middle.contains(r#"<a href="chapter-0050.xhtml">Previous</a>"#));
+        assert!(middle.contains(r#"<a href="chapter-0052.xhtml">Next</a>"#));
+    }
+
+    #[test]
+    fn chapter_file_names_pad_to_widest_index() {
+        let book = book_with_chapters(&[7, 12345]);
+        assert_eq!(
+            chapter_file_names(&book, ChapterFileNames::Index),
+            vec!["chapter-00007.xhtml", "chapter-12345.xhtml"]
+        );
+        assert_eq!(
+            chapter_file_names(&book, ChapterFileNames::Position),
+            vec!["chapter-1.xhtml", "chapter-2.xhtml"]
+        );
+    }
+
+    #[test]
+    fn opds_entry_escapes_fields_and_links_epub() {
+        let mut book = minimal_book();
+        book.title = "Tom & Jerry <3".to_string();
+        book.description = Some("A \"cat\" story".to_string());
+        book.source_url = Some("https://www.royalroad.com/fiction/1/x".to_string());
+        let xml = opds_entry(&book, "books/Tom & Jerry.epub", "2024-01-01T00:00:00Z");
+        assert!(xml.contains("<title>Tom &amp; Jerry &lt;3</title>"));
+        assert!(xml.contains("<summary type=\"text\">A &quot;cat&quot; story</summary>"));
+        assert!(xml.contains(
+            r#"<link rel="http://opds-spec.org/acquisition" type="application/epub+zip" href="books/Tom &amp; Jerry.epub"/>"#
+        ));
+        assert!(xml.contains("<id>https://www.royalroad.com/fiction/1/x</id>"));
+        assert!(xml.contains("<updated>2024-01-01T00:00:00Z</updated>"));
+        book.description = None;
+        assert!(!opds_entry(&book, "b.epub", "x").contains("<summary"));
+    }
+
+    #[test]
+    fn write_epub3_includes_dcterms_modified() {
+        let book = minimal_book();
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_modified.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        write_epub(&book, &path, &EpubOptions::default(), &mut client).unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let mut zip = ZipArchive::new(file).unwrap();
+        let mut opf = String::new();
+        zip.by_name("OEBPS/content.opf")
+            .unwrap()
+            .read_to_string(&mut opf)
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        let re = Regex::new(
+            r#"<meta property="dcterms:modified">\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z</meta>"#,
+        )
+        .unwrap();
+        let metadata = &opf[opf.find("<metadata").unwrap()..opf.find("</metadata>").unwrap()];
+        assert!(re.is_match(metadata), "{}", opf);
+    }
+
+    #[test]
+    fn write_epub_about_page_is_last_in_spine() {
+        let mut book = minimal_book();
+        book.source_url = Some("https://www.royalroad.com/fiction/1/a&b".to_string());
+        for version in [EpubVersion::Epub3, EpubVersion::Epub2] {
+            let path =
+                std::env::temp_dir().join(format!("rdrscrape_epub_test_about_{:?}.epub", version));
+            let mut client = crate::PoliteClient::new().unwrap();
+            let options = EpubOptions {
+                version,
+                about_page: true,
+                verify_links: true,
+                ..EpubOptions::default()
+            };
+            write_epub(&book, &path, &options, &mut client).unwrap();
+            let file = std::fs::File::open(&path).unwrap();
+            let mut zip = ZipArchive::new(file).unwrap();
+            let mut read = |name: &str| {
+                let mut content = String::new();
+                zip.by_name(name)
+                    .unwrap()
+                    .read_to_string(&mut content)
+                    .unwrap();
+                content
+            };
+            let opf = read("OEBPS/content.opf");
+            let about = read("OEBPS/about.xhtml");
+            std::fs::remove_file(&path).ok();
+            assert!(opf.contains(r#"<item id="about" href="about.xhtml""#));
+            assert!(opf.contains("<itemref idref=\"chapter-1\"/>\n  <itemref idref=\"about\"/>\n"));
+            assert!(about.contains("fiction/1/a&amp;b</a>"));
+            assert!(about.contains(&format!("rdrscrape {}", env!("CARGO_PKG_VERSION"))));
+            assert!(about.contains("<p>Chapters: 1</p>"));
+        }
+    }
+
+    #[test]
+    fn description_paragraphs_split_on_blank_lines() {
+        let text = "First line\nstill first.\n\n  \nSecond & last.\n";
+        assert_eq!(
+            description_paragraphs(text),
+            "  <p>First line still first.</p>\n  <p>Second &amp; last.</p>"
+        );
+        assert_eq!(description_paragraphs("One"), "  <p>One</p>");
+    }
+
+    #[test]
+    fn write_epub_description_page_follows_cover() {
+        let mut book = minimal_book();
+        book.description = Some("Para one.\n\nPara two.".to_string());
+        let path = std::env::temp_dir().join("rdrscrape_epub_test_description_page.epub");
+        let mut client = crate::PoliteClient::new().unwrap();
+        let options = EpubOptions {
+            description_page: true,
+            verify_links: true,
+            ..EpubOptions::default()
+        };
+        write_epub(&book, &path, &options, &mut client).unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let mut zip = ZipArchive::new(file).unwrap();
+        let mut read = |name: &str| {
+            let mut content = String::new();
+            zip.by_name(name)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+        let opf = read("OEBPS/content.opf");
+        let page = read("OEBPS/description.xhtml");
+        std::fs::remove_file(&path).ok();
+        assert!(opf.contains("<dc:description>Para one.\n\nPara two.</dc:description>"));
+        assert!(opf.contains("<itemref idref=\"description\"/>\n  <itemref idref=\"toc-page\"/>"));
+        assert!(page.contains("<p>Para one.</p>\n  <p>Para two.</p>"));
+
+        book.description = None;
+        assert!(!has_description_page(&book, &options));
+    }
 }
@@ -1,10 +1,16 @@
-//! Single-file output formats: HTML, Markdown, and plain text.
+//! Single-file output formats: HTML, Markdown, plain text, PDF, and FB2.
 //! Consumes the canonical Book and writes one file per format.
 
-use crate::model::Book;
-use scraper::Html;
+use crate::model::{identifier, Book};
+use base64::Engine;
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerIndex,
+    PdfPageIndex,
+};
+use scraper::{ElementRef, Html, Node};
+use std::collections::BTreeSet;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 use thiserror::Error;
 
@@ -16,9 +22,11 @@ pub enum OutputFormat {
     Html,
     Markdown,
     Text,
+    Pdf,
+    Fb2,
 }
 
-/// Errors from the format writers (HTML, Markdown, text).
+/// Errors from the format writers (HTML, Markdown, text, PDF, FB2).
 #[derive(Debug, Error)]
 pub enum FormatError {
     #[error("Cannot write: book title is empty.")]
@@ -36,6 +44,26 @@ pub enum FormatError {
 
     #[error("Failed to write output: {0}")]
     Write(#[from] std::io::Error),
+
+    #[error("Failed to write bundle: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error("Failed to write PDF: {0}")]
+    Pdf(#[from] printpdf::Error),
+}
+
+impl FormatError {
+    /// Stable snake_case name of the variant, for machine-readable output.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            FormatError::EmptyTitle => "format_empty_title",
+            FormatError::EmptyAuthor => "format_empty_author",
+            FormatError::Io { .. } => "format_io",
+            FormatError::Write(_) => "format_write",
+            FormatError::Zip(_) => "format_zip",
+            FormatError::Pdf(_) => "format_pdf",
+        }
+    }
 }
 
 fn validate_book(book: &Book) -> Result<(), FormatError> {
@@ -55,6 +83,442 @@ pub(crate) fn html_escape_attr(s: &str) -> String {
         .replace('"', "&quot;")
 }
 
+/// Tag allowlists for chapter bodies. Block tags are the top-level elements that become
+/// paragraphs of the body; inline tags survive inside them. Other elements are unwrapped to
+/// their text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BodyOptions {
+    pub block_tags: Vec<String>,
+    pub inline_tags: Vec<String>,
+}
+
+impl Default for BodyOptions {
+    /// Built-in set: `<p>` blocks, `<ul>`/`<ol>` lists, `<h1>`–`<h6>` subheadings and `<hr>` scene
+    /// breaks, keeping emphasis (`em`, `strong`, `i`, `b`), line breaks (`br`) and images (`img`)
+    /// inside them.
+    fn default() -> Self {
+        Self {
+            block_tags: ["p", "ul", "ol", "h1", "h2", "h3", "h4", "h5", "h6", "hr"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            inline_tags: ["em", "strong", "i", "b", "br", "img"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Plain-text word count of a chapter body (HTML tags stripped).
+pub(crate) fn body_word_count(body: &str) -> usize {
+    Html::parse_fragment(body)
+        .root_element()
+        .text()
+        .flat_map(str::split_whitespace)
+        .count()
+}
+
+/// Reduce raw chapter HTML (the content container's inner HTML) to the minimal-HTML body used in
+/// [Chapter::body](crate::model::Chapter::body): top-level elements in `opts.block_tags` are kept,
+/// with text escaped and only `opts.inline_tags` inside them; everything else is dropped.
+/// A top-level `<img>` is kept as its own paragraph when `img` is an inline tag.
+/// When no top-level element other than a heading, image or rule is a block tag, top-level
+/// `<div>`s holding only text and inline markup become `<p>` paragraphs instead (chapters that
+/// use one `<div>` per paragraph), kept in order with those headings and images.
+/// Top-level `<hr>` scene breaks (when `hr` is a block tag) stay between either kind of
+/// paragraph as `<hr/>`; a body of nothing but rules is empty.
+pub fn normalize_body_html(raw: &str, opts: &BodyOptions) -> String {
+    let fragment = Html::parse_fragment(raw);
+    let top_level = || {
+        fragment
+            .root_element()
+            .children()
+            .filter_map(ElementRef::wrap)
+    };
+    let is_rule = |el: &ElementRef<'_>| {
+        el.value().name() == "hr" && opts.block_tags.iter().any(|t| t == "hr")
+    };
+    let is_block = |el: &ElementRef<'_>| {
+        let name = el.value().name();
+        name != "hr"
+            && (opts.block_tags.iter().any(|t| t == name)
+                || (name == "img" && opts.inline_tags.iter().any(|t| t == "img")))
+    };
+    // Headings and images alone do not mean the chapter uses block tags for its paragraphs.
+    let is_paragraph_block = |el: &ElementRef<'_>| {
+        is_block(el) && el.value().name() != "img" && demoted_heading(el.value().name()).is_none()
+    };
+    if top_level().any(|el| is_paragraph_block(&el)) {
+        return top_level()
+            .filter(|el| is_block(el) || is_rule(el))
+            .map(|el| serialize_block(el, opts))
+            .collect();
+    }
+    if !top_level().any(|el| is_block(&el) || is_div_paragraph(el, opts)) {
+        return String::new();
+    }
+    top_level()
+        .filter(|el| is_block(el) || is_rule(el) || is_div_paragraph(*el, opts))
+        .map(|el| {
+            if !is_div_paragraph(el, opts) {
+                return serialize_block(el, opts);
+            }
+            let mut inner = String::new();
+            serialize_inline(el, opts, &mut inner, false);
+            format!("<p>{}</p>", inner.trim())
+        })
+        .collect()
+}
+
+/// Longest text (in chars) that can still be a scene-break divider such as `* * *` or `~ ~ ~`.
+const DIVIDER_MAX_CHARS: usize = 40;
+
+/// A centred paragraph whose text is only punctuation and symbols (`* * *`, `~~~`, `§`): the
+/// author's scene break. Centred means `align="center"`,
+/// a `text-align: center` style, or a class containing "center"/"centre".
+fn is_scene_divider(el: ElementRef<'_>) -> bool {
+    let text: String = el.text().collect();
+    let text = text.trim();
+    if text.is_empty()
+        || text.chars().count() > DIVIDER_MAX_CHARS
+        || text.chars().any(char::is_alphanumeric)
+    {
+        return false;
+    }
+    let attr = |name: &str| el.value().attr(name).unwrap_or("").to_ascii_lowercase();
+    let style: String = attr("style").split_whitespace().collect();
+    let class = attr("class");
+    attr("align") == "center"
+        || style.contains("text-align:center")
+        || class.contains("center")
+        || class.contains("centre")
+}
+
+/// A `<div>` used as a paragraph: it has text and no nested `<div>` or block-tag elements, so
+/// wrappers around real paragraphs are left to the caller's own fallback.
+fn is_div_paragraph(el: ElementRef<'_>, opts: &BodyOptions) -> bool {
+    el.value().name() == "div"
+        && el.text().any(|t| !t.trim().is_empty())
+        && !el
+            .descendants()
+            .skip(1)
+            .filter_map(ElementRef::wrap)
+            .any(|d| {
+                let name = d.value().name();
+                name == "div" || opts.block_tags.iter().any(|t| t == name)
+            })
+}
+
+/// One block element with its inline content filtered by `tags`, e.g. `<p>text <em>x</em></p>`.
+/// Lists keep their `<li>` items (see [serialize_list]); headings are demoted (see
+/// [demoted_heading]). `<hr>` becomes `<hr/>`, and a divider paragraph (see [is_scene_divider])
+/// keeps its text verbatim as `<p class="scene-break">`.
+pub(crate) fn serialize_block(el: ElementRef<'_>, tags: &BodyOptions) -> String {
+    if is_list(el) {
+        return serialize_list(el, tags);
+    }
+    if el.value().name() == "hr" {
+        return "<hr/>".to_string();
+    }
+    if el.value().name() == "p" && is_scene_divider(el) {
+        let text: String = el.text().collect();
+        return format!(
+            r#"<p class="scene-break">{}</p>"#,
+            html_escape_attr(text.trim())
+        );
+    }
+    if el.value().name() == "img" {
+        return Some(img_tag(el))
+            .filter(|img| !img.is_empty())
+            .map(|img| format!("<p>{}</p>", img))
+            .unwrap_or_default();
+    }
+    let name = demoted_heading(el.value().name()).unwrap_or(el.value().name());
+    let mut inner = String::new();
+    serialize_inline(el, tags, &mut inner, false);
+    format!("<{0}>{1}</{0}>", name, inner.trim())
+}
+
+/// Body tag for a subheading inside chapter content: one level below the source so the chapter
+/// title stays the top heading (`<h1>` -> `<h2>`), capped at `<h6>`. None for other tags.
+fn demoted_heading(name: &str) -> Option<&'static str> {
+    match name {
+        "h1" => Some("h2"),
+        "h2" => Some("h3"),
+        "h3" => Some("h4"),
+        "h4" => Some("h5"),
+        "h5" | "h6" => Some("h6"),
+        _ => None,
+    }
+}
+
+/// `<img>` with only `src` and `alt`, e.g. `<img src="/map.png" alt="Map"/>`. Empty when the
+/// element has no `src`.
+fn img_tag(el: ElementRef<'_>) -> String {
+    match el
+        .value()
+        .attr("src")
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        Some(src) => format!(
+            r#"<img src="{}" alt="{}"/>"#,
+            html_escape_attr(src),
+            html_escape_attr(el.value().attr("alt").unwrap_or(""))
+        ),
+        None => String::new(),
+    }
+}
+
+fn is_list(el: ElementRef<'_>) -> bool {
+    matches!(el.value().name(), "ul" | "ol")
+}
+
+/// `<ul>`/`<ol>` with only its `<li>` children, each holding inline content and nested lists.
+fn serialize_list(el: ElementRef<'_>, tags: &BodyOptions) -> String {
+    let name = el.value().name();
+    let mut out = format!("<{}>", name);
+    for item in el
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|c| c.value().name() == "li")
+    {
+        let mut inner = String::new();
+        serialize_inline(item, tags, &mut inner, true);
+        out.push_str(&format!("<li>{}</li>", inner.trim()));
+    }
+    out.push_str(&format!("</{}>", name));
+    out
+}
+
+/// Inline content of `el`, unwrapping elements not in `tags.inline_tags`. With `keep_lists` (inside
+/// `<li>`), nested `<ul>`/`<ol>` stay lists.
+fn serialize_inline(el: ElementRef<'_>, tags: &BodyOptions, out: &mut String, keep_lists: bool) {
+    for child in el.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(&html_escape_attr(text)),
+            Node::Element(e) => {
+                let Some(child_el) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                let name = e.name();
+                if keep_lists && is_list(child_el) {
+                    out.push_str(&serialize_list(child_el, tags));
+                } else if !tags.inline_tags.iter().any(|t| t == name) {
+                    serialize_inline(child_el, tags, out, keep_lists);
+                } else if matches!(name, "br" | "hr" | "wbr") {
+                    out.push_str(&format!("<{}/>", name));
+                } else if name == "img" {
+                    out.push_str(&img_tag(child_el));
+                } else {
+                    match e.attr("href").filter(|_| name == "a") {
+                        Some(href) => {
+                            out.push_str(&format!("<a href=\"{}\">", html_escape_attr(href)))
+                        }
+                        None => out.push_str(&format!("<{}>", name)),
+                    }
+                    serialize_inline(child_el, tags, out, keep_lists);
+                    out.push_str(&format!("</{}>", name));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Markdown for a chapter body in the shape [normalize_body_html] produces: `<p>` paragraphs,
+/// `<ul>`/`<ol>` lists, `<h2>`–`<h6>` subheadings (one `#` deeper than the HTML level, below the
+/// `##` chapter title), `<hr>`, and the inline tags `em`/`i`, `strong`/`b`, `code`, `a`, `br`, `img`.
+/// None when the body contains anything else; [write_markdown] then falls back to html2md.
+pub(crate) fn body_to_markdown(body: &str) -> Option<String> {
+    let fragment = Html::parse_fragment(body);
+    let mut blocks = Vec::new();
+    for child in fragment.root_element().children() {
+        match child.value() {
+            Node::Text(text) if text.trim().is_empty() => {}
+            Node::Element(e) => {
+                let el = ElementRef::wrap(child)?;
+                match e.name() {
+                    "p" => {
+                        let text = markdown_paragraph(el)?;
+                        if !text.is_empty() {
+                            blocks.push(text);
+                        }
+                    }
+                    "ul" | "ol" => blocks.push(markdown_list(el, "")?),
+                    "h2" | "h3" | "h4" | "h5" | "h6" => {
+                        let level: usize = e.name()[1..].parse().ok()?;
+                        let mut text = String::new();
+                        markdown_inline(el, &mut text, None)?;
+                        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                        if !text.is_empty() {
+                            blocks.push(format!("{} {}", "#".repeat((level + 1).min(6)), text));
+                        }
+                    }
+                    "hr" => blocks.push("---".to_string()),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(blocks.join("\n\n"))
+}
+
+/// Inline Markdown of a paragraph, one output line per `<br>`.
+fn markdown_paragraph(el: ElementRef<'_>) -> Option<String> {
+    let mut out = String::new();
+    markdown_inline(el, &mut out, None)?;
+    Some(markdown_lines(&out, ""))
+}
+
+/// Trim each line of `inline`, escape text that would start a Markdown block, and indent
+/// continuation lines by `indent`.
+fn markdown_lines(inline: &str, indent: &str) -> String {
+    inline
+        .trim()
+        .split('\n')
+        .map(|line| escape_markdown_line_start(line.trim_start()))
+        .collect::<Vec<_>>()
+        .join(&format!("\n{}", indent))
+}
+
+/// Markdown list with `indent` before each marker; nested lists are indented under their item.
+fn markdown_list(el: ElementRef<'_>, indent: &str) -> Option<String> {
+    let ordered = el.value().name() == "ol";
+    let mut lines = Vec::new();
+    for (n, item) in el
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|c| c.value().name() == "li")
+        .enumerate()
+    {
+        let marker = if ordered {
+            format!("{}. ", n + 1)
+        } else {
+            "- ".to_string()
+        };
+        let child_indent = format!("{}{}", indent, " ".repeat(marker.len()));
+        let mut text = String::new();
+        let mut nested = Vec::new();
+        markdown_inline(item, &mut text, Some(&mut nested))?;
+        let text = markdown_lines(&text, &child_indent);
+        lines.push(
+            format!("{}{}{}", indent, marker, text)
+                .trim_end()
+                .to_string(),
+        );
+        for list in nested {
+            lines.push(markdown_list(list, &child_indent)?);
+        }
+    }
+    Some(lines.join("\n"))
+}
+
+/// Append the inline Markdown of `el`'s children to `out`. Nested lists are collected into
+/// `lists` when given (inside `<li>`); any other unexpected element returns None.
+fn markdown_inline<'a>(
+    el: ElementRef<'a>,
+    out: &mut String,
+    mut lists: Option<&mut Vec<ElementRef<'a>>>,
+) -> Option<()> {
+    for child in el.children() {
+        match child.value() {
+            Node::Text(text) => push_markdown_text(text, out),
+            Node::Element(e) => {
+                let child_el = ElementRef::wrap(child)?;
+                match e.name() {
+                    "br" => out.push_str("  \n"),
+                    "em" | "i" => push_markdown_emphasis(child_el, "*", out)?,
+                    "strong" | "b" => push_markdown_emphasis(child_el, "**", out)?,
+                    "code" => {
+                        let code: String = child_el.text().collect();
+                        if code.contains('`') {
+                            return None;
+                        }
+                        if !code.is_empty() {
+                            out.push_str(&format!("`{}`", code));
+                        }
+                    }
+                    "a" => {
+                        let mut label = String::new();
+                        markdown_inline(child_el, &mut label, None)?;
+                        match e.attr("href") {
+                            Some(href) if href.contains([' ', '(', ')']) => {
+                                out.push_str(&format!("[{}](<{}>)", label.trim(), href))
+                            }
+                            Some(href) => out.push_str(&format!("[{}]({})", label.trim(), href)),
+                            None => out.push_str(&label),
+                        }
+                    }
+                    "img" => {
+                        let src = e.attr("src")?;
+                        let alt = e.attr("alt").unwrap_or("").replace(['[', ']'], "");
+                        if src.contains([' ', '(', ')']) {
+                            out.push_str(&format!("![{}](<{}>)", alt, src));
+                        } else {
+                            out.push_str(&format!("![{}]({})", alt, src));
+                        }
+                    }
+                    "ul" | "ol" => lists.as_deref_mut()?.push(child_el),
+                    _ => return None,
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(())
+}
+
+/// `*text*` / `**text**`, with surrounding whitespace moved outside the markers so they still
+/// parse as emphasis.
+fn push_markdown_emphasis(el: ElementRef<'_>, marker: &str, out: &mut String) -> Option<()> {
+    let mut inner = String::new();
+    markdown_inline(el, &mut inner, None)?;
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        out.push_str(&inner);
+        return Some(());
+    }
+    if inner.starts_with(char::is_whitespace) && !out.ends_with([' ', '\n']) {
+        out.push(' ');
+    }
+    out.push_str(&format!("{0}{1}{0}", marker, trimmed));
+    if inner.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+    Some(())
+}
+
+/// Text with whitespace runs collapsed (as HTML renders them) and Markdown punctuation escaped.
+fn push_markdown_text(text: &str, out: &mut String) {
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !out.ends_with([' ', '\n']) {
+                out.push(' ');
+            }
+            continue;
+        }
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// Escape a line start that Markdown would read as a heading, quote, list item, or rule.
+fn escape_markdown_line_start(line: &str) -> String {
+    if line.starts_with(['#', '>', '-', '+']) {
+        return format!("\\{}", line);
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 && line[digits..].starts_with(['.', ')']) {
+        return format!("{}\\{}", &line[..digits], &line[digits..]);
+    }
+    line.to_string()
+}
+
 /// Strip HTML from chapter body to plain text using scraper.
 pub(crate) fn body_to_plain_text(body: &str) -> String {
     let fragment = Html::parse_fragment(body);
@@ -67,8 +531,29 @@ pub(crate) fn body_to_plain_text(body: &str) -> String {
     }
 }
 
+/// Previous/Next links for the chapter at position `i` (0-based) in `hrefs`, one link target per
+/// chapter (EPUB chapter files, or `#chapter-N` anchors in the HTML output). None when there is
+/// nowhere to go.
+pub(crate) fn chapter_nav_links(hrefs: &[String], i: usize) -> Option<String> {
+    let prev = i
+        .checked_sub(1)
+        .map(|p| format!(r#"<a href="{}">Previous</a>"#, hrefs[p]));
+    let next = hrefs
+        .get(i + 1)
+        .map(|n| format!(r#"<a href="{}">Next</a>"#, n));
+    let links: Vec<String> = prev.into_iter().chain(next).collect();
+    if links.is_empty() {
+        return None;
+    }
+    Some(format!(
+        r#"<p class="chapter-nav">{}</p>"#,
+        links.join(" | ")
+    ))
+}
+
 /// Write a single HTML file with full book: title, author, description, and all chapters.
-pub fn write_html(book: &Book, path: &Path) -> Result<(), FormatError> {
+/// Each chapter section gets an `id="chapter-N"` anchor; with `nav_links`, Previous/Next links are added around each chapter.
+pub fn write_html(book: &Book, path: &Path, nav_links: bool) -> Result<(), FormatError> {
     validate_book(book)?;
 
     let path = path.to_path_buf();
@@ -86,7 +571,11 @@ pub fn write_html(book: &Book, path: &Path) -> Result<(), FormatError> {
         .unwrap_or_default();
 
     writeln!(f, r#"<!DOCTYPE html>"#)?;
-    writeln!(f, r#"<html lang="en">"#)?;
+    writeln!(
+        f,
+        r#"<html lang="{}">"#,
+        html_escape_attr(book.language.as_deref().unwrap_or("en"))
+    )?;
     writeln!(f, r#"<head>"#)?;
     writeln!(f, r#"  <meta charset="UTF-8"/>"#)?;
     writeln!(f, r#"  <title>{}</title>"#, title_esc)?;
@@ -100,14 +589,24 @@ pub fn write_html(book: &Book, path: &Path) -> Result<(), FormatError> {
     }
     writeln!(f, r#"  </header>"#)?;
 
-    for ch in &book.chapters {
+    let anchors: Vec<String> = (1..=book.chapters.len())
+        .map(|n| format!("#chapter-{}", n))
+        .collect();
+    for (i, ch) in book.chapters.iter().enumerate() {
         let ch_title_esc = html_escape_attr(&ch.title);
-        writeln!(f, r#"  <section class="chapter">"#)?;
+        let nav = chapter_nav_links(&anchors, i).filter(|_| nav_links);
+        writeln!(f, r#"  <section class="chapter" id="chapter-{}">"#, i + 1)?;
+        if let Some(ref nav) = nav {
+            writeln!(f, "    {}", nav)?;
+        }
         writeln!(f, r#"    <h2>{}</h2>"#, ch_title_esc)?;
         writeln!(f, r#"    <div class="chapter-body">"#)?;
         f.write_all(ch.body.as_bytes())?;
         writeln!(f)?;
         writeln!(f, r#"    </div>"#)?;
+        if let Some(ref nav) = nav {
+            writeln!(f, "    {}", nav)?;
+        }
         writeln!(f, r#"  </section>"#)?;
     }
 
@@ -141,7 +640,7 @@ pub fn write_markdown(book: &Book, path: &Path) -> Result<(), FormatError> {
     for ch in &book.chapters {
         writeln!(f, "## {}", ch.title)?;
         writeln!(f)?;
-        let md = html2md::parse_html(&ch.body);
+        let md = body_to_markdown(&ch.body).unwrap_or_else(|| html2md::parse_html(&ch.body));
         writeln!(f, "{}", md)?;
         writeln!(f)?;
     }
@@ -149,6 +648,60 @@ pub fn write_markdown(book: &Book, path: &Path) -> Result<(), FormatError> {
     Ok(())
 }
 
+/// Raw HTML tags left in Markdown from [write_markdown] where a chapter body could not be fully
+/// converted: one `(section, tag)` per distinct tag name per section, in file order. The section is
+/// the enclosing `## ` chapter heading, or "front matter" before the first. Heading lines, fenced
+/// code blocks, and inline code spans are not scanned; autolinks such as `<https://...>` are not tags.
+pub fn markdown_residual_html(markdown: &str) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = Vec::new();
+    let mut section = "front matter".to_string();
+    let mut in_fence = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some(title) = line.strip_prefix("## ") {
+            section = title.trim().to_string();
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let outside_code = line.split('`').step_by(2).collect::<Vec<_>>().join(" ");
+        for tag in html_tag_names(&outside_code) {
+            if !found.iter().any(|(s, t)| *s == section && *t == tag) {
+                found.push((section.clone(), tag));
+            }
+        }
+    }
+    found
+}
+
+/// Lowercase names of tags like `<sup>`, `</sup>`, `<br/>`, or `<span class="x">` in `line`.
+fn html_tag_names(line: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = line;
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open + 1..];
+        let after_slash = rest.strip_prefix('/').unwrap_or(rest);
+        let name_len = after_slash
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(after_slash.len());
+        let name = &after_slash[..name_len];
+        let starts_with_letter = name.starts_with(|c: char| c.is_ascii_alphabetic());
+        let tail = &after_slash[name_len..];
+        let ends_name = tail.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace());
+        if starts_with_letter && ends_name && tail.contains('>') {
+            names.push(name.to_ascii_lowercase());
+        }
+    }
+    names
+}
+
 /// Write a single plain-text file: title, author, description, then each chapter with a heading and stripped body.
 pub fn write_text(book: &Book, path: &Path) -> Result<(), FormatError> {
     validate_book(book)?;
@@ -178,6 +731,631 @@ pub fn write_text(book: &Book, path: &Path) -> Result<(), FormatError> {
     Ok(())
 }
 
+const PDF_PAGE_WIDTH_MM: f32 = 210.0;
+const PDF_PAGE_HEIGHT_MM: f32 = 297.0;
+const PDF_MARGIN_MM: f32 = 20.0;
+/// Space kept free above the bottom margin for the page number.
+const PDF_FOOTER_MM: f32 = 8.0;
+const PDF_BODY_PT: f32 = 11.0;
+const PDF_HEADING_PT: f32 = 16.0;
+const PT_TO_MM: f32 = 25.4 / 72.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PdfAlign {
+    Left,
+    Center,
+}
+
+/// Line height in mm for text set at `size` points.
+fn pdf_line_height(size: f32) -> f32 {
+    size * 1.35 * PT_TO_MM
+}
+
+/// Approximate advance width of `text` in mm at `size` points. The built-in Times metrics are
+/// not exposed, so this errs wide: lines wrap a little early rather than run into the margin.
+fn pdf_text_width(text: &str, size: f32) -> f32 {
+    let em: f32 = text
+        .chars()
+        .map(|c| match c {
+            ' ' => 0.25,
+            'i' | 'j' | 'l' | 't' | 'f' | 'r' | '.' | ',' | ':' | ';' | '\'' | '!' | '|' => 0.33,
+            'm' | 'w' | 'M' | 'W' | '—' => 0.9,
+            c if c.is_uppercase() => 0.72,
+            _ => 0.5,
+        })
+        .sum();
+    em * size * PT_TO_MM
+}
+
+/// Greedy word wrap of `text` to `width` mm at `size` points. Words wider than a line are split.
+fn wrap_pdf_text(text: &str, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if pdf_text_width(&candidate, size) <= width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            line.push(c);
+            if pdf_text_width(&line, size) > width && line.chars().count() > 1 {
+                line.pop();
+                lines.push(std::mem::replace(&mut line, c.to_string()));
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Whether the built-in PDF fonts can show `c` (WinAnsi: Latin-1 plus typographic quotes,
+/// dashes, and a few symbols).
+fn is_win_ansi(c: char) -> bool {
+    const WIN_ANSI_EXTRA: &str = "€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ";
+    matches!(c as u32, 0x20..=0x7e | 0xa0..=0xff) || WIN_ANSI_EXTRA.contains(c)
+}
+
+/// Map `text` onto what the built-in PDF fonts can show (see [is_win_ansi]). Anything else
+/// becomes `?`.
+fn pdf_safe_text(text: &str) -> String {
+    text.chars()
+        .map(|c| if is_win_ansi(c) { c } else { '?' })
+        .collect()
+}
+
+/// The characters of `text` that [pdf_safe_text] replaces, added to `out`. Line breaks are
+/// handled by the layout and not counted.
+fn note_pdf_unsupported(text: &str, out: &mut BTreeSet<char>) {
+    out.extend(text.chars().filter(|&c| c != '\n' && !is_win_ansi(c)));
+}
+
+/// Text of a block element with `<br>` kept as a line break and other whitespace collapsed.
+fn pdf_block_text(el: ElementRef<'_>) -> String {
+    let mut raw = String::new();
+    for node in el.descendants() {
+        match node.value() {
+            Node::Text(t) => raw.push_str(t),
+            Node::Element(e) if e.name() == "br" => raw.push('\n'),
+            _ => {}
+        }
+    }
+    raw.lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Plain-text paragraphs of a chapter body for fixed-layout output: one per top-level block,
+/// one per list item (bulleted), and `* * *` for a scene break (`<hr>` or a `scene-break`
+/// divider paragraph).
+fn body_paragraphs(body: &str) -> Vec<String> {
+    let fragment = Html::parse_fragment(body);
+    let mut out = Vec::new();
+    for node in fragment.root_element().children() {
+        if let Node::Text(t) = node.value() {
+            let text = t.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !text.is_empty() {
+                out.push(text);
+            }
+            continue;
+        }
+        let Some(el) = ElementRef::wrap(node) else {
+            continue;
+        };
+        match el.value().name() {
+            "hr" => out.push("* * *".to_string()),
+            "p" if el.value().attr("class") == Some("scene-break") => out.push("* * *".to_string()),
+            "ul" | "ol" => out.extend(
+                el.children()
+                    .filter_map(ElementRef::wrap)
+                    .map(pdf_block_text)
+                    .filter(|t| !t.is_empty())
+                    .map(|t| format!("• {}", t)),
+            ),
+            _ => {
+                let text = pdf_block_text(el);
+                if !text.is_empty() {
+                    out.push(text);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// A PDF under construction: the pages added so far and the cursor on the last one.
+struct PdfPages {
+    doc: PdfDocumentReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    pages: Vec<(PdfPageIndex, PdfLayerIndex)>,
+    /// Baseline position for the next line on the last page, in mm from the bottom edge.
+    y: f32,
+}
+
+impl PdfPages {
+    fn new(title: &str) -> Result<PdfPages, FormatError> {
+        let (doc, page, layer) = PdfDocument::new(
+            pdf_safe_text(title),
+            Mm(PDF_PAGE_WIDTH_MM),
+            Mm(PDF_PAGE_HEIGHT_MM),
+            "Page 1",
+        );
+        let regular = doc.add_builtin_font(BuiltinFont::TimesRoman)?;
+        let bold = doc.add_builtin_font(BuiltinFont::TimesBold)?;
+        Ok(PdfPages {
+            doc,
+            regular,
+            bold,
+            pages: vec![(page, layer)],
+            y: PDF_PAGE_HEIGHT_MM - PDF_MARGIN_MM,
+        })
+    }
+
+    fn new_page(&mut self) {
+        let name = format!("Page {}", self.pages.len() + 1);
+        let added = self
+            .doc
+            .add_page(Mm(PDF_PAGE_WIDTH_MM), Mm(PDF_PAGE_HEIGHT_MM), name);
+        self.pages.push(added);
+        self.y = PDF_PAGE_HEIGHT_MM - PDF_MARGIN_MM;
+    }
+
+    /// Draw one line of text on page `n` (0-based) with its baseline at `y`.
+    fn draw(&self, n: usize, text: &str, size: f32, bold: bool, x: f32, y: f32) {
+        let (page, layer) = self.pages[n];
+        let font = if bold { &self.bold } else { &self.regular };
+        self.doc.get_page(page).get_layer(layer).use_text(
+            pdf_safe_text(text),
+            size,
+            Mm(x),
+            Mm(y),
+            font,
+        );
+    }
+
+    /// Set `text` as a wrapped paragraph from the cursor down, starting new pages as needed.
+    /// Line breaks in `text` are kept.
+    fn paragraph(&mut self, text: &str, size: f32, bold: bool, align: PdfAlign) {
+        let width = PDF_PAGE_WIDTH_MM - 2.0 * PDF_MARGIN_MM;
+        let line_height = pdf_line_height(size);
+        for line in text.lines().flat_map(|l| wrap_pdf_text(l, size, width)) {
+            if self.y - line_height < PDF_MARGIN_MM + PDF_FOOTER_MM {
+                self.new_page();
+            }
+            self.y -= line_height;
+            let x = match align {
+                PdfAlign::Left => PDF_MARGIN_MM,
+                PdfAlign::Center => (PDF_PAGE_WIDTH_MM - pdf_text_width(&line, size)) / 2.0,
+            };
+            self.draw(self.pages.len() - 1, &line, size, bold, x, self.y);
+        }
+    }
+
+    fn gap(&mut self, mm: f32) {
+        self.y -= mm;
+    }
+}
+
+/// Number of contents rows that fit on the first contents page (under the heading) and on each
+/// following one.
+fn pdf_toc_rows_per_page() -> (usize, usize) {
+    let usable = PDF_PAGE_HEIGHT_MM - 2.0 * PDF_MARGIN_MM - PDF_FOOTER_MM;
+    let row = pdf_line_height(PDF_BODY_PT);
+    let heading = pdf_line_height(PDF_HEADING_PT) + row;
+    (
+        ((usable - heading) / row).floor() as usize,
+        (usable / row).floor() as usize,
+    )
+}
+
+/// Write a single PDF (A4, Times): a title page with author and description, an optional
+/// contents listing with page numbers, then each chapter from a new page with its heading and
+/// paragraphs. Every page after the title page is numbered, and each chapter gets a bookmark.
+/// Chapter bodies are set as plain text; characters outside the built-in fonts' Latin range
+/// print as `?`, with a warning on stderr.
+pub fn write_pdf(book: &Book, path: &Path, toc_page: bool) -> Result<(), FormatError> {
+    validate_book(book)?;
+
+    let path = path.to_path_buf();
+    let file = File::create(&path).map_err(|e| FormatError::Io {
+        path: path.clone(),
+        source: e,
+    })?;
+
+    let mut unsupported = BTreeSet::new();
+    note_pdf_unsupported(&book.title, &mut unsupported);
+    note_pdf_unsupported(&book.author, &mut unsupported);
+    let mut pdf = PdfPages::new(&book.title)?;
+    pdf.y = PDF_PAGE_HEIGHT_MM * 0.65;
+    pdf.paragraph(&book.title, 24.0, true, PdfAlign::Center);
+    pdf.gap(6.0);
+    pdf.paragraph(
+        &format!("By {}", book.author),
+        14.0,
+        false,
+        PdfAlign::Center,
+    );
+    if let Some(ref d) = book.description {
+        pdf.gap(12.0);
+        for para in d.split("\n\n").filter(|p| !p.trim().is_empty()) {
+            note_pdf_unsupported(para.trim(), &mut unsupported);
+            pdf.paragraph(para.trim(), PDF_BODY_PT, false, PdfAlign::Left);
+            pdf.gap(2.0);
+        }
+    }
+
+    // Reserve the contents pages now; they are filled in once chapter page numbers are known.
+    let toc_start = pdf.pages.len();
+    if toc_page && !book.chapters.is_empty() {
+        let (first, rest) = pdf_toc_rows_per_page();
+        let extra = book.chapters.len().saturating_sub(first).div_ceil(rest);
+        for _ in 0..=extra {
+            pdf.new_page();
+        }
+    }
+    let toc_pages = pdf.pages.len() - toc_start;
+
+    let mut chapter_pages = Vec::with_capacity(book.chapters.len());
+    for ch in &book.chapters {
+        pdf.new_page();
+        chapter_pages.push(pdf.pages.len());
+        let (page, _) = pdf.pages[pdf.pages.len() - 1];
+        pdf.doc.add_bookmark(pdf_safe_text(&ch.title), page);
+        note_pdf_unsupported(&ch.title, &mut unsupported);
+        pdf.paragraph(&ch.title, PDF_HEADING_PT, true, PdfAlign::Left);
+        pdf.gap(pdf_line_height(PDF_BODY_PT));
+        for para in body_paragraphs(&ch.body) {
+            note_pdf_unsupported(&para, &mut unsupported);
+            let align = if para == "* * *" {
+                PdfAlign::Center
+            } else {
+                PdfAlign::Left
+            };
+            pdf.paragraph(&para, PDF_BODY_PT, false, align);
+            pdf.gap(pdf_line_height(PDF_BODY_PT) * 0.5);
+        }
+    }
+
+    if toc_pages > 0 {
+        let (first, rest) = pdf_toc_rows_per_page();
+        let row = pdf_line_height(PDF_BODY_PT);
+        let top = PDF_PAGE_HEIGHT_MM - PDF_MARGIN_MM;
+        let heading_y = top - pdf_line_height(PDF_HEADING_PT);
+        pdf.draw(
+            toc_start,
+            "Contents",
+            PDF_HEADING_PT,
+            true,
+            PDF_MARGIN_MM,
+            heading_y,
+        );
+        let right = PDF_PAGE_WIDTH_MM - PDF_MARGIN_MM;
+        for (i, (ch, number)) in book.chapters.iter().zip(&chapter_pages).enumerate() {
+            let (page, slot, start_y) = if i < first {
+                (toc_start, i, heading_y - row)
+            } else {
+                (toc_start + 1 + (i - first) / rest, (i - first) % rest, top)
+            };
+            let y = start_y - (slot + 1) as f32 * row;
+            let number = number.to_string();
+            let title_width = right - PDF_MARGIN_MM - pdf_text_width(&number, PDF_BODY_PT) - 6.0;
+            let title = wrap_pdf_text(&ch.title, PDF_BODY_PT, title_width)
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+            pdf.draw(page, &title, PDF_BODY_PT, false, PDF_MARGIN_MM, y);
+            let x = right - pdf_text_width(&number, PDF_BODY_PT);
+            pdf.draw(page, &number, PDF_BODY_PT, false, x, y);
+        }
+    }
+
+    for n in 1..pdf.pages.len() {
+        let number = (n + 1).to_string();
+        let x = (PDF_PAGE_WIDTH_MM - pdf_text_width(&number, 10.0)) / 2.0;
+        pdf.draw(n, &number, 10.0, false, x, PDF_MARGIN_MM);
+    }
+
+    pdf.doc.save(&mut BufWriter::new(file))?;
+    if !unsupported.is_empty() {
+        let sample: String = unsupported.iter().take(10).collect();
+        eprintln!(
+            "Warning: {} distinct character(s) are outside the PDF's built-in fonts and were printed as '?' (e.g. {}).",
+            unsupported.len(),
+            sample
+        );
+    }
+    Ok(())
+}
+
+/// One FB2 `<author>` element per comma-separated name. Single-word names become a
+/// `<nickname>`; otherwise the first word is the first name, the last word the last name, and
+/// anything between the middle name.
+fn fb2_authors(author: &str) -> String {
+    let mut out = String::new();
+    for name in author.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let words: Vec<&str> = name.split_whitespace().collect();
+        let inner = match words.as_slice() {
+            [only] => format!("<nickname>{}</nickname>", html_escape_attr(only)),
+            [first, middle @ .., last] => {
+                let mut parts = format!("<first-name>{}</first-name>", html_escape_attr(first));
+                if !middle.is_empty() {
+                    parts.push_str(&format!(
+                        "<middle-name>{}</middle-name>",
+                        html_escape_attr(&middle.join(" "))
+                    ));
+                }
+                parts.push_str(&format!(
+                    "<last-name>{}</last-name>",
+                    html_escape_attr(last)
+                ));
+                parts
+            }
+            [] => continue,
+        };
+        out.push_str(&format!("      <author>{}</author>\n", inner));
+    }
+    out
+}
+
+/// `<p>` elements for plain-text paragraphs, one per line; a `* * *` scene break becomes a
+/// `<subtitle>`. Never empty, since an FB2 section needs content after its title.
+fn fb2_paragraphs(paragraphs: &[String], indent: &str) -> String {
+    let mut out = String::new();
+    for para in paragraphs {
+        if para == "* * *" {
+            out.push_str(&format!("{}<subtitle>* * *</subtitle>\n", indent));
+            continue;
+        }
+        for line in para.lines() {
+            out.push_str(&format!("{}<p>{}</p>\n", indent, html_escape_attr(line)));
+        }
+    }
+    if out.is_empty() {
+        out.push_str(&format!("{}<empty-line/>\n", indent));
+    }
+    out
+}
+
+/// Write a single FictionBook 2 (`.fb2`) file: `<title-info>` with genre, authors, title,
+/// annotation (from the description), keywords (from tags), series, and language; then one
+/// `<section>` per chapter with its title and plain-text paragraphs. `cover` is the image bytes
+/// and file extension, embedded base64 in a `<binary>` and referenced from `<coverpage>`.
+pub fn write_fb2(
+    book: &Book,
+    path: &Path,
+    cover: Option<(&[u8], &str)>,
+) -> Result<(), FormatError> {
+    validate_book(book)?;
+
+    let path = path.to_path_buf();
+    let mut f = File::create(&path).map_err(|e| FormatError::Io {
+        path: path.clone(),
+        source: e,
+    })?;
+
+    let title_esc = html_escape_attr(&book.title);
+    let date = modified_timestamp()[..10].to_string();
+    writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        f,
+        r#"<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0" xmlns:l="http://www.w3.org/1999/xlink">"#
+    )?;
+    writeln!(f, "  <description>")?;
+    writeln!(f, "    <title-info>")?;
+    writeln!(f, "      <genre>prose_contemporary</genre>")?;
+    write!(f, "{}", fb2_authors(&book.author))?;
+    writeln!(f, "      <book-title>{}</book-title>", title_esc)?;
+    if let Some(ref d) = book.description {
+        let paragraphs: Vec<String> = d
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+            .collect();
+        if !paragraphs.is_empty() {
+            writeln!(f, "      <annotation>")?;
+            write!(f, "{}", fb2_paragraphs(&paragraphs, "        "))?;
+            writeln!(f, "      </annotation>")?;
+        }
+    }
+    if !book.tags.is_empty() {
+        writeln!(
+            f,
+            "      <keywords>{}</keywords>",
+            html_escape_attr(&book.tags.join(", "))
+        )?;
+    }
+    if let Some((_, ext)) = cover {
+        writeln!(
+            f,
+            r##"      <coverpage><image l:href="#cover.{}"/></coverpage>"##,
+            ext
+        )?;
+    }
+    writeln!(
+        f,
+        "      <lang>{}</lang>",
+        html_escape_attr(book.language.as_deref().unwrap_or("en"))
+    )?;
+    if let Some(ref series) = book.series {
+        // FB2 sequence numbers are integers; a fractional index is left off.
+        let number = match book.series_index {
+            Some(i) if i >= 0.0 && i.fract() == 0.0 => format!(r#" number="{}""#, i as u32),
+            _ => String::new(),
+        };
+        writeln!(
+            f,
+            r#"      <sequence name="{}"{}/>"#,
+            html_escape_attr(series),
+            number
+        )?;
+    }
+    writeln!(f, "    </title-info>")?;
+    writeln!(f, "    <document-info>")?;
+    writeln!(f, "      <author><nickname>rdrscrape</nickname></author>")?;
+    writeln!(f, "      <program-used>rdrscrape</program-used>")?;
+    writeln!(f, r#"      <date value="{}">{}</date>"#, date, date)?;
+    if let Some(ref url) = book.source_url {
+        writeln!(f, "      <src-url>{}</src-url>", html_escape_attr(url))?;
+    }
+    writeln!(f, "      <id>{}</id>", html_escape_attr(&identifier(book)))?;
+    writeln!(f, "      <version>1.0</version>")?;
+    writeln!(f, "    </document-info>")?;
+    writeln!(f, "  </description>")?;
+
+    writeln!(f, "  <body>")?;
+    writeln!(
+        f,
+        "    <title><p>{}</p><p>{}</p></title>",
+        title_esc,
+        html_escape_attr(&book.author)
+    )?;
+    for ch in &book.chapters {
+        writeln!(f, "    <section>")?;
+        writeln!(
+            f,
+            "      <title><p>{}</p></title>",
+            html_escape_attr(&ch.title)
+        )?;
+        write!(
+            f,
+            "{}",
+            fb2_paragraphs(&body_paragraphs(&ch.body), "      ")
+        )?;
+        writeln!(f, "    </section>")?;
+    }
+    writeln!(f, "  </body>")?;
+
+    if let Some((data, ext)) = cover {
+        writeln!(
+            f,
+            r#"  <binary id="cover.{}" content-type="{}">{}</binary>"#,
+            ext,
+            image_media_type(ext),
+            base64::engine::general_purpose::STANDARD.encode(data)
+        )?;
+    }
+    writeln!(f, "</FictionBook>")?;
+    Ok(())
+}
+
+/// Write a chapter index sidecar: UTF-8 TSV with a header row and one
+/// `index<TAB>title<TAB>url<TAB>word_count` line per chapter. Tabs and line breaks in titles
+/// become spaces; the url column is empty when the chapter URL is unknown.
+pub fn write_index(book: &Book, path: &Path) -> Result<(), FormatError> {
+    let path = path.to_path_buf();
+    let mut f = File::create(&path).map_err(|e| FormatError::Io {
+        path: path.clone(),
+        source: e,
+    })?;
+    f.write_all(index_tsv(book).as_bytes())?;
+    Ok(())
+}
+
+fn index_tsv(book: &Book) -> String {
+    let field = |s: &str| s.replace(['\t', '\r', '\n'], " ");
+    let mut out = String::from("index\ttitle\turl\tword_count\n");
+    for ch in &book.chapters {
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\n",
+            ch.index,
+            field(&ch.title),
+            field(ch.url.as_deref().unwrap_or_default()),
+            body_word_count(&ch.body)
+        ));
+    }
+    out
+}
+
+/// Write a zip bundle with one deflated entry per `(name, bytes)` pair, in order.
+pub fn write_bundle(path: &Path, entries: &[(String, Vec<u8>)]) -> Result<(), FormatError> {
+    let file = File::create(path).map_err(|e| FormatError::Io {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for (name, bytes) in entries {
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(bytes)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Media type for an image file extension (as produced by the cover and image fetchers); PNG
+/// for anything unrecognised.
+pub(crate) fn image_media_type(ext: &str) -> &'static str {
+    match ext {
+        "jpg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => "image/png",
+    }
+}
+
+/// Current UTC time as RFC 3339 `YYYY-MM-DDThh:mm:ssZ`, for Atom `<updated>`.
+pub(crate) fn utc_datetime_now() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    utc_datetime(secs)
+}
+
+/// EPUB 3 `dcterms:modified` value: `SOURCE_DATE_EPOCH` (seconds since 1970) when set, so repeated
+/// builds of the same book are byte-identical, else the current time.
+pub(crate) fn modified_timestamp() -> String {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(utc_datetime)
+        .unwrap_or_else(utc_datetime_now)
+}
+
+/// `secs` since 1970-01-01 as RFC 3339 `YYYY-MM-DDThh:mm:ssZ`.
+fn utc_datetime(secs: u64) -> String {
+    let (y, m, d) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        y,
+        m,
+        d,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// Proleptic Gregorian (year, month, day) for a count of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,13 +1367,14 @@ mod tests {
             title: "Test Book".to_string(),
             author: "Test Author".to_string(),
             description: Some("A test.".to_string()),
-            cover_url: None,
             chapters: vec![Chapter {
                 title: "Chapter One".to_string(),
                 index: 1,
                 body: "<p>First paragraph.</p><p>Second paragraph.</p>".to_string(),
+                reported_word_count: None,
+                url: None,
             }],
-            source_url: None,
+            ..Default::default()
         }
     }
 
@@ -203,16 +1382,51 @@ mod tests {
     fn write_html_contains_title_and_chapter_heading() {
         let book = minimal_book();
         let path = std::env::temp_dir().join("rdrscrape_test_html.html");
-        write_html(&book, &path).unwrap();
+        write_html(&book, &path, false).unwrap();
         let mut buf = String::new();
         File::open(&path).unwrap().read_to_string(&mut buf).unwrap();
         std::fs::remove_file(&path).ok();
         assert!(buf.contains("Test Book"));
+        assert!(buf.contains(r#"<html lang="en">"#));
         assert!(buf.contains("<h2>"));
         assert!(buf.contains("Chapter One"));
         assert!(buf.contains("First paragraph"));
     }
 
+    #[test]
+    fn write_html_uses_book_language() {
+        let mut book = minimal_book();
+        book.language = Some("pt-BR".to_string());
+        let path = std::env::temp_dir().join("rdrscrape_test_html_lang.html");
+        write_html(&book, &path, false).unwrap();
+        let mut buf = String::new();
+        File::open(&path).unwrap().read_to_string(&mut buf).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(buf.contains(r#"<html lang="pt-BR">"#));
+    }
+
+    #[test]
+    fn write_html_nav_links_omit_missing_direction() {
+        let mut book = minimal_book();
+        book.chapters.push(Chapter {
+            title: "Chapter Two".to_string(),
+            index: 2,
+            body: "<p>Second chapter.</p>".to_string(),
+            reported_word_count: None,
+            url: None,
+        });
+        let path = std::env::temp_dir().join("rdrscrape_test_html_nav.html");
+        write_html(&book, &path, true).unwrap();
+        let mut buf = String::new();
+        File::open(&path).unwrap().read_to_string(&mut buf).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(buf.contains(r#"id="chapter-1""#));
+        assert!(buf.contains(r##"<a href="#chapter-2">Next</a>"##));
+        assert!(buf.contains(r##"<a href="#chapter-1">Previous</a>"##));
+        assert!(!buf.contains(r##"href="#chapter-0""##));
+        assert!(!buf.contains(r##"href="#chapter-3""##));
+    }
+
     #[test]
     fn write_markdown_contains_headers_and_no_raw_p_tags() {
         let book = minimal_book();
@@ -227,6 +1441,52 @@ mod tests {
         assert!(!buf.contains("<p>"));
     }
 
+    #[test]
+    fn body_to_markdown_maps_allowlisted_tags() {
+        let body = "<p>A <em>quiet</em> and <strong>loud <em>mix</em></strong>.</p>\
+                    <p>Line one<br/>line two</p><hr/><p><a href=\"https://e.x/a\">link</a></p>";
+        assert_eq!(
+            body_to_markdown(body).unwrap(),
+            "A *quiet* and **loud *mix***.\n\nLine one  \nline two\n\n---\n\n[link](https://e.x/a)"
+        );
+    }
+
+    #[test]
+    fn body_to_markdown_subheadings_sit_below_chapter_title() {
+        let body = "<h2>Part <em>One</em></h2><p>Text.</p><h6>Deep</h6>";
+        assert_eq!(
+            body_to_markdown(body).unwrap(),
+            "### Part *One*\n\nText.\n\n###### Deep"
+        );
+        assert_eq!(body_to_markdown("<h1>Top</h1>"), None);
+    }
+
+    #[test]
+    fn body_to_markdown_moves_whitespace_outside_emphasis_and_escapes_text() {
+        let body = "<p>one<em> two </em>three</p><p>1. not a list * or _emphasis_</p>";
+        assert_eq!(
+            body_to_markdown(body).unwrap(),
+            "one *two* three\n\n1\\. not a list \\* or \\_emphasis\\_"
+        );
+    }
+
+    #[test]
+    fn body_to_markdown_lists_nest_under_items() {
+        let body = "<ul><li>a<ul><li>b</li></ul></li><li><b>c</b></li></ul>\
+                    <ol><li>x</li><li>y</li></ol>";
+        assert_eq!(
+            body_to_markdown(body).unwrap(),
+            "- a\n  - b\n- **c**\n\n1. x\n2. y"
+        );
+    }
+
+    #[test]
+    fn body_to_markdown_unexpected_markup_falls_back() {
+        assert!(body_to_markdown("<p>x<span>y</span></p>").is_none());
+        assert!(body_to_markdown("<div><p>x</p></div>").is_none());
+        assert!(body_to_markdown("bare text").is_none());
+    }
+
     #[test]
     fn write_text_contains_chapter_title_and_no_html_tags() {
         let book = minimal_book();
@@ -241,13 +1501,118 @@ mod tests {
         assert!(!buf.contains("<p>"));
     }
 
+    #[test]
+    fn write_pdf_writes_a_complete_pdf() {
+        let mut book = minimal_book();
+        book.chapters.push(Chapter {
+            title: "Chapter Two".to_string(),
+            index: 2,
+            body: "<p>Third.</p><hr/><p>Fourth.</p>".to_string(),
+            reported_word_count: None,
+            url: None,
+        });
+        let path = std::env::temp_dir().join("rdrscrape_test_pdf.pdf");
+        write_pdf(&book, &path, true).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(bytes.starts_with(b"%PDF-"));
+        assert!(String::from_utf8_lossy(&bytes)
+            .trim_end()
+            .ends_with("%%EOF"));
+    }
+
+    #[test]
+    fn write_pdf_rejects_empty_title() {
+        let mut book = minimal_book();
+        book.title.clear();
+        let path = std::env::temp_dir().join("rdrscrape_void.pdf");
+        assert!(matches!(
+            write_pdf(&book, &path, false),
+            Err(FormatError::EmptyTitle)
+        ));
+    }
+
+    #[test]
+    fn body_paragraphs_splits_blocks_lists_and_breaks() {
+        let body =
+            "<p>One  <em>two</em></p><p>Line<br/>break</p><hr/><ul><li>a</li><li>b</li></ul><p class=\"scene-break\">~ ~ ~</p>";
+        assert_eq!(
+            body_paragraphs(body),
+            vec!["One two", "Line\nbreak", "* * *", "• a", "• b", "* * *"]
+        );
+    }
+
+    #[test]
+    fn wrap_pdf_text_fits_width_and_splits_long_words() {
+        let lines = wrap_pdf_text("the quick brown fox jumps over the lazy dog", 11.0, 30.0);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| pdf_text_width(l, 11.0) <= 30.0));
+        assert_eq!(
+            lines.join(" "),
+            "the quick brown fox jumps over the lazy dog"
+        );
+        let long = wrap_pdf_text(&"x".repeat(200), 11.0, 30.0);
+        assert!(long.len() > 1);
+        assert_eq!(long.concat(), "x".repeat(200));
+    }
+
+    #[test]
+    fn pdf_safe_text_keeps_win_ansi_and_replaces_the_rest() {
+        assert_eq!(pdf_safe_text("Café “quoted” — …"), "Café “quoted” — …");
+        assert_eq!(pdf_safe_text("日本 ok"), "?? ok");
+        let mut unsupported = BTreeSet::new();
+        note_pdf_unsupported("日本 ok\nCafé 日", &mut unsupported);
+        assert_eq!(unsupported.into_iter().collect::<String>(), "日本");
+    }
+
+    #[test]
+    fn write_fb2_writes_sections_annotation_and_cover() {
+        let mut book = minimal_book();
+        book.author = "Jane Q. Public, Solo".to_string();
+        book.chapters[0].body = "<p>One &amp; two</p><hr/><p>Line<br/>break</p>".to_string();
+        book.series = Some("Saga".to_string());
+        book.series_index = Some(2.0);
+        let path = std::env::temp_dir().join("rdrscrape_test_fb2.fb2");
+        write_fb2(&book, &path, Some((b"png-bytes".as_slice(), "png"))).unwrap();
+        let buf = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(buf.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert!(buf.contains("<author><first-name>Jane</first-name><middle-name>Q.</middle-name><last-name>Public</last-name></author>"));
+        assert!(buf.contains("<author><nickname>Solo</nickname></author>"));
+        assert!(buf.contains("<book-title>Test Book</book-title>"));
+        assert!(buf.contains("<annotation>\n        <p>A test.</p>\n      </annotation>"));
+        assert!(buf.contains(r#"<sequence name="Saga" number="2"/>"#));
+        assert!(buf.contains("<title><p>Chapter One</p></title>"));
+        assert!(buf.contains("<p>One &amp; two</p>\n      <subtitle>* * *</subtitle>\n      <p>Line</p>\n      <p>break</p>"));
+        assert!(buf.contains(r##"<coverpage><image l:href="#cover.png"/></coverpage>"##));
+        assert!(buf
+            .contains(r#"<binary id="cover.png" content-type="image/png">cG5nLWJ5dGVz</binary>"#));
+        assert!(buf.trim_end().ends_with("</FictionBook>"));
+    }
+
+    #[test]
+    fn write_fb2_without_cover_or_body_text() {
+        let mut book = minimal_book();
+        book.chapters[0].body = String::new();
+        let path = std::env::temp_dir().join("rdrscrape_test_fb2_bare.fb2");
+        write_fb2(&book, &path, None).unwrap();
+        let buf = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(!buf.contains("<coverpage>"));
+        assert!(!buf.contains("<binary"));
+        assert!(buf.contains(
+            "<author><first-name>Test</first-name><last-name>Author</last-name></author>"
+        ));
+        assert!(buf.contains("<title><p>Chapter One</p></title>\n      <empty-line/>"));
+    }
+
     #[test]
     fn validate_rejects_empty_title() {
         let mut book = minimal_book();
         book.title.clear();
         let path = std::env::temp_dir().join("rdrscrape_void.html");
         assert!(matches!(
-            write_html(&book, &path),
+            write_html(&book, &path, false),
             Err(FormatError::EmptyTitle)
         ));
     }
@@ -258,7 +1623,7 @@ mod tests {
         book.author.clear();
         let path = std::env::temp_dir().join("rdrscrape_void.html");
         assert!(matches!(
-            write_html(&book, &path),
+            write_html(&book, &path, false),
             Err(FormatError::EmptyAuthor)
         ));
     }
@@ -301,4 +1666,214 @@ mod tests {
         assert_eq!(once, "a &amp; b");
         assert_eq!(twice, "a &amp;amp; b");
     }
+
+    #[test]
+    fn write_bundle_contains_entries_in_order() {
+        let path = std::env::temp_dir().join("rdrscrape_test_bundle.zip");
+        let entries = vec![
+            ("book.html".to_string(), b"<html></html>".to_vec()),
+            ("book.json".to_string(), b"{}".to_vec()),
+        ];
+        write_bundle(&path, &entries).unwrap();
+        let mut archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let names: Vec<String> = archive.file_names().map(String::from).collect();
+        assert_eq!(names.len(), 2);
+        let mut json = String::new();
+        archive
+            .by_name("book.json")
+            .unwrap()
+            .read_to_string(&mut json)
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(names.contains(&"book.html".to_string()));
+        assert_eq!(json, "{}");
+    }
+
+    #[test]
+    fn index_tsv_has_header_and_one_row_per_chapter() {
+        let mut book = minimal_book();
+        book.chapters[0].title = "One\ttwo\nthree".to_string();
+        book.chapters[0].url = Some("https://example.com/c/1".to_string());
+        book.chapters.push(Chapter {
+            title: "Ünïcode".to_string(),
+            index: 2,
+            body: "<p>Four words right here.</p>".to_string(),
+            reported_word_count: None,
+            url: None,
+        });
+        let tsv = index_tsv(&book);
+        let lines: Vec<&str> = tsv.lines().collect();
+        assert_eq!(lines[0], "index\ttitle\turl\tword_count");
+        assert!(lines[1].starts_with("1\tOne two three\thttps://example.com/c/1\t"));
+        assert_eq!(lines[2], "2\tÜnïcode\t\t4");
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn normalize_body_html_default_keeps_emphasis_only() {
+        let html = r#"<p> One <em>two</em> &amp; <a href="/x">three</a> </p>
+<blockquote>quoted</blockquote><p>x<sup>2</sup> <b>bold</b><br>next <i>it</i> <strong>s</strong></p>"#;
+        assert_eq!(
+            normalize_body_html(html, &BodyOptions::default()),
+            "<p>One <em>two</em> &amp; three</p>\
+             <p>x2 <b>bold</b><br/>next <i>it</i> <strong>s</strong></p>"
+        );
+        assert_eq!(
+            normalize_body_html("<p>He said <em>no</em>.</p>", &BodyOptions::default()),
+            "<p>He said <em>no</em>.</p>"
+        );
+    }
+
+    #[test]
+    fn normalize_body_html_keeps_scene_breaks() {
+        let opts = BodyOptions::default();
+        let html = r#"<p>One.</p><hr class="x"><p align="center">~ ~ ~</p>
+<p class="text-center">Centred prose.</p><p>* * *</p><p>Two.</p>"#;
+        assert_eq!(
+            normalize_body_html(html, &opts),
+            r#"<p>One.</p><hr/><p class="scene-break">~ ~ ~</p><p>Centred prose.</p><p>* * *</p><p>Two.</p>"#
+        );
+        assert_eq!(
+            normalize_body_html("<div>One.</div><hr><div>Two.</div>", &opts),
+            "<p>One.</p><hr/><p>Two.</p>"
+        );
+        assert_eq!(normalize_body_html("<hr><hr>", &opts), "");
+        let no_rules = BodyOptions {
+            block_tags: vec!["p".to_string()],
+            ..BodyOptions::default()
+        };
+        assert_eq!(
+            normalize_body_html("<p>One.</p><hr><p>Two.</p>", &no_rules),
+            "<p>One.</p><p>Two.</p>"
+        );
+        assert_eq!(
+            body_to_markdown(r#"<p>One.</p><hr/><p class="scene-break">~ ~ ~</p>"#).unwrap(),
+            "One.\n\n---\n\n~ ~ ~"
+        );
+    }
+
+    #[test]
+    fn normalize_body_html_demotes_headings() {
+        let html = "<h1>Scene <b>one</b></h1><p>Text.</p><h3>Aside</h3><h6>Deep</h6>";
+        assert_eq!(
+            normalize_body_html(html, &BodyOptions::default()),
+            "<h2>Scene <b>one</b></h2><p>Text.</p><h4>Aside</h4><h6>Deep</h6>"
+        );
+    }
+
+    #[test]
+    fn normalize_body_html_keeps_images_with_src_and_alt() {
+        let html = r#"<p>Map: <img src="/m.png" alt="The &quot;map&quot;" width="9" class="x"></p>
+<img src="stats.jpg"><img alt="no source"><p><img data-src="lazy.png"></p>"#;
+        assert_eq!(
+            normalize_body_html(html, &BodyOptions::default()),
+            r#"<p>Map: <img src="/m.png" alt="The &quot;map&quot;"/></p><p><img src="stats.jpg" alt=""/></p><p></p>"#
+        );
+        assert_eq!(
+            body_to_markdown(r#"<p>See <img src="/m.png" alt="Map"/></p>"#).unwrap(),
+            "See ![Map](/m.png)"
+        );
+    }
+
+    #[test]
+    fn normalize_body_html_div_paragraphs_only_without_blocks() {
+        let opts = BodyOptions::default();
+        let divs = "<div>First <b>line</b>.</div><div> </div><div>Second.</div>";
+        assert_eq!(
+            normalize_body_html(divs, &opts),
+            "<p>First <b>line</b>.</p><p>Second.</p>"
+        );
+        assert_eq!(
+            normalize_body_html("<p>Real.</p><div>Aside.</div>", &opts),
+            "<p>Real.</p>"
+        );
+        assert_eq!(normalize_body_html("<div><p>Wrapped.</p></div>", &opts), "");
+    }
+
+    #[test]
+    fn normalize_body_html_div_paragraphs_beside_headings_and_images() {
+        let opts = BodyOptions::default();
+        assert_eq!(
+            normalize_body_html("<h2>T</h2><div>a</div><div>b</div>", &opts),
+            "<h3>T</h3><p>a</p><p>b</p>"
+        );
+        assert_eq!(
+            normalize_body_html(r#"<img src="map.png"><div>a</div><hr><div>b</div>"#, &opts),
+            r#"<p><img src="map.png" alt=""/></p><p>a</p><hr/><p>b</p>"#
+        );
+    }
+
+    #[test]
+    fn normalize_body_html_keeps_list_items_and_nesting() {
+        let html = r#"<ul>
+<li>Sword <em>(rusty)</em></li>
+<li>Potions<ol><li>Health</li><li>Mana</li></ol></li>
+<span>stray</span>
+</ul>"#;
+        let tags = BodyOptions {
+            inline_tags: vec!["em".to_string()],
+            ..BodyOptions::default()
+        };
+        assert_eq!(
+            normalize_body_html(html, &tags),
+            "<ul><li>Sword <em>(rusty)</em></li>\
+             <li>Potions<ol><li>Health</li><li>Mana</li></ol></li></ul>"
+        );
+        let paragraphs_only = BodyOptions {
+            block_tags: vec!["p".to_string()],
+            inline_tags: Vec::new(),
+        };
+        assert_eq!(normalize_body_html(html, &paragraphs_only), "");
+    }
+
+    #[test]
+    fn normalize_body_html_configured_tags_keep_and_strip() {
+        let html = r#"<p>E = mc<sup>2</sup>, <a href="/x?a=1&amp;b=2">link</a></p>
+<blockquote><p>quoted <em>text</em></p></blockquote><div>dropped</div>"#;
+        let tags = BodyOptions {
+            block_tags: vec!["p".to_string(), "blockquote".to_string()],
+            inline_tags: vec!["sup".to_string(), "em".to_string()],
+        };
+        assert_eq!(
+            normalize_body_html(html, &tags),
+            "<p>E = mc<sup>2</sup>, link</p><blockquote>quoted <em>text</em></blockquote>"
+        );
+        let links = BodyOptions {
+            inline_tags: vec!["a".to_string()],
+            ..BodyOptions::default()
+        };
+        assert_eq!(
+            normalize_body_html(html, &links),
+            r#"<p>E = mc2, <a href="/x?a=1&amp;b=2">link</a></p>"#
+        );
+    }
+
+    #[test]
+    fn markdown_residual_html_reports_tags_by_chapter() {
+        let md = "# Book\n\nBy A\n\n<b>blurb</b>\n\n---\n\n## 1. Start\n\nE = mc<sup>2</sup> and <sup>3</sup>\n\n## 2. Clean\n\nSee <https://example.com>, `<code>` and 1 < 2 > 0.\n\n```\n<pre>kept</pre>\n```\n\n## 3. Break\n\nline<br/>next <span class=\"x\">y</span>\n";
+        assert_eq!(
+            markdown_residual_html(md),
+            vec![
+                ("front matter".to_string(), "b".to_string()),
+                ("1. Start".to_string(), "sup".to_string()),
+                ("3. Break".to_string(), "br".to_string()),
+                ("3. Break".to_string(), "span".to_string()),
+            ]
+        );
+        assert!(markdown_residual_html("## T\n\nPlain *text*.\n").is_empty());
+    }
+
+    #[test]
+    fn utc_datetime_formats_seconds() {
+        assert_eq!(utc_datetime(0), "1970-01-01T00:00:00Z");
+        assert_eq!(utc_datetime(1_704_067_200), "2024-01-01T00:00:00Z");
+        assert_eq!(utc_datetime(1_704_153_599), "2024-01-01T23:59:59Z");
+    }
+
+    #[test]
+    fn civil_from_days_known_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+    }
 }
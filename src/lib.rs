@@ -8,9 +8,18 @@ pub mod model;
 pub mod scraper;
 
 // Re-exports for CLI and consumers.
-pub use epub::{write_epub, EpubError, EpubVersion};
-pub use formats::{write_html, write_markdown, write_text, FormatError, OutputFormat};
+pub use epub::{
+    chapter_display_title, write_epub, write_epub_to, write_opds_entry, ChapterFileNames,
+    EpubError, EpubOptions, EpubVersion,
+};
+pub use formats::{
+    markdown_residual_html, normalize_body_html, write_bundle, write_fb2, write_html, write_index,
+    write_markdown, write_pdf, write_text, BodyOptions, FormatError, OutputFormat,
+};
 pub use scraper::{
-    resolve_site, scrape_book, EmptyChapterBehavior, PoliteClient, PoliteClientBuilder,
-    ScrapeOptions, Scraper, ScraperError, Site,
+    check_blocked_host, drop_nav_artifacts, resolve_site, scrape_book, site_for_host,
+    site_override_mismatch, ChapterAllowlist, ChapterSelection, ChapterSource,
+    EmptyChapterBehavior, ExtraHosts, IndexCollisionBehavior, PoliteClient, PoliteClientBuilder,
+    ProgressCallback, ScrapeOptions, Scraper, ScraperError, ScraperErrorKind, ScribbleHubTocMode,
+    Site, TocCallback, TocEntry, TocSort,
 };
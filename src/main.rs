@@ -1,14 +1,20 @@
 fn main() {
     use clap::Parser;
+    use rdrscrape::cli::ErrorFormat;
     use std::error::Error;
     let args = rdrscrape::cli::Args::parse();
     if let Err(e) = rdrscrape::cli::run(&args) {
-        eprintln!("{}", e);
-        if args.verbose {
-            let mut source = e.source();
-            while let Some(s) = source {
-                eprintln!("  cause: {}", s);
-                source = s.source();
+        match args.error_format {
+            ErrorFormat::Json => eprintln!("{}", e.to_json()),
+            ErrorFormat::Human => {
+                eprintln!("{}", e);
+                if args.verbose {
+                    let mut source = e.source();
+                    while let Some(s) = source {
+                        eprintln!("  cause: {}", s);
+                        source = s.source();
+                    }
+                }
             }
         }
         std::process::exit(e.exit_code());
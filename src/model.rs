@@ -3,12 +3,15 @@
 //! Shape is defined in OUTPUT_SHAPE.md.
 //! The EPUB writer and scrapers use this as the single source of truth.
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
 
 /// Canonical book shape: one story/series.
 ///
 /// See OUTPUT_SHAPE.md. All site adapters produce this shape; the EPUB writer consumes it.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Book {
     pub title: String,
     pub author: String,
@@ -19,6 +22,205 @@ pub struct Book {
     /// Origin URL for logging/cache. Not in OUTPUT_SHAPE.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source_url: Option<String>,
+    /// Popularity stats from the story page (e.g. "total_views" -> "1,234"), collected with
+    /// `--stats`. Values are kept as the site prints them. JSON output only.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stats: BTreeMap<String, String>,
+    /// BCP 47 language tag for EPUB `<dc:language>` (e.g. "fr"), from `--language` or config
+    /// `language`. None is written as "en".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Series the book belongs to, from `--series`. Written to the OPF as EPUB 3
+    /// `belongs-to-collection` or EPUB 2 `calibre:series`; nothing is written when None.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
+    /// Position within `series` (e.g. 2 or 2.5), from `--series-index`.
+    #[serde(
+        rename = "seriesIndex",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub series_index: Option<f32>,
+    /// Genre tags from the story page (Royal Road `span.tags a`, Scribble Hub `a.fic_genre`),
+    /// trimmed and deduplicated with [clean_tags]. Written to the OPF as `<dc:subject>`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Credits besides the primary author (translators, co-authors, ...), from `--contributor`.
+    /// Written to the OPF as `<dc:contributor>`; `author` stays the only `<dc:creator>`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contributors: Vec<Contributor>,
+    /// 1-based number of this book among the parts `--split-on-title` cut from one story. The
+    /// parts share `source_url`; the number keeps their [identifier]s apart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part: Option<u32>,
+    /// The scraped title when `--title-with-author` replaced `title` with "Title — Author".
+    /// Written to the OPF as `<meta name="rdrscrape:original-title">`.
+    #[serde(
+        rename = "originalTitle",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub original_title: Option<String>,
+}
+
+/// An additional credit on a book.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contributor {
+    /// MARC relator code: "trl" (translator), "aut" (co-author), "edt" (editor), "ill" (illustrator), ...
+    pub role: String,
+    pub name: String,
+}
+
+/// Tags trimmed, with blanks and case-insensitive repeats dropped; first spelling and order kept.
+pub fn clean_tags<I, S>(tags: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut seen = std::collections::HashSet::new();
+    tags.into_iter()
+        .map(|t| t.as_ref().trim().to_string())
+        .filter(|t| !t.is_empty() && seen.insert(t.to_lowercase()))
+        .collect()
+}
+
+/// Whether `tag` looks like a BCP 47 language tag: a primary subtag of 2–8 letters, then
+/// `-`-separated subtags of 1–8 letters or digits ("fr", "pt-BR", "zh-Hant-TW"). Checks the shape
+/// only, not the subtag registry.
+pub fn is_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let primary = subtags.next().unwrap_or_default();
+    (2..=8).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_alphabetic())
+        && subtags
+            .all(|s| (1..=8).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+impl Book {
+    /// Stable content hash for duplicate detection across runs: 64-bit FNV-1a over title, author,
+    /// and chapter bodies in order (fields separated by a 0x1F byte), as 16 lowercase hex digits.
+    /// Not cryptographic; unchanged across rdrscrape versions for identical content.
+    pub fn content_hash(&self) -> String {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut feed = |bytes: &[u8]| {
+            for &b in bytes.iter().chain(std::iter::once(&0x1f)) {
+                hash ^= u64::from(b);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+        feed(self.title.as_bytes());
+        feed(self.author.as_bytes());
+        for ch in &self.chapters {
+            feed(ch.body.as_bytes());
+        }
+        format!("{:016x}", hash)
+    }
+
+    /// Post-scrape sanity check on chapter order: indices must be strictly increasing and titles
+    /// non-empty. Returns one message per problem; empty when the order looks right. Repeated
+    /// titles are reported separately by [repeated_titles](Book::repeated_titles).
+    pub fn verify_order(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, ch) in self.chapters.iter().enumerate() {
+            if ch.title.trim().is_empty() {
+                problems.push(format!("chapter {} has an empty title", ch.index));
+            }
+            let Some(prev) = i.checked_sub(1).map(|j| &self.chapters[j]) else {
+                continue;
+            };
+            if ch.index <= prev.index {
+                problems.push(format!(
+                    "chapter {} follows chapter {}; indices are not strictly increasing",
+                    ch.index, prev.index
+                ));
+            }
+        }
+        problems
+    }
+
+    /// Consecutive chapters with the same non-empty title. Often a bad TOC merge or sort, but
+    /// stories also repeat titles such as "Interlude" or "Bonus", so this is only ever a warning.
+    pub fn repeated_titles(&self) -> Vec<String> {
+        self.chapters
+            .windows(2)
+            .filter(|w| !w[1].title.trim().is_empty() && w[1].title.trim() == w[0].title.trim())
+            .map(|w| {
+                format!(
+                    "chapters {} and {} have the same title {:?}",
+                    w[0].index, w[1].index, w[1].title
+                )
+            })
+            .collect()
+    }
+
+    /// Split an anthology into one book per story: a chapter whose title matches `pattern`
+    /// starts a new book titled after it. Chapters before the first match keep the original
+    /// title as their own book. Author, contributors, description, cover, source URL, language,
+    /// series and tags are copied to every part; chapter indices are kept. Each part is numbered
+    /// in [part](Book::part) so it gets its own [identifier]. With no match the result is the
+    /// whole book.
+    pub fn split_on_title(&self, pattern: &Regex) -> Vec<Book> {
+        if !self.chapters.iter().any(|ch| pattern.is_match(&ch.title)) {
+            return vec![self.clone()];
+        }
+        let empty = Book {
+            title: self.title.clone(),
+            author: self.author.clone(),
+            description: self.description.clone(),
+            cover_url: self.cover_url.clone(),
+            source_url: self.source_url.clone(),
+            stats: self.stats.clone(),
+            language: self.language.clone(),
+            series: self.series.clone(),
+            series_index: self.series_index,
+            tags: self.tags.clone(),
+            contributors: self.contributors.clone(),
+            ..Default::default()
+        };
+        let mut parts: Vec<Book> = Vec::new();
+        for ch in &self.chapters {
+            let starts_story = pattern.is_match(&ch.title);
+            if starts_story || parts.is_empty() {
+                let mut part = empty.clone();
+                if starts_story {
+                    part.title = ch.title.trim().to_string();
+                }
+                parts.push(part);
+            }
+            if let Some(part) = parts.last_mut() {
+                part.chapters.push(ch.clone());
+            }
+        }
+        for (n, part) in (1..).zip(parts.iter_mut()) {
+            part.part = Some(n);
+        }
+        parts
+    }
+}
+
+/// Stable book id for EPUB `dc:identifier` (also the NCX uid and OPDS entry id) and the FB2
+/// `<id>`: the source URL, else a UUIDv5 of title and author as `urn:uuid:...`, so different
+/// books differ and re-runs keep the same id. A split part ([Book::part]) adds `#part-N` to the
+/// URL, or its number to the UUID name.
+pub(crate) fn identifier(book: &Book) -> String {
+    match (book.source_url.as_deref(), book.part) {
+        (Some(url), None) => url.to_string(),
+        (Some(url), Some(n)) => format!("{}#part-{}", url, n),
+        (None, part) => {
+            let part = part
+                .map(|n| format!("\u{1f}part-{}", n))
+                .unwrap_or_default();
+            let name = format!(
+                "urn:rdrscrape:book:{}\u{1f}{}{}",
+                book.title, book.author, part
+            );
+            Uuid::new_v5(&Uuid::NAMESPACE_URL, name.as_bytes())
+                .urn()
+                .to_string()
+        }
+    }
 }
 
 /// One chapter in TOC order.
@@ -31,11 +233,22 @@ pub struct Chapter {
     pub index: u32,
     /// Plain text or minimal HTML (e.g. `<p>...</p>` only).
     pub body: String,
+    /// Word count shown by the site for this chapter, when it publishes one (Scribble Hub).
+    #[serde(
+        rename = "reportedWordCount",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reported_word_count: Option<u32>,
+    /// Page the chapter was fetched from, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeSet;
     use std::error::Error;
 
     fn sample_book() -> Book {
@@ -49,11 +262,117 @@ mod tests {
                 index: 1,
                 body: "<p>The first paragraph of the chapter.</p><p>The second paragraph.</p>"
                     .to_string(),
+                reported_word_count: None,
+                url: None,
             }],
-            source_url: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn clean_tags_drops_blanks_and_repeats() {
+        assert_eq!(
+            clean_tags([" Fantasy ", "", "LitRPG", "fantasy", "  ", "Action"]),
+            vec!["Fantasy", "LitRPG", "Action"]
+        );
+    }
+
+    #[test]
+    fn is_language_tag_accepts_bcp47_shapes() {
+        for tag in [
+            "en",
+            "fr",
+            "fil",
+            "pt-BR",
+            "zh-Hant-TW",
+            "es-419",
+            "de-CH-1996",
+        ] {
+            assert!(is_language_tag(tag), "{}", tag);
+        }
+        for tag in [
+            "",
+            "e",
+            "en_US",
+            "english!",
+            "fr-",
+            "-fr",
+            "1a",
+            "en-toolongsubtag",
+        ] {
+            assert!(!is_language_tag(tag), "{}", tag);
         }
     }
 
+    #[test]
+    fn verify_order_reports_index_and_title_problems() {
+        let mut book = sample_book();
+        assert!(book.verify_order().is_empty());
+        let mut second = book.chapters[0].clone();
+        second.index = 1;
+        book.chapters.push(second);
+        book.chapters.push(Chapter {
+            title: " ".to_string(),
+            index: 3,
+            body: String::new(),
+            reported_word_count: None,
+            url: None,
+        });
+        let problems = book.verify_order();
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].contains("not strictly increasing"));
+        assert!(problems[1].contains("empty title"));
+        let repeated = book.repeated_titles();
+        assert_eq!(repeated.len(), 1, "{:?}", repeated);
+        assert!(repeated[0].contains("chapters 1 and 1 have the same title"));
+    }
+
+    #[test]
+    fn split_on_title_starts_a_book_per_match() {
+        let chapter = |index: u32, title: &str| Chapter {
+            title: title.to_string(),
+            index,
+            body: format!("<p>{}</p>", index),
+            reported_word_count: None,
+            url: None,
+        };
+        let mut book = sample_book();
+        book.source_url = Some("https://www.royalroad.com/fiction/21220".to_string());
+        book.chapters = vec![
+            chapter(1, "Foreword"),
+            chapter(2, "Story: The Well"),
+            chapter(3, "Part 2"),
+            chapter(4, " Story: Ashes "),
+        ];
+        let pattern = Regex::new("^\\s*Story:").unwrap();
+        let parts = book.split_on_title(&pattern);
+        let titles: Vec<&str> = parts.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec!["Mother of Learning", "Story: The Well", "Story: Ashes"]
+        );
+        let indices: Vec<Vec<u32>> = parts
+            .iter()
+            .map(|b| b.chapters.iter().map(|c| c.index).collect())
+            .collect();
+        assert_eq!(indices, vec![vec![1], vec![2, 3], vec![4]]);
+        assert!(parts.iter().all(|b| b.author == "nobody103"));
+        assert!(parts.iter().all(|b| b.source_url == book.source_url));
+        let numbers: Vec<Option<u32>> = parts.iter().map(|b| b.part).collect();
+        assert_eq!(numbers, vec![Some(1), Some(2), Some(3)]);
+        let ids: BTreeSet<String> = parts.iter().map(identifier).collect();
+        assert_eq!(ids.len(), parts.len());
+        assert_eq!(
+            identifier(&parts[1]),
+            "https://www.royalroad.com/fiction/21220#part-2"
+        );
+
+        let whole = book.split_on_title(&Regex::new("^Interlude").unwrap());
+        assert_eq!(whole.len(), 1);
+        assert_eq!(whole[0].chapters.len(), 4);
+        assert_eq!(whole[0].part, None);
+    }
+
     #[test]
     fn book_serializes_to_output_shape_json() -> Result<(), Box<dyn Error>> {
         let book = sample_book();
@@ -132,4 +451,54 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn stats_serialized_only_when_present() -> Result<(), Box<dyn Error>> {
+        let mut book = sample_book();
+        assert!(!serde_json::to_string(&book)?.contains("\"stats\""));
+        book.stats
+            .insert("followers".to_string(), "12,345".to_string());
+        let value = serde_json::to_value(&book)?;
+        assert_eq!(value["stats"]["followers"].as_str(), Some("12,345"));
+        let round_tripped: Book = serde_json::from_value(value)?;
+        assert_eq!(round_tripped.stats, book.stats);
+        Ok(())
+    }
+
+    #[test]
+    fn content_hash_is_stable_and_content_sensitive() {
+        let book = sample_book();
+        let hash = book.content_hash();
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, sample_book().content_hash());
+
+        let mut other = sample_book();
+        other.description = None;
+        other.cover_url = None;
+        assert_eq!(
+            other.content_hash(),
+            hash,
+            "metadata besides title/author is not hashed"
+        );
+
+        other.chapters[0].body.push_str("<p>More.</p>");
+        assert_ne!(other.content_hash(), hash);
+
+        let mut swapped = sample_book();
+        swapped.title = "nobody103".to_string();
+        swapped.author = "Mother of Learning".to_string();
+        assert_ne!(swapped.content_hash(), hash);
+    }
+
+    #[test]
+    fn content_hash_matches_fnv1a_of_empty_fields() {
+        let book = Book::default();
+        // FNV-1a 64 of two 0x1F separators.
+        let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+        for _ in 0..2 {
+            h ^= 0x1f;
+            h = h.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        assert_eq!(book.content_hash(), format!("{:016x}", h));
+    }
 }
@@ -0,0 +1,608 @@
+//! Archive of Our Own adapter. Fetches the whole work in one request (`?view_full_work=true`) and
+//! splits it into chapters; produces canonical Book.
+//!
+//! Metadata: preface (`h2.title`, `h3.byline`, summary) and the `dl.work.meta` block. Chapters:
+//! `#chapters > div.chapter`, or the lone `#chapters > div.userstuff` of a one-chapter work.
+
+use crate::formats::{normalize_body_html, BodyOptions};
+use crate::model::{clean_tags, is_language_tag, Book, Chapter};
+use crate::scraper::error::ScraperError;
+use crate::scraper::{
+    absolutize_image_srcs, chapter_or_missing, handle_missing_chapter, needs_fetch, record_chapter,
+    response_text, retain_wanted_chapters, site_for_host, stat_key, stop_early,
+    title_from_page_title, title_from_url_slug, transform_body, EmptyChapterBehavior, ExtraHosts,
+    MissingContent, PoliteClient, ScrapeOptions, Scraper, Site, TocEntry, UNKNOWN_AUTHOR,
+};
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::BTreeMap;
+
+/// Site suffixes stripped from `<title>`.
+const TITLE_SUFFIXES: &[&str] = &[" [Archive of Our Own]"];
+
+/// Query that asks for every chapter on one page and skips the adult-content interstitial.
+const FULL_WORK_QUERY: &str = "view_full_work=true&view_adult=true";
+
+/// Parse a CSS selector or return a parse error (avoids panics from Selector::parse).
+fn parse_selector(sel: &str) -> Result<Selector, ScraperError> {
+    Selector::parse(sel).map_err(|e| ScraperError::ParseStoryPage {
+        message: format!("invalid selector {:?}: {}", sel, e),
+    })
+}
+
+/// Archive of Our Own scraper. Holds a reference to the shared polite client.
+pub struct Ao3Scraper<'a> {
+    client: &'a mut PoliteClient,
+}
+
+/// One chapter cut from the full-work page. `body` is None when the chapter has no text block.
+#[derive(Debug)]
+struct WorkChapter {
+    index: u32,
+    url: String,
+    title: String,
+    body: Option<String>,
+}
+
+/// Extract work ID from URL path /works/{id}[/chapters/{id}]. Returns None if not found.
+fn extract_work_id_from_url(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let after_works = parsed.path().strip_prefix("/works/")?;
+    let id = after_works.split('/').next()?;
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(id.to_string())
+}
+
+/// Require a work URL on an AO3 host per [site_for_host], including config `extra_hosts`.
+/// A chapter URL resolves to its work. Returns `{scheme}://{host}/works/{id}` without query.
+fn ensure_work_url(url: &str, extra_hosts: Option<&ExtraHosts>) -> Result<String, ScraperError> {
+    let parsed = Url::parse(url).map_err(|e| ScraperError::InvalidUrl {
+        input: url.to_string(),
+        reason: e.to_string(),
+    })?;
+    let host = parsed.host_str().ok_or_else(|| ScraperError::InvalidUrl {
+        input: url.to_string(),
+        reason: "URL has no host".to_string(),
+    })?;
+    if site_for_host(host, extra_hosts) != Some(Site::Ao3) {
+        return Err(ScraperError::ParseStoryPage {
+            message: "Expected an Archive of Our Own work URL (host archiveofourown.org)."
+                .to_string(),
+        });
+    }
+    let id = extract_work_id_from_url(url).ok_or_else(|| ScraperError::ParseStoryPage {
+        message: "Expected a work URL containing /works/{id}, e.g. https://archiveofourown.org/works/2080878".to_string(),
+    })?;
+    let mut work_url = parsed;
+    work_url.set_path(&format!("/works/{}", id));
+    work_url.set_query(None);
+    work_url.set_fragment(None);
+    Ok(work_url.to_string())
+}
+
+/// Require a chapter URL (path /works/{id}/chapters/{id}). Used only with `--single-chapter`.
+fn ensure_chapter_url(url: &str) -> Result<String, ScraperError> {
+    let parsed = Url::parse(url).map_err(|e| ScraperError::InvalidUrl {
+        input: url.to_string(),
+        reason: e.to_string(),
+    })?;
+    let path = parsed.path();
+    if !(path.starts_with("/works/") && path.contains("/chapters/")) {
+        return Err(ScraperError::ParseStoryPage {
+            message: "Expected a chapter URL with --single-chapter, e.g. https://archiveofourown.org/works/2080878/chapters/4525509".to_string(),
+        });
+    }
+    Ok(url.to_string())
+}
+
+/// `url` with its query replaced by `query` (see [FULL_WORK_QUERY]).
+fn with_query(url: &str, query: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut u) => {
+            u.set_query(Some(query));
+            u.to_string()
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+/// Check response status and read body as text (see [response_text]). Returns body or ScraperError.
+fn check_response(
+    response: reqwest::blocking::Response,
+    url: &str,
+    context: Option<&str>,
+) -> Result<String, ScraperError> {
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ScraperError::HttpStatus {
+            status: status.as_u16(),
+            url: url.to_string(),
+            context: context.map(String::from),
+        });
+    }
+    response_text(response, url)
+}
+
+/// Whitespace-collapsed text of an element.
+fn element_text(el: ElementRef<'_>) -> String {
+    el.text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Extract metadata from the work preface: title (`h2.title`), authors (`a[rel=author]` in the
+/// byline, joined with ", "; the byline text for anonymous works) and the work summary as plain
+/// text with one blank line between paragraphs. A missing title is replaced by `url_title`
+/// (`--title-from-url`) when given. With `allow_missing_metadata`, a missing title falls back to
+/// `<title>` and a missing author to [UNKNOWN_AUTHOR].
+fn parse_metadata(
+    html: &str,
+    allow_missing_metadata: bool,
+    url_title: Option<String>,
+) -> Result<(String, String, Option<String>), ScraperError> {
+    let doc = Html::parse_document(html);
+    let title_sel = parse_selector("h2.title.heading")?;
+    let byline_sel = parse_selector("h3.byline.heading")?;
+    let author_sel = parse_selector("a[rel=\"author\"]")?;
+    let summary_sel = parse_selector("div.preface .summary blockquote.userstuff")?;
+    let paragraph_sel = parse_selector("p")?;
+
+    let title = doc
+        .select(&title_sel)
+        .next()
+        .map(element_text)
+        .filter(|s| !s.is_empty());
+    let author = doc.select(&byline_sel).next().and_then(|byline| {
+        let authors: Vec<String> = byline
+            .select(&author_sel)
+            .map(element_text)
+            .filter(|s| !s.is_empty())
+            .collect();
+        if authors.is_empty() {
+            Some(element_text(byline)).filter(|s| !s.is_empty())
+        } else {
+            Some(authors.join(", "))
+        }
+    });
+    let description = doc.select(&summary_sel).next().and_then(|summary| {
+        let paragraphs: Vec<String> = summary
+            .select(&paragraph_sel)
+            .map(element_text)
+            .filter(|s| !s.is_empty())
+            .collect();
+        let text = if paragraphs.is_empty() {
+            element_text(summary)
+        } else {
+            paragraphs.join("\n\n")
+        };
+        Some(text).filter(|s| !s.is_empty())
+    });
+
+    let title = title.or(url_title);
+    match (title, author) {
+        (Some(t), Some(a)) => Ok((t, a, description)),
+        (title, author) if allow_missing_metadata => {
+            let title = title
+                .or_else(|| title_from_page_title(&doc, TITLE_SUFFIXES))
+                .ok_or_else(|| ScraperError::ParseStoryPage {
+                    message: "missing title (no selector or <title> matched)".to_string(),
+                })?;
+            let author = author.unwrap_or_else(|| UNKNOWN_AUTHOR.to_string());
+            Ok((title, author, description))
+        }
+        _ => Err(ScraperError::ParseStoryPage {
+            message: "missing title or author (h2.title / h3.byline)".to_string(),
+        }),
+    }
+}
+
+/// Fandom and additional tags from `dl.work.meta`, cleaned with [clean_tags]. Warnings,
+/// relationships and characters are left out. Empty when the block is missing.
+fn parse_tags(html: &str) -> Result<Vec<String>, ScraperError> {
+    let doc = Html::parse_document(html);
+    let tag_sel = parse_selector("dl.work.meta dd.fandom a.tag, dl.work.meta dd.freeform a.tag")?;
+    Ok(clean_tags(
+        doc.select(&tag_sel).map(|a| a.text().collect::<String>()),
+    ))
+}
+
+/// Work language from the `lang` attribute of `dl.work.meta dd.language`, when it is a BCP 47 tag.
+fn parse_language(html: &str) -> Result<Option<String>, ScraperError> {
+    let doc = Html::parse_document(html);
+    let lang_sel = parse_selector("dl.work.meta dd.language")?;
+    Ok(doc
+        .select(&lang_sel)
+        .next()
+        .and_then(|dd| dd.value().attr("lang"))
+        .map(str::trim)
+        .filter(|tag| is_language_tag(tag))
+        .map(String::from))
+}
+
+/// Work stats from `dl.work.meta dl.stats` (published, words, chapters, kudos, hits, ...),
+/// keyed with [stat_key]. Empty when the block is missing.
+fn parse_stats(html: &str) -> Result<BTreeMap<String, String>, ScraperError> {
+    let doc = Html::parse_document(html);
+    let dt_sel = parse_selector("dl.work.meta dl.stats dt")?;
+    let dd_sel = parse_selector("dl.work.meta dl.stats dd")?;
+    Ok(doc
+        .select(&dt_sel)
+        .zip(doc.select(&dd_sel))
+        .map(|(dt, dd)| (stat_key(&element_text(dt)), element_text(dd)))
+        .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+        .collect())
+}
+
+/// Chapter body from a `div.userstuff` block: the inner HTML minus the hidden "Chapter Text"
+/// landmark heading, normalized per `tags`. None when nothing is left.
+fn chapter_body(
+    userstuff: ElementRef<'_>,
+    url: &str,
+    tags: &BodyOptions,
+) -> Result<Option<String>, ScraperError> {
+    let landmark_sel = parse_selector("h3.landmark")?;
+    let mut inner = userstuff.inner_html();
+    for heading in userstuff.select(&landmark_sel) {
+        inner = inner.replacen(&heading.html(), "", 1);
+    }
+    let body = normalize_body_html(&inner, tags);
+    if body.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(absolutize_image_srcs(&body, url)))
+}
+
+/// Split a work page into chapters, numbered from 1 in page order. Each `#chapters > div.chapter`
+/// takes its title from `h3.title` ("Chapter 1: Beginnings") and its URL from the title link; a
+/// one-chapter work has no chapter wrapper, so its lone `div.userstuff` becomes chapter 1 titled
+/// `work_title` at `work_url`.
+fn parse_work_chapters(
+    html: &str,
+    work_url: &str,
+    work_title: &str,
+    tags: &BodyOptions,
+) -> Result<Vec<WorkChapter>, ScraperError> {
+    let doc = Html::parse_document(html);
+    let chapter_sel = parse_selector("#chapters > div.chapter")?;
+    let title_sel = parse_selector("h3.title")?;
+    let link_sel = parse_selector("h3.title a")?;
+    let body_sel = parse_selector("div.userstuff.module")?;
+    let lone_body_sel = parse_selector("#chapters > div.userstuff")?;
+    let base = Url::parse(work_url).map_err(|e| ScraperError::InvalidUrl {
+        input: work_url.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    let mut chapters = Vec::new();
+    for (pos, chapter) in doc.select(&chapter_sel).enumerate() {
+        let index = pos as u32 + 1;
+        let url = chapter
+            .select(&link_sel)
+            .next()
+            .and_then(|a| a.value().attr("href"))
+            .and_then(|href| base.join(href).ok())
+            .map(String::from)
+            .unwrap_or_else(|| format!("{}#chapter-{}", work_url, index));
+        let title = chapter
+            .select(&title_sel)
+            .next()
+            .map(element_text)
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| format!("Chapter {}", index));
+        let body = match chapter.select(&body_sel).next() {
+            Some(userstuff) => chapter_body(userstuff, &url, tags)?,
+            None => None,
+        };
+        chapters.push(WorkChapter {
+            index,
+            url,
+            title,
+            body,
+        });
+    }
+
+    if chapters.is_empty() {
+        if let Some(userstuff) = doc.select(&lone_body_sel).next() {
+            chapters.push(WorkChapter {
+                index: 1,
+                url: work_url.to_string(),
+                title: work_title.to_string(),
+                body: chapter_body(userstuff, work_url, tags)?,
+            });
+        }
+    }
+    Ok(chapters)
+}
+
+impl<'a> Ao3Scraper<'a> {
+    pub fn new(client: &'a mut PoliteClient) -> Self {
+        Self { client }
+    }
+
+    /// `--single-chapter`: fetch one chapter page and wrap it in a one-chapter book. Chapter
+    /// pages carry the work preface, so title and author come from it as for a full scrape.
+    fn scrape_single_chapter(
+        &mut self,
+        url: &str,
+        options: &ScrapeOptions<'_>,
+    ) -> Result<Book, ScraperError> {
+        let chapter_url = ensure_chapter_url(url)?;
+        let page_url = with_query(&chapter_url, "view_adult=true");
+        let response = self
+            .client
+            .get_with_retry(&page_url)
+            .map_err(|e| ScraperError::from_request(page_url.clone(), e))?;
+        let html = check_response(response, &page_url, Some("chapter page"))?;
+        let (title, author, _) = parse_metadata(&html, true, None)?;
+        let chapter = parse_work_chapters(&html, &chapter_url, &title, &options.allowed_tags)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ScraperError::ParseChapter {
+                index: 1,
+                url: chapter_url.clone(),
+            })?;
+        let body = chapter.body.ok_or_else(|| ScraperError::ParseChapter {
+            index: 1,
+            url: chapter_url.clone(),
+        })?;
+        Ok(Book {
+            title,
+            author,
+            chapters: vec![Chapter {
+                title: chapter.title,
+                index: 1,
+                body: transform_body(body, options),
+                reported_word_count: None,
+                url: Some(chapter_url.clone()),
+            }],
+            source_url: Some(chapter_url),
+            ..Default::default()
+        })
+    }
+}
+
+impl Scraper for Ao3Scraper<'_> {
+    fn scrape_book(
+        &mut self,
+        url: &str,
+        options: &ScrapeOptions<'_>,
+    ) -> Result<Book, ScraperError> {
+        if options.single_chapter {
+            return self.scrape_single_chapter(url, options);
+        }
+        let work_url = ensure_work_url(url, options.extra_hosts)?;
+
+        // Every chapter is on the full-work page, so it is fetched even with a cached TOC.
+        let page_url = with_query(&work_url, FULL_WORK_QUERY);
+        let response = self
+            .client
+            .get_with_retry(&page_url)
+            .map_err(|e| ScraperError::from_request(page_url.clone(), e))?;
+        let html = check_response(response, &page_url, Some("story page"))?;
+
+        let mut book: Book = if let Some(init) = options.initial_book {
+            init.clone()
+        } else {
+            let (title, author, description) = parse_metadata(
+                &html,
+                options.allow_missing_metadata,
+                options
+                    .title_from_url
+                    .then(|| title_from_url_slug(&work_url))
+                    .flatten(),
+            )?;
+            Book {
+                title,
+                author,
+                description,
+                source_url: Some(work_url.clone()),
+                language: parse_language(&html)?,
+                ..Default::default()
+            }
+        };
+        if options.stats {
+            book.stats = parse_stats(&html)?;
+        }
+        if book.tags.is_empty() {
+            book.tags = parse_tags(&html)?;
+        }
+
+        let mut chapters =
+            parse_work_chapters(&html, &work_url, &book.title, &options.allowed_tags)?;
+        if chapters.is_empty() {
+            return Err(ScraperError::EmptyChapterList);
+        }
+        if let Some(cb) = options.on_toc {
+            let entries: Vec<TocEntry> = chapters
+                .iter()
+                .map(|c| TocEntry {
+                    index: c.index,
+                    url: c.url.clone(),
+                    title: c.title.clone(),
+                    unlocked: true,
+                })
+                .collect();
+            cb(&entries);
+        }
+        let total = chapters.len() as u32;
+        retain_wanted_chapters(&mut chapters, options, |c| {
+            (c.index, c.url.as_str(), c.title.as_str())
+        });
+
+        if options.toc_only {
+            for c in chapters {
+                if !needs_fetch(&mut book, c.index, &c.url, options.index_collision)? {
+                    continue;
+                }
+                book.chapters.push(Chapter {
+                    title: c.title,
+                    index: c.index,
+                    body: String::new(),
+                    reported_word_count: None,
+                    url: Some(c.url),
+                });
+            }
+            book.chapters.sort_by_key(|c| c.index);
+            return Ok(book);
+        }
+
+        let mut done = 0u32;
+        let empty_behavior = options
+            .empty_chapter_behavior
+            .unwrap_or(EmptyChapterBehavior::Skip);
+        for entry in chapters {
+            if !needs_fetch(&mut book, entry.index, &entry.url, options.index_collision)
+                .map_err(|e| stop_early(&book, options, e))?
+            {
+                continue;
+            }
+            if options.cancel_check.map(|c| c()).unwrap_or(false) {
+                return Err(stop_early(&book, options, ScraperError::Cancelled));
+            }
+            let parsed = match entry.body {
+                Some(body) => chapter_or_missing(entry.title, entry.index, body, options),
+                None => Err(MissingContent::Unparseable),
+            };
+            let chapter = match parsed {
+                Ok(ch) => Some(ch),
+                Err(missing) => {
+                    handle_missing_chapter(empty_behavior, missing, entry.index, &entry.url)
+                        .map_err(|e| stop_early(&book, options, e))?
+                }
+            };
+            if let Some(ch) = chapter {
+                record_chapter(&mut book, ch, &entry.url, &mut done, total, options);
+            }
+        }
+
+        if book.chapters.is_empty() {
+            return Err(ScraperError::NoChaptersRetrieved);
+        }
+
+        Ok(book)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORK_HTML: &str = r##"<html><head><title>Inline Work - Inline Author - Fandom [Archive of Our Own]</title></head><body>
+<div class="wrapper"><dl class="work meta group">
+<dt class="fandom tags">Fandom:</dt><dd class="fandom tags"><ul><li><a class="tag" href="/tags/F">Some Fandom</a></li></ul></dd>
+<dt class="freeform tags">Additional Tags:</dt><dd class="freeform tags"><ul><li><a class="tag" href="/tags/A">Slow Burn</a></li><li><a class="tag" href="/tags/B">slow burn</a></li></ul></dd>
+<dt class="language">Language:</dt><dd class="language" lang="en">English</dd>
+<dt class="stats">Stats:</dt><dd class="stats"><dl class="stats"><dt class="published">Published:</dt><dd class="published">2020-01-02</dd><dt class="words">Words:</dt><dd class="words">1,234</dd><dt class="kudos">Kudos:</dt><dd class="kudos">56</dd></dl></dd>
+</dl></div>
+<div id="workskin"><div class="preface group">
+<h2 class="title heading">Inline Work</h2>
+<h3 class="byline heading"><a rel="author" href="/users/a/pseuds/a">Inline Author</a>, <a rel="author" href="/users/b/pseuds/b">Second</a></h3>
+<div class="summary module"><h3 class="heading">Summary:</h3><blockquote class="userstuff"><p>First line.</p><p>Second line.</p></blockquote></div>
+</div>
+<div id="chapters" role="article">
+<div class="chapter" id="chapter-1">
+<div class="chapter preface group" role="complementary"><h3 class="title"><a href="/works/123/chapters/456">Chapter 1</a>: Beginnings</h3></div>
+<div class="userstuff module" role="article"><h3 class="landmark heading" id="work">Chapter Text</h3><p>One.</p><p>Two <em>three</em>.</p></div>
+</div>
+<div class="chapter" id="chapter-2">
+<div class="chapter preface group" role="complementary"><h3 class="title"><a href="/works/123/chapters/789">Chapter 2</a></h3></div>
+<div class="userstuff module" role="article"><h3 class="landmark heading" id="work">Chapter Text</h3><p>Four.</p></div>
+</div>
+</div></div></body></html>"##;
+
+    #[test]
+    fn inline_parse_metadata() -> Result<(), ScraperError> {
+        let (title, author, description) = parse_metadata(WORK_HTML, false, None)?;
+        assert_eq!(title, "Inline Work");
+        assert_eq!(author, "Inline Author, Second");
+        assert_eq!(description.as_deref(), Some("First line.\n\nSecond line."));
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_metadata_anonymous_byline() -> Result<(), ScraperError> {
+        let html =
+            r#"<h2 class="title heading">Anon Work</h2><h3 class="byline heading">Anonymous</h3>"#;
+        let (title, author, description) = parse_metadata(html, false, None)?;
+        assert_eq!(title, "Anon Work");
+        assert_eq!(author, "Anonymous");
+        assert!(description.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_work_meta() -> Result<(), ScraperError> {
+        assert_eq!(parse_tags(WORK_HTML)?, vec!["Some Fandom", "Slow Burn"]);
+        assert_eq!(parse_language(WORK_HTML)?.as_deref(), Some("en"));
+        let stats = parse_stats(WORK_HTML)?;
+        assert_eq!(
+            stats.get("published").map(String::as_str),
+            Some("2020-01-02")
+        );
+        assert_eq!(stats.get("words").map(String::as_str), Some("1,234"));
+        assert_eq!(stats.get("kudos").map(String::as_str), Some("56"));
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_work_chapters() -> Result<(), ScraperError> {
+        let work_url = "https://archiveofourown.org/works/123";
+        let chapters =
+            parse_work_chapters(WORK_HTML, work_url, "Inline Work", &BodyOptions::default())?;
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].index, 1);
+        assert_eq!(chapters[0].title, "Chapter 1: Beginnings");
+        assert_eq!(
+            chapters[0].url,
+            "https://archiveofourown.org/works/123/chapters/456"
+        );
+        let body = chapters[0].body.as_deref().unwrap_or_default();
+        assert!(body.contains("One."), "{}", body);
+        assert!(body.contains("<em>three</em>"), "{}", body);
+        assert!(!body.contains("Chapter Text"), "{}", body);
+        assert_eq!(chapters[1].index, 2);
+        assert_eq!(chapters[1].title, "Chapter 2");
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_oneshot_work() -> Result<(), ScraperError> {
+        let html = r#"<div id="chapters" role="article"><div class="userstuff"><h3 class="landmark heading">Work Text:</h3><p>Only chapter.</p></div></div>"#;
+        let work_url = "https://archiveofourown.org/works/9";
+        let chapters = parse_work_chapters(html, work_url, "Oneshot", &BodyOptions::default())?;
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].title, "Oneshot");
+        assert_eq!(chapters[0].url, work_url);
+        assert!(chapters[0]
+            .body
+            .as_deref()
+            .is_some_and(|b| b.contains("Only chapter.") && !b.contains("Work Text")));
+        Ok(())
+    }
+
+    #[test]
+    fn ensure_work_url_strips_chapter_and_query() -> Result<(), ScraperError> {
+        assert_eq!(
+            ensure_work_url(
+                "https://archiveofourown.org/works/123/chapters/456?view_adult=true#main",
+                None
+            )?,
+            "https://archiveofourown.org/works/123"
+        );
+        assert!(ensure_work_url("https://archiveofourown.org/tags/Fluff/works", None).is_err());
+        assert!(ensure_work_url("https://www.royalroad.com/works/123", None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn full_work_query_is_appended() {
+        assert_eq!(
+            with_query("https://archiveofourown.org/works/123", FULL_WORK_QUERY),
+            "https://archiveofourown.org/works/123?view_full_work=true&view_adult=true"
+        );
+    }
+}
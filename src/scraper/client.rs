@@ -1,5 +1,7 @@
 //! Blocking HTTP client with configurable politeness (delay between requests) and optional retries.
 
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 const DEFAULT_USER_AGENT: &str =
@@ -12,17 +14,47 @@ const MAX_REDIRECTS: usize = 10;
 const DEFAULT_RETRY_COUNT: u32 = 5;
 /// Default backoff delays in seconds after each failed attempt (1s, 2s, 4s, 8s).
 const DEFAULT_BACKOFF_SECS: [u64; 4] = [1, 2, 4, 8];
-/// Backoff for HTTP 429 (rate limit): wait longer so the server can recover.
+/// Default backoff for HTTP 429 (rate limit): wait longer so the server can recover.
 const BACKOFF_429_SECS: [u64; 4] = [30, 60, 90, 120];
 
 /// Blocking HTTP client that enforces a delay between requests.
 #[derive(Debug)]
 pub struct PoliteClient {
     inner: reqwest::blocking::Client,
+    user_agent: String,
     delay: Duration,
     last_request: Option<Instant>,
     retry_count: u32,
     backoff_secs: Vec<u64>,
+    max_total_backoff_secs: Option<u64>,
+    rate_limit_backoff_secs: Vec<u64>,
+    rate_limited_responses: AtomicU32,
+}
+
+/// A page fetched by [PoliteClient::get_pages]. The body is read in the worker thread so no
+/// connection stays open while the page waits to be parsed.
+#[derive(Debug)]
+pub(crate) struct FetchedPage {
+    pub(crate) status: reqwest::StatusCode,
+    pub(crate) content_type: Option<String>,
+    pub(crate) body: Result<Vec<u8>, reqwest::Error>,
+}
+
+impl FetchedPage {
+    fn read(response: reqwest::blocking::Response) -> FetchedPage {
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let body = response.bytes().map(|b| b.to_vec());
+        FetchedPage {
+            status,
+            content_type,
+            body,
+        }
+    }
 }
 
 impl PoliteClient {
@@ -36,10 +68,27 @@ impl PoliteClient {
         PoliteClientBuilder::default()
     }
 
+    /// User-Agent sent with every request (default or configured, plus any suffix).
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    /// HTTP 429 and 503 responses received so far, counting every attempt (including retried ones).
+    pub fn rate_limited_responses(&self) -> u32 {
+        self.rate_limited_responses.load(Ordering::Relaxed)
+    }
+
+    /// When the last request completed, if any has.
+    #[cfg(test)]
+    pub(crate) fn last_request_at(&self) -> Option<Instant> {
+        self.last_request
+    }
+
     /// Perform a GET request. Sleeps until the configured delay has passed since the last request.
     pub fn get(&mut self, url: &str) -> Result<reqwest::blocking::Response, reqwest::Error> {
         self.wait_delay();
         let response = self.inner.get(url).send()?;
+        self.note_status(response.status());
         self.last_request = Some(Instant::now());
         Ok(response)
     }
@@ -52,6 +101,7 @@ impl PoliteClient {
     ) -> Result<reqwest::blocking::Response, reqwest::Error> {
         self.wait_delay();
         let response = self.inner.post(url).form(form).send()?;
+        self.note_status(response.status());
         self.last_request = Some(Instant::now());
         Ok(response)
     }
@@ -60,50 +110,155 @@ impl PoliteClient {
     ///
     /// Retries on: timeout, connection errors, HTTP 5xx, and HTTP 429. Attempt count
     /// and backoff delays are configurable via the builder. Non-retryable errors
-    /// (e.g. 4xx except 429) are returned immediately. On success or after exhausting
-    /// retries, updates the last-request time for politeness.
+    /// (e.g. 4xx except 429) are returned immediately. If a max total backoff is set,
+    /// retrying stops once the next sleep would push cumulative backoff past it, and the
+    /// last response or error is returned as if it were the final attempt. On success or
+    /// after exhausting retries, updates the last-request time for politeness.
     pub fn get_with_retry(
         &mut self,
         url: &str,
+    ) -> Result<reqwest::blocking::Response, reqwest::Error> {
+        self.retrying_get(url, None, None)
+    }
+
+    /// [get_with_retry](Self::get_with_retry) with `timeout` in place of the client-wide timeout
+    /// for each attempt (connect through reading the body).
+    pub fn get_with_retry_timeout(
+        &mut self,
+        url: &str,
+        timeout: Duration,
+    ) -> Result<reqwest::blocking::Response, reqwest::Error> {
+        self.retrying_get(url, Some(timeout), None)
+    }
+
+    /// GET for an asset such as a cover image: retries like [get_with_retry](Self::get_with_retry)
+    /// with `timeout` per attempt, and waits `delay` (None: the configured delay) since the last
+    /// request instead of the page delay.
+    pub fn get_asset(
+        &mut self,
+        url: &str,
+        timeout: Duration,
+        delay: Option<Duration>,
+    ) -> Result<reqwest::blocking::Response, reqwest::Error> {
+        self.retrying_get(url, Some(timeout), delay)
+    }
+
+    /// Fetch `urls` on up to `workers` threads that take the next URL from a shared counter, each
+    /// with [get_with_retry](Self::get_with_retry)'s retries. Request starts stay at least the
+    /// configured delay apart across all threads, so the site sees no more requests than from a
+    /// serial run; only the waiting for responses overlaps. A retry backoff (HTTP 429, 5xx or a
+    /// timeout) holds off every thread, not just the one that hit it. Results are in `urls` order;
+    /// a URL whose worker never stored a result (it panicked) is None.
+    pub(crate) fn get_pages(
+        &mut self,
+        urls: &[String],
+        workers: usize,
+    ) -> Vec<Option<Result<FetchedPage, reqwest::Error>>> {
+        let next = AtomicUsize::new(0);
+        let last_start = Mutex::new(self.last_request);
+        let results: Mutex<Vec<Option<Result<FetchedPage, reqwest::Error>>>> =
+            Mutex::new(urls.iter().map(|_| None).collect());
+        // Claims the next free slot; re-checks after sleeping in case a backoff moved it.
+        let wait_turn = || loop {
+            let mut last = last_start.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            match last.map(|t| t + self.delay) {
+                Some(ready) if ready > now => {
+                    drop(last);
+                    std::thread::sleep(ready - now);
+                }
+                _ => {
+                    *last = Some(now);
+                    return;
+                }
+            }
+        };
+        // Moves the shared slot past the backoff; the backing-off thread then waits in wait_turn.
+        let back_off = |backoff: Duration| {
+            let mut last = last_start.lock().unwrap_or_else(|e| e.into_inner());
+            let until = Instant::now() + backoff;
+            *last = Some(last.map_or(until, |t| t.max(until)));
+        };
+        let client = &*self;
+        std::thread::scope(|scope| {
+            for _ in 0..workers.clamp(1, urls.len().max(1)) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(url) = urls.get(i) else {
+                        break;
+                    };
+                    let page = client
+                        .send_with_retries(url, None, &wait_turn, &back_off)
+                        .map(FetchedPage::read);
+                    results.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(page);
+                });
+            }
+        });
+        self.last_request = Some(Instant::now());
+        results.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn retrying_get(
+        &mut self,
+        url: &str,
+        timeout: Option<Duration>,
+        delay: Option<Duration>,
+    ) -> Result<reqwest::blocking::Response, reqwest::Error> {
+        let delay = delay.unwrap_or(self.delay);
+        let last_request = self.last_request;
+        let wait = || wait_since(last_request, delay);
+        let result = self.send_with_retries(url, timeout, &wait, &std::thread::sleep);
+        if result.is_ok() {
+            self.last_request = Some(Instant::now());
+        }
+        result
+    }
+
+    /// The retry loop behind [retrying_get](Self::retrying_get), calling `wait` before each attempt
+    /// and `back_off` with the backoff before each retry.
+    fn send_with_retries(
+        &self,
+        url: &str,
+        timeout: Option<Duration>,
+        wait: &dyn Fn(),
+        back_off: &dyn Fn(Duration),
     ) -> Result<reqwest::blocking::Response, reqwest::Error> {
         let max_attempts = self.retry_count;
         let mut last_err: Option<reqwest::Error> = None;
+        let mut total_backoff_secs: u64 = 0;
         for attempt in 0..max_attempts {
-            self.wait_delay();
-            match self.inner.get(url).send() {
+            wait();
+            let is_last_attempt = attempt + 1 >= max_attempts;
+            let mut request = self.inner.get(url);
+            if let Some(timeout) = timeout {
+                request = request.timeout(timeout);
+            }
+            match request.send() {
                 Ok(response) => {
                     let status = response.status();
+                    self.note_status(status);
                     let retryable_status = status.is_server_error() || status.as_u16() == 429;
-                    if retryable_status && attempt < max_attempts - 1 {
-                        last_err = Some(response.error_for_status().unwrap_err());
-                        let backoff = if status.as_u16() == 429 {
-                            BACKOFF_429_SECS
-                                .get(attempt as usize)
-                                .copied()
-                                .unwrap_or(*BACKOFF_429_SECS.last().unwrap_or(&60))
-                        } else {
-                            self.backoff_secs
-                                .get(attempt as usize)
-                                .copied()
-                                .unwrap_or_else(|| *self.backoff_secs.last().unwrap_or(&1))
-                        };
-                        std::thread::sleep(Duration::from_secs(backoff));
-                        continue;
+                    if retryable_status && !is_last_attempt {
+                        let backoff = self.backoff_for(attempt, status.as_u16() == 429);
+                        if backoff_fits(total_backoff_secs, backoff, self.max_total_backoff_secs) {
+                            total_backoff_secs += backoff;
+                            last_err = Some(response.error_for_status().unwrap_err());
+                            back_off(Duration::from_secs(backoff));
+                            continue;
+                        }
                     }
-                    self.last_request = Some(Instant::now());
                     return Ok(response);
                 }
                 Err(e) => {
                     let retryable = e.is_timeout() || e.is_connect();
-                    if retryable && attempt < max_attempts - 1 {
-                        last_err = Some(e);
-                        let backoff = self
-                            .backoff_secs
-                            .get(attempt as usize)
-                            .copied()
-                            .unwrap_or_else(|| *self.backoff_secs.last().unwrap_or(&1));
-                        std::thread::sleep(Duration::from_secs(backoff));
-                        continue;
+                    if retryable && !is_last_attempt {
+                        let backoff = self.backoff_for(attempt, false);
+                        if backoff_fits(total_backoff_secs, backoff, self.max_total_backoff_secs) {
+                            total_backoff_secs += backoff;
+                            last_err = Some(e);
+                            back_off(Duration::from_secs(backoff));
+                            continue;
+                        }
                     }
                     return Err(e);
                 }
@@ -112,34 +267,91 @@ impl PoliteClient {
         Err(last_err.unwrap_or_else(|| self.inner.get("http://[::1]:0/").send().unwrap_err()))
     }
 
-    fn wait_delay(&mut self) {
-        if let Some(last) = self.last_request {
-            let elapsed = last.elapsed();
-            if elapsed < self.delay {
-                std::thread::sleep(self.delay - elapsed);
-            }
+    /// Backoff in seconds after failed `attempt` (0-based). HTTP 429 uses the longer rate-limit schedule.
+    fn backoff_for(&self, attempt: u32, rate_limited: bool) -> u64 {
+        if rate_limited {
+            self.rate_limit_backoff_secs
+                .get(attempt as usize)
+                .copied()
+                .unwrap_or(*self.rate_limit_backoff_secs.last().unwrap_or(&60))
+        } else {
+            self.backoff_secs
+                .get(attempt as usize)
+                .copied()
+                .unwrap_or_else(|| *self.backoff_secs.last().unwrap_or(&1))
+        }
+    }
+
+    /// Count rate-limit responses (429 Too Many Requests, 503 Service Unavailable).
+    fn note_status(&self, status: reqwest::StatusCode) {
+        if matches!(status.as_u16(), 429 | 503) {
+            self.rate_limited_responses.fetch_add(1, Ordering::Relaxed);
         }
     }
+
+    fn wait_delay(&self) {
+        self.wait_delay_for(self.delay);
+    }
+
+    fn wait_delay_for(&self, delay: Duration) {
+        wait_since(self.last_request, delay);
+    }
+}
+
+/// Sleep until `delay` has passed since `last` (no wait when there was no request yet).
+fn wait_since(last: Option<Instant>, delay: Duration) {
+    if let Some(last) = last {
+        let elapsed = last.elapsed();
+        if elapsed < delay {
+            std::thread::sleep(delay - elapsed);
+        }
+    }
+}
+
+/// Whether sleeping `next` more seconds keeps cumulative backoff within `cap` (no cap: always).
+fn backoff_fits(total: u64, next: u64, cap: Option<u64>) -> bool {
+    match cap {
+        Some(cap) => total.saturating_add(next) <= cap,
+        None => true,
+    }
+}
+
+/// Resolved User-Agent: `base`, plus ` {suffix}` when a non-blank suffix is given.
+fn with_user_agent_suffix(base: String, suffix: Option<&str>) -> String {
+    match suffix.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(suffix) => format!("{} {}", base, suffix),
+        None => base,
+    }
 }
 
 /// Builder for PoliteClient with optional User-Agent, delay, timeout, and retry settings.
 #[derive(Debug)]
 pub struct PoliteClientBuilder {
     user_agent: Option<String>,
+    user_agent_suffix: Option<String>,
     delay_secs: u64,
     timeout_secs: u64,
     retry_count: u32,
     retry_backoff_secs: Vec<u64>,
+    max_total_backoff_secs: Option<u64>,
+    rate_limit_backoff_secs: Vec<u64>,
+    root_certificates_pem: Vec<Vec<u8>>,
+    danger_accept_invalid_certs: bool,
 }
 
 impl Default for PoliteClientBuilder {
     fn default() -> Self {
         Self {
             user_agent: None,
+            user_agent_suffix: None,
             delay_secs: DEFAULT_DELAY_SECS,
             timeout_secs: DEFAULT_TIMEOUT_SECS,
             retry_count: DEFAULT_RETRY_COUNT,
             retry_backoff_secs: DEFAULT_BACKOFF_SECS.to_vec(),
+            max_total_backoff_secs: None,
+            rate_limit_backoff_secs: BACKOFF_429_SECS.to_vec(),
+            root_certificates_pem: Vec::new(),
+            danger_accept_invalid_certs: false,
         }
     }
 }
@@ -151,6 +363,13 @@ impl PoliteClientBuilder {
         self
     }
 
+    /// Append text to the User-Agent in effect (custom or default), separated by a space,
+    /// e.g. "(+contact@example.com)". Empty or whitespace-only suffixes are ignored.
+    pub fn user_agent_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.user_agent_suffix = Some(suffix.into());
+        self
+    }
+
     /// Set delay between requests in seconds. Default 2.
     pub fn delay_secs(mut self, secs: u64) -> Self {
         self.delay_secs = secs;
@@ -175,17 +394,49 @@ impl PoliteClientBuilder {
         self
     }
 
+    /// Cap cumulative retry backoff per request in seconds. Retrying stops when the next
+    /// sleep would exceed it. Default: no cap.
+    pub fn max_total_backoff_secs(mut self, secs: u64) -> Self {
+        self.max_total_backoff_secs = Some(secs);
+        self
+    }
+
+    /// Set backoff delays in seconds before each retry after HTTP 429 (default [30, 60, 90, 120]). If shorter than retry_count - 1, last value is reused.
+    pub fn rate_limit_backoff_secs(mut self, secs: Vec<u64>) -> Self {
+        self.rate_limit_backoff_secs = secs;
+        self
+    }
+
+    /// Trust an extra root certificate (PEM), e.g. a corporate proxy CA, in addition to the
+    /// built-in roots. An unparseable certificate fails [build](Self::build).
+    pub fn add_root_certificate_pem(mut self, pem: Vec<u8>) -> Self {
+        self.root_certificates_pem.push(pem);
+        self
+    }
+
+    /// Accept any TLS certificate, including self-signed and expired ones. Dangerous: disables
+    /// protection against interception. Default false.
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.danger_accept_invalid_certs = accept;
+        self
+    }
+
     /// Build the blocking client and polite wrapper.
     pub fn build(self) -> Result<PoliteClient, reqwest::Error> {
-        let user_agent = self
+        let base_user_agent = self
             .user_agent
             .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string());
-        let inner = reqwest::blocking::Client::builder()
+        let user_agent = with_user_agent_suffix(base_user_agent, self.user_agent_suffix.as_deref());
+        let mut inner = reqwest::blocking::Client::builder()
             .cookie_store(true)
-            .user_agent(user_agent)
+            .user_agent(user_agent.as_str())
             .timeout(Duration::from_secs(self.timeout_secs))
             .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
-            .build()?;
+            .danger_accept_invalid_certs(self.danger_accept_invalid_certs);
+        for pem in &self.root_certificates_pem {
+            inner = inner.add_root_certificate(reqwest::Certificate::from_pem(pem)?);
+        }
+        let inner = inner.build()?;
         let backoff_secs = if self.retry_backoff_secs.is_empty() {
             // Default exponential: 1, 2, 4, ... for (retry_count - 1) steps
             let n = self.retry_count.saturating_sub(1) as usize;
@@ -195,10 +446,157 @@ impl PoliteClientBuilder {
         };
         Ok(PoliteClient {
             inner,
+            user_agent,
             delay: Duration::from_secs(self.delay_secs),
             last_request: None,
             retry_count: self.retry_count,
             backoff_secs,
+            max_total_backoff_secs: self.max_total_backoff_secs,
+            rate_limit_backoff_secs: self.rate_limit_backoff_secs,
+            rate_limited_responses: AtomicU32::new(0),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_agent_suffix_appends_to_base() {
+        assert_eq!(
+            with_user_agent_suffix("Base/1.0".to_string(), Some(" (+me@example.com) ")),
+            "Base/1.0 (+me@example.com)"
+        );
+        assert_eq!(
+            with_user_agent_suffix("Base/1.0".to_string(), Some("  ")),
+            "Base/1.0"
+        );
+        assert_eq!(
+            with_user_agent_suffix("Base/1.0".to_string(), None),
+            "Base/1.0"
+        );
+    }
+
+    #[test]
+    fn note_status_counts_429_and_503_only() {
+        let client = PoliteClient::new().unwrap();
+        for code in [200, 404, 429, 500, 503, 429] {
+            client.note_status(reqwest::StatusCode::from_u16(code).unwrap());
+        }
+        assert_eq!(client.rate_limited_responses(), 3);
+    }
+
+    #[test]
+    fn backoff_fits_without_cap() {
+        assert!(backoff_fits(u64::MAX, 120, None));
+    }
+
+    #[test]
+    fn backoff_fits_respects_cap() {
+        assert!(backoff_fits(0, 30, Some(30)));
+        assert!(backoff_fits(30, 60, Some(90)));
+        assert!(!backoff_fits(30, 61, Some(90)));
+        assert!(!backoff_fits(0, 1, Some(0)));
+    }
+
+    #[test]
+    fn per_request_timeout_overrides_client_timeout() {
+        // Accepts the connection but never answers, so only the timeout ends the request.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}/cover.jpg", listener.local_addr().expect("addr"));
+        let mut client = PoliteClient::builder()
+            .timeout_secs(60)
+            .retry_count(1)
+            .build()
+            .expect("client");
+        let started = Instant::now();
+        let err = client
+            .get_with_retry_timeout(&url, Duration::from_millis(200))
+            .unwrap_err();
+        assert!(err.is_timeout());
+        assert!(started.elapsed() < Duration::from_secs(30));
+        drop(listener);
+    }
+
+    #[test]
+    fn rate_limit_backoff_holds_off_other_workers() {
+        // Answers the first request with 429 and the rest with 200, recording when each arrived.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let base = format!("http://{}", listener.local_addr().expect("addr"));
+        let urls: Vec<String> = (0..6).map(|i| format!("{}/{}", base, i)).collect();
+        let server = std::thread::spawn(move || {
+            let mut arrivals = Vec::new();
+            // Six pages plus the retry of the rate-limited one.
+            for stream in listener.incoming().take(7) {
+                let mut stream = stream.expect("accept");
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = std::io::Read::read(&mut stream, &mut buf).expect("read");
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                arrivals.push(Instant::now());
+                let status = if arrivals.len() == 1 {
+                    "429 Too Many Requests"
+                } else {
+                    "200 OK"
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                    status
+                );
+                std::io::Write::write_all(&mut stream, response.as_bytes()).expect("write");
+            }
+            arrivals
+        });
+        let mut client = PoliteClient::builder()
+            .retry_count(2)
+            .rate_limit_backoff_secs(vec![1])
+            .build()
+            .expect("client");
+        client.delay = Duration::from_millis(200);
+        let pages = client.get_pages(&urls, 3);
+        assert!(pages
+            .iter()
+            .all(|p| matches!(p, Some(Ok(p)) if p.status.is_success())));
+        let arrivals = server.join().expect("server");
+        // Only the request that already held the next slot may start during the backoff.
+        let during_backoff = arrivals[1..]
+            .iter()
+            .filter(|t| t.duration_since(arrivals[0]) < Duration::from_millis(900))
+            .count();
+        assert!(
+            during_backoff <= 1,
+            "{} requests during the backoff",
+            during_backoff
+        );
+    }
+
+    #[test]
+    fn asset_delay_overrides_page_delay() {
+        let server = crate::scraper::mock_server::MockServer::start();
+        server.route("/page", &[(200, "page")]);
+        server.route("/cover.jpg", &[(200, "jpg")]);
+        let mut client = PoliteClient::builder().build().expect("client");
+        client.delay = Duration::from_millis(500);
+        let timeout = Duration::from_secs(10);
+        client.get_with_retry(&server.url("/page")).expect("page");
+        // --asset-delay 0: the cover request does not wait out the page delay.
+        let started = Instant::now();
+        client
+            .get_asset(&server.url("/cover.jpg"), timeout, Some(Duration::ZERO))
+            .expect("cover");
+        assert!(started.elapsed() < Duration::from_millis(400));
+        // No --asset-delay: the next asset waits the page delay since that request.
+        let started = Instant::now();
+        client
+            .get_asset(&server.url("/cover.jpg"), timeout, None)
+            .expect("cover");
+        assert!(started.elapsed() >= Duration::from_millis(400));
+        assert_eq!(server.hits("/cover.jpg"), 2);
+    }
+}
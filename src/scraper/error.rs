@@ -14,6 +14,9 @@ pub enum ScraperError {
     )]
     UnrecognizedHost { host: String },
 
+    #[error("Host '{host}' is blocked by blocked_hosts entry '{pattern}' in config.")]
+    BlockedHost { host: String, pattern: String },
+
     // HTTP and network (2.3.1)
     #[error("Network error: could not reach {url}: {source}")]
     Network { url: String, source: reqwest::Error },
@@ -45,6 +48,14 @@ pub enum ScraperError {
     #[error("Chapter {index} has no content at {url}.")]
     EmptyChapter { index: u32, url: String },
 
+    #[error("Chapter {index} has only {words} word(s), below the minimum of {min}, at {url}.")]
+    ChapterTooShort {
+        index: u32,
+        words: usize,
+        min: u32,
+        url: String,
+    },
+
     #[error("Invalid encoding or HTML at {url}: {reason}")]
     Encoding { url: String, reason: String },
 
@@ -58,14 +69,179 @@ pub enum ScraperError {
     #[error("Access blocked or restricted at {url}. If using a browser you may need cookies or captcha; scripted access may be limited.")]
     AccessBlocked { url: String },
 
+    /// Royal Road: the fiction page is a "not found" page served with HTTP 200.
+    #[error("Story not found at {url}. It may have been deleted, unpublished, or made private.")]
+    StoryNotFound { url: String },
+
     #[error("No chapters could be retrieved (all locked, missing, or failed).")]
     NoChaptersRetrieved,
 
+    /// `--strict`: the post-scrape chapter-order check ([Book::verify_order](crate::model::Book::verify_order)) failed.
+    #[error("Chapter order check failed: {problems}")]
+    ChapterOrder { problems: String },
+
     /// Royal Road: fiction has locked (premium) chapters and --locked-chapters=fail.
     #[error("Fiction has {count} locked (premium) chapter(s). Use --locked-chapters skip or placeholder to include only free chapters or add placeholders.")]
     LockedChaptersNotAllowed { count: usize },
 
+    /// Resume: a TOC entry reuses a checkpoint chapter's index with a different URL and
+    /// --index-collision=fail.
+    #[error("Chapter index {index} in the resume file is {existing_url}, but the TOC now lists {new_url} at that index (chapters renumbered?). Use --index-collision keep-existing or prefer-new to continue.")]
+    IndexCollision {
+        index: u32,
+        existing_url: String,
+        new_url: String,
+    },
+
     /// User cancelled (e.g. pressed q during scrape).
     #[error("Scraping cancelled by user.")]
     Cancelled,
 }
+
+/// Coarse category of a [ScraperError], for callers that only need to know what kind of failure
+/// happened. New variants of [ScraperError] map onto an existing kind where possible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ScraperErrorKind {
+    /// The URL could not be parsed or its site was not recognised.
+    InvalidInput,
+    /// The host is blocked by config or the site refused access.
+    Blocked,
+    /// Connection, redirect, TLS, or body-read failure.
+    Network,
+    /// The server answered with a non-success HTTP status.
+    HttpStatus,
+    /// The story does not exist (deleted, unpublished, or private).
+    NotFound,
+    /// A story page, chapter page, or chapter list could not be parsed or decoded.
+    Parse,
+    /// Pages parsed, but the content was missing, too short, or locked.
+    Content,
+    /// A resume file disagrees with the current chapter list.
+    Resume,
+    /// The user stopped the scrape.
+    Cancelled,
+}
+
+/// Words in an error (or its sources) that mark a TLS failure: handshake, untrusted or invalid
+/// certificate. reqwest has no `is_tls()`, so the message chain is the only signal.
+const TLS_ERROR_MARKERS: &[&str] = &["certificate", "tls", "handshake", "ssl"];
+
+/// True when `err` or any of its sources reads like a TLS failure (see [TLS_ERROR_MARKERS]).
+fn is_tls_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        let message = e.to_string().to_lowercase();
+        if TLS_ERROR_MARKERS.iter().any(|m| message.contains(m)) {
+            return true;
+        }
+        current = e.source();
+    }
+    false
+}
+
+impl ScraperError {
+    /// Error for a failed request to `url`: [ScraperError::Tls] for certificate and handshake
+    /// failures (e.g. a proxy CA missing from `--ca-cert`), otherwise [ScraperError::Network].
+    pub fn from_request(url: String, source: reqwest::Error) -> Self {
+        // Skip reqwest's own message: it contains the URL, which may contain a marker word.
+        if source.is_connect() && std::error::Error::source(&source).is_some_and(is_tls_error) {
+            ScraperError::Tls { source }
+        } else {
+            ScraperError::Network { url, source }
+        }
+    }
+
+    /// Category of this error; see [ScraperErrorKind].
+    pub fn kind(&self) -> ScraperErrorKind {
+        match self {
+            ScraperError::InvalidUrl { .. } | ScraperError::UnrecognizedHost { .. } => {
+                ScraperErrorKind::InvalidInput
+            }
+            ScraperError::BlockedHost { .. } | ScraperError::AccessBlocked { .. } => {
+                ScraperErrorKind::Blocked
+            }
+            ScraperError::Network { .. }
+            | ScraperError::Redirect { .. }
+            | ScraperError::Tls { .. }
+            | ScraperError::BodyRead { .. } => ScraperErrorKind::Network,
+            ScraperError::HttpStatus { .. } => ScraperErrorKind::HttpStatus,
+            ScraperError::StoryNotFound { .. } => ScraperErrorKind::NotFound,
+            ScraperError::ParseStoryPage { .. }
+            | ScraperError::ParseChapter { .. }
+            | ScraperError::Encoding { .. }
+            | ScraperError::ChapterListParse { .. } => ScraperErrorKind::Parse,
+            ScraperError::EmptyChapter { .. }
+            | ScraperError::ChapterTooShort { .. }
+            | ScraperError::EmptyChapterList
+            | ScraperError::NoChaptersRetrieved
+            | ScraperError::ChapterOrder { .. }
+            | ScraperError::LockedChaptersNotAllowed { .. } => ScraperErrorKind::Content,
+            ScraperError::IndexCollision { .. } => ScraperErrorKind::Resume,
+            ScraperError::Cancelled => ScraperErrorKind::Cancelled,
+        }
+    }
+
+    /// Stable snake_case name of the variant, for machine-readable output (e.g. `--error-format json`).
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            ScraperError::InvalidUrl { .. } => "invalid_url",
+            ScraperError::UnrecognizedHost { .. } => "unrecognized_host",
+            ScraperError::BlockedHost { .. } => "blocked_host",
+            ScraperError::Network { .. } => "network",
+            ScraperError::HttpStatus { .. } => "http_status",
+            ScraperError::Redirect { .. } => "redirect",
+            ScraperError::Tls { .. } => "tls",
+            ScraperError::BodyRead { .. } => "body_read",
+            ScraperError::ParseStoryPage { .. } => "parse_story_page",
+            ScraperError::ParseChapter { .. } => "parse_chapter",
+            ScraperError::EmptyChapter { .. } => "empty_chapter",
+            ScraperError::ChapterTooShort { .. } => "chapter_too_short",
+            ScraperError::Encoding { .. } => "encoding",
+            ScraperError::ChapterListParse { .. } => "chapter_list_parse",
+            ScraperError::EmptyChapterList => "empty_chapter_list",
+            ScraperError::AccessBlocked { .. } => "access_blocked",
+            ScraperError::StoryNotFound { .. } => "story_not_found",
+            ScraperError::NoChaptersRetrieved => "no_chapters_retrieved",
+            ScraperError::ChapterOrder { .. } => "chapter_order",
+            ScraperError::LockedChaptersNotAllowed { .. } => "locked_chapters_not_allowed",
+            ScraperError::IndexCollision { .. } => "index_collision",
+            ScraperError::Cancelled => "cancelled",
+        }
+    }
+
+    /// URL the error refers to, if the variant carries one.
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            ScraperError::Network { url, .. }
+            | ScraperError::HttpStatus { url, .. }
+            | ScraperError::Redirect { url, .. }
+            | ScraperError::ParseChapter { url, .. }
+            | ScraperError::EmptyChapter { url, .. }
+            | ScraperError::ChapterTooShort { url, .. }
+            | ScraperError::Encoding { url, .. }
+            | ScraperError::AccessBlocked { url }
+            | ScraperError::StoryNotFound { url } => Some(url.as_str()),
+            ScraperError::InvalidUrl { input, .. } => Some(input.as_str()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn tls_errors_detected_through_source_chain() {
+        let inner = io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid peer certificate: UnknownIssuer",
+        );
+        let outer = io::Error::other(inner);
+        assert!(is_tls_error(&outer));
+        let refused = io::Error::new(io::ErrorKind::ConnectionRefused, "connection refused");
+        assert!(!is_tls_error(&refused));
+    }
+}
@@ -0,0 +1,374 @@
+//! Local HTTP server for end-to-end scrape tests. Serves canned responses per path from a
+//! background thread so the full fetch loop (client retries, TOC parsing, chapter parsing,
+//! checkpoints) runs without touching the network.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Canned responses for one path: served in order, the last one repeating.
+type Responses = VecDeque<(u16, String)>;
+
+pub(crate) struct MockServer {
+    base_url: String,
+    routes: Arc<Mutex<HashMap<String, Responses>>>,
+    hits: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl MockServer {
+    /// Bind to an ephemeral port on 127.0.0.1 and start serving. Unknown paths get 404.
+    pub(crate) fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let base_url = format!("http://{}", listener.local_addr().expect("local addr"));
+        let routes: Arc<Mutex<HashMap<String, Responses>>> = Arc::default();
+        let hits: Arc<Mutex<HashMap<String, u32>>> = Arc::default();
+        let (thread_routes, thread_hits) = (Arc::clone(&routes), Arc::clone(&hits));
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                serve(stream, &thread_routes, &thread_hits);
+            }
+        });
+        MockServer {
+            base_url,
+            routes,
+            hits,
+        }
+    }
+
+    /// Absolute URL for `path` (starting with `/`) on this server.
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Serve `responses` for `path` in order; the last one repeats once the others are used up.
+    pub(crate) fn route(&self, path: &str, responses: &[(u16, &str)]) {
+        let queue = responses
+            .iter()
+            .map(|(status, body)| (*status, body.to_string()))
+            .collect();
+        self.routes.lock().unwrap().insert(path.to_string(), queue);
+    }
+
+    /// Number of requests received for `path`.
+    pub(crate) fn hits(&self, path: &str) -> u32 {
+        self.hits.lock().unwrap().get(path).copied().unwrap_or(0)
+    }
+}
+
+fn serve(
+    mut stream: TcpStream,
+    routes: &Mutex<HashMap<String, Responses>>,
+    hits: &Mutex<HashMap<String, u32>>,
+) {
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    });
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Drain headers; test requests are GETs without a body.
+    let mut line = String::new();
+    while reader.read_line(&mut line).map(|n| n > 2).unwrap_or(false) {
+        line.clear();
+    }
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or("/")
+        .to_string();
+    *hits.lock().unwrap().entry(path.clone()).or_insert(0) += 1;
+    let (status, body) = {
+        let mut routes = routes.lock().unwrap();
+        match routes.get_mut(&path) {
+            Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+            Some(queue) => queue.front().cloned().unwrap_or((404, String::new())),
+            None => (404, String::new()),
+        }
+    };
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} Mock\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper::{
+        scrape_book, ChapterSelection, PoliteClient, ScrapeOptions, ScraperError, Site,
+    };
+    use std::cell::Cell;
+
+    const FICTION_PATH: &str = "/fiction/1/mock-story";
+
+    const FICTION_HTML: &str = r#"<!DOCTYPE html><html><head><title>Mock Story | Royal Road</title>
+<script type="application/ld+json">{"@type":"Book","name":"Mock Story","author":{"name":"Tester"},"description":"<p>A story.</p>"}</script>
+</head><body><script>
+window.chapters = [{"id":11,"title":"One","url":"/fiction/1/mock-story/chapter/11/one","order":0,"isUnlocked":true},{"id":12,"title":"Two","url":"/fiction/1/mock-story/chapter/12/two","order":1,"isUnlocked":true}];
+</script></body></html>"#;
+
+    fn chapter_html(title: &str, text: &str) -> String {
+        format!(
+            r#"<!DOCTYPE html><html><body><h1 class="font-white break-word">{}</h1>
+<div class="chapter-inner chapter-content"><p>{}</p></div></body></html>"#,
+            title, text
+        )
+    }
+
+    fn test_client() -> PoliteClient {
+        PoliteClient::builder()
+            .delay_secs(0)
+            .retry_count(3)
+            .retry_backoff_secs(vec![0])
+            .rate_limit_backoff_secs(vec![0])
+            .build()
+            .unwrap()
+    }
+
+    fn story_server() -> MockServer {
+        let server = MockServer::start();
+        server.route(FICTION_PATH, &[(200, FICTION_HTML)]);
+        server.route(
+            "/fiction/1/mock-story/chapter/11/one",
+            &[(200, &chapter_html("1. One", "First chapter."))],
+        );
+        server
+    }
+
+    #[test]
+    fn royalroad_scrape_retries_rate_limited_chapter() -> Result<(), ScraperError> {
+        let server = story_server();
+        let two = "/fiction/1/mock-story/chapter/12/two";
+        server.route(
+            two,
+            &[
+                (429, "slow down"),
+                (200, &chapter_html("2. Two", "Second.")),
+            ],
+        );
+        let mut client = test_client();
+        let checkpoints = Cell::new(0u32);
+        let on_checkpoint = |_: &crate::model::Book| checkpoints.set(checkpoints.get() + 1);
+        let options = ScrapeOptions {
+            on_checkpoint: Some(&on_checkpoint),
+            ..Default::default()
+        };
+        let book = scrape_book(
+            Site::RoyalRoad,
+            &server.url(FICTION_PATH),
+            &mut client,
+            &options,
+        )?;
+        assert_eq!(book.title, "Mock Story");
+        assert_eq!(book.author, "Tester");
+        let titles: Vec<&str> = book.chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["1. One", "2. Two"]);
+        assert_eq!(book.chapters[1].body, "<p>Second.</p>");
+        assert_eq!(
+            book.chapters[1].url.as_deref(),
+            Some(server.url(two).as_str())
+        );
+        assert_eq!(server.hits(two), 2);
+        assert_eq!(client.rate_limited_responses(), 1);
+        assert_eq!(checkpoints.get(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn royalroad_scrape_with_concurrency_keeps_toc_order() -> Result<(), ScraperError> {
+        let server = story_server();
+        let two = "/fiction/1/mock-story/chapter/12/two";
+        server.route(two, &[(200, &chapter_html("2. Two", "Second."))]);
+        let mut client = test_client();
+        let progress_titles = std::cell::RefCell::new(Vec::new());
+        let progress =
+            |_: u32, _: u32, title: &str| progress_titles.borrow_mut().push(title.to_string());
+        let options = ScrapeOptions {
+            progress: Some(&progress),
+            concurrency: 4,
+            ..Default::default()
+        };
+        let book = scrape_book(
+            Site::RoyalRoad,
+            &server.url(FICTION_PATH),
+            &mut client,
+            &options,
+        )?;
+        let titles: Vec<&str> = book.chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["1. One", "2. Two"]);
+        assert_eq!(*progress_titles.borrow(), vec!["1. One", "2. Two"]);
+        assert_eq!(server.hits("/fiction/1/mock-story/chapter/11/one"), 1);
+        assert_eq!(server.hits(two), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn royalroad_scrape_skips_chapter_after_exhausting_retries() -> Result<(), ScraperError> {
+        let server = story_server();
+        let two = "/fiction/1/mock-story/chapter/12/two";
+        server.route(two, &[(503, "")]);
+        let mut client = test_client();
+        let book = scrape_book(
+            Site::RoyalRoad,
+            &server.url(FICTION_PATH),
+            &mut client,
+            &ScrapeOptions::default(),
+        )?;
+        assert_eq!(book.chapters.len(), 1);
+        assert_eq!(book.chapters[0].index, 1);
+        assert_eq!(server.hits(two), 3);
+        assert_eq!(client.rate_limited_responses(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn royalroad_scrape_cancel_hands_fetched_chapters_to_on_stop() {
+        let server = story_server();
+        let two = "/fiction/1/mock-story/chapter/12/two";
+        server.route(two, &[(200, &chapter_html("2. Two", "Second."))]);
+        let mut client = test_client();
+        let checks = Cell::new(0u32);
+        let cancel_check = || {
+            checks.set(checks.get() + 1);
+            checks.get() > 1
+        };
+        let checkpoints = Cell::new(0u32);
+        let on_checkpoint = |_: &crate::model::Book| checkpoints.set(checkpoints.get() + 1);
+        let stopped_with = Cell::new(None);
+        let on_stop = |b: &crate::model::Book| stopped_with.set(Some(b.chapters.len()));
+        let options = ScrapeOptions {
+            cancel_check: Some(&cancel_check),
+            on_checkpoint: Some(&on_checkpoint),
+            on_stop: Some(&on_stop),
+            ..Default::default()
+        };
+        let err = scrape_book(
+            Site::RoyalRoad,
+            &server.url(FICTION_PATH),
+            &mut client,
+            &options,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ScraperError::Cancelled));
+        assert_eq!(checkpoints.get(), 1);
+        assert_eq!(stopped_with.get(), Some(1));
+        assert_eq!(server.hits(two), 0);
+    }
+
+    #[test]
+    fn royalroad_api_source_falls_back_to_chapter_page() -> Result<(), ScraperError> {
+        let server = story_server();
+        let two = "/fiction/1/mock-story/chapter/12/two";
+        server.route(
+            "/api/chapter/11",
+            &[(
+                200,
+                r#"{"title":"1. One (API)","content":"<p>From the API.</p>"}"#,
+            )],
+        );
+        server.route(two, &[(200, &chapter_html("2. Two", "Second."))]);
+        let mut client = test_client();
+        let options = ScrapeOptions {
+            chapter_source: crate::scraper::ChapterSource::Api,
+            ..Default::default()
+        };
+        let book = scrape_book(
+            Site::RoyalRoad,
+            &server.url(FICTION_PATH),
+            &mut client,
+            &options,
+        )?;
+        let titles: Vec<&str> = book.chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["1. One (API)", "2. Two"]);
+        assert_eq!(book.chapters[0].body, "<p>From the API.</p>");
+        assert_eq!(book.chapters[1].body, "<p>Second.</p>");
+        assert_eq!(server.hits("/fiction/1/mock-story/chapter/11/one"), 0);
+        assert_eq!(server.hits("/api/chapter/12"), 1);
+        assert_eq!(server.hits(two), 1);
+        Ok(())
+    }
+
+    const AO3_WORK_PATH: &str = "/works/7";
+
+    const AO3_WORK_HTML: &str = r#"<!DOCTYPE html><html><head><title>Mock Work - Tester [Archive of Our Own]</title></head><body>
+<dl class="work meta group"><dt class="language">Language:</dt><dd class="language" lang="fr">Français</dd></dl>
+<div class="preface group"><h2 class="title heading">Mock Work</h2><h3 class="byline heading"><a rel="author" href="/users/t">Tester</a></h3></div>
+<div id="chapters">
+<div class="chapter" id="chapter-1"><div class="chapter preface group"><h3 class="title"><a href="/works/7/chapters/71">Chapter 1</a></h3></div><div class="userstuff module"><p>Un.</p></div></div>
+<div class="chapter" id="chapter-2"><div class="chapter preface group"><h3 class="title"><a href="/works/7/chapters/72">Chapter 2</a>: Deux</h3></div><div class="userstuff module"><p>Deux.</p></div></div>
+</div></body></html>"#;
+
+    /// A mock server for an AO3 work, with its host mapped to [Site::Ao3].
+    fn ao3_server() -> (MockServer, crate::scraper::ExtraHosts) {
+        let server = MockServer::start();
+        server.route(
+            &format!("{}?view_full_work=true&view_adult=true", AO3_WORK_PATH),
+            &[(200, AO3_WORK_HTML)],
+        );
+        let mut extra_hosts = crate::scraper::ExtraHosts::new();
+        extra_hosts.insert("127.0.0.1".to_string(), Site::Ao3);
+        (server, extra_hosts)
+    }
+
+    #[test]
+    fn ao3_scrape_honours_chapter_range() -> Result<(), ScraperError> {
+        let (server, extra_hosts) = ao3_server();
+        let mut client = test_client();
+        let options = ScrapeOptions {
+            chapter_range: Some(ChapterSelection {
+                ranges: vec![(2, 2)],
+            }),
+            extra_hosts: Some(&extra_hosts),
+            ..Default::default()
+        };
+        let book = scrape_book(Site::Ao3, &server.url(AO3_WORK_PATH), &mut client, &options)?;
+        assert_eq!(book.title, "Mock Work");
+        assert_eq!(book.language.as_deref(), Some("fr"));
+        assert_eq!(book.chapters.len(), 1);
+        assert_eq!(book.chapters[0].index, 2);
+        assert_eq!(book.chapters[0].title, "Chapter 2: Deux");
+        assert_eq!(book.chapters[0].body, "<p>Deux.</p>");
+        Ok(())
+    }
+
+    #[test]
+    fn ao3_scrape_toc_only_leaves_bodies_empty() -> Result<(), ScraperError> {
+        let (server, extra_hosts) = ao3_server();
+        let mut client = test_client();
+        let options = ScrapeOptions {
+            toc_only: true,
+            extra_hosts: Some(&extra_hosts),
+            ..Default::default()
+        };
+        let book = scrape_book(Site::Ao3, &server.url(AO3_WORK_PATH), &mut client, &options)?;
+        let titles: Vec<&str> = book.chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Chapter 1", "Chapter 2: Deux"]);
+        assert!(book.chapters.iter().all(|c| c.body.is_empty()));
+        assert_eq!(
+            book.chapters[1].url.as_deref(),
+            Some(server.url("/works/7/chapters/72").as_str())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn royalroad_scrape_missing_story_is_http_status() {
+        let server = MockServer::start();
+        let mut client = test_client();
+        let err = scrape_book(
+            Site::RoyalRoad,
+            &server.url("/fiction/404/gone"),
+            &mut client,
+            &ScrapeOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ScraperError::HttpStatus { status: 404, .. }));
+    }
+}
@@ -2,15 +2,30 @@
 
 mod client;
 mod error;
+#[cfg(test)]
+pub(crate) mod mock_server;
 
+pub mod ao3;
 pub mod royalroad;
 pub mod scribblehub;
 
+use client::FetchedPage;
 pub use client::{PoliteClient, PoliteClientBuilder};
-pub use error::ScraperError;
+pub use error::{ScraperError, ScraperErrorKind};
 
-use crate::model::Book;
+use crate::formats::{body_word_count, html_escape_attr, BodyOptions};
+use crate::model::{Book, Chapter};
+use encoding_rs::{Encoding, UTF_8};
+use regex::Regex;
+use reqwest::header::CONTENT_TYPE;
 use reqwest::Url;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::LazyLock;
+
+/// Author used when metadata falls back to the page `<title>` (`--allow-missing-metadata`).
+pub const UNKNOWN_AUTHOR: &str = "Unknown Author";
 
 /// Strip known site suffix from the end of a page title (e.g. " - Royal Road", " | Scribble Hub")
 /// so that titles containing " - " or " | " in the actual title are preserved.
@@ -25,6 +40,96 @@ pub fn strip_title_site_suffix(s: &str, suffixes: &[&str]) -> String {
     t.to_string()
 }
 
+/// Title to keep for a fetched chapter. A parsed title that is just the book title
+/// (case-insensitive; the `<title>` fallbacks already strip the site suffix) came from a page
+/// naming only the story, which would label every chapter alike in the nav; it is replaced by the
+/// TOC title, or "Chapter {index}" when the TOC title is empty. Titles that merely begin with the
+/// book title ("Wormhole" in "Worm") are kept.
+pub(crate) fn chapter_title_or_toc(
+    parsed: String,
+    toc_title: &str,
+    book_title: &str,
+    index: u32,
+) -> String {
+    let book_title = book_title.trim().to_lowercase();
+    let degenerate = !book_title.is_empty() && parsed.trim().to_lowercase() == book_title;
+    if !degenerate {
+        return parsed;
+    }
+    let toc_title = toc_title.trim();
+    if toc_title.is_empty() {
+        format!("Chapter {}", index)
+    } else {
+        toc_title.to_string()
+    }
+}
+
+/// Last-resort title: the document `<title>` with the site suffix stripped. None if absent or empty.
+pub(crate) fn title_from_page_title(doc: &Html, suffixes: &[&str]) -> Option<String> {
+    let sel = Selector::parse("title").ok()?;
+    doc.select(&sel)
+        .next()
+        .map(|e| e.text().collect::<String>())
+        .map(|t| strip_title_site_suffix(t.trim(), suffixes))
+        .filter(|s| !s.is_empty())
+}
+
+/// `--title-from-url` title: the path segment after the numeric story id, title-cased
+/// ("/fiction/21220/mother-of-learning" -> "Mother Of Learning"). None when the URL has no slug.
+pub(crate) fn title_from_url_slug(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let mut segments = parsed.path_segments()?;
+    segments.find(|s| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()))?;
+    let title = segments
+        .next()?
+        .split(['-', '_'])
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+    Some(title).filter(|t| !t.is_empty())
+}
+
+static IMG_SRC: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<img src="([^"]*)""#).expect("static regex"));
+
+/// Resolve every `<img src>` in a normalized chapter body against the chapter's `page_url`, so the
+/// EPUB writer can fetch the images later. Sources that do not resolve are left unchanged.
+pub(crate) fn absolutize_image_srcs(body: &str, page_url: &str) -> String {
+    let Ok(base) = Url::parse(page_url) else {
+        return body.to_string();
+    };
+    IMG_SRC
+        .replace_all(body, |caps: &regex::Captures| {
+            let src = caps[1]
+                .replace("&quot;", "\"")
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&amp;", "&");
+            match base.join(&src) {
+                Ok(abs) => format!(r#"<img src="{}""#, html_escape_attr(abs.as_str())),
+                Err(_) => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Key for [Book::stats]: the site's stat label in snake_case, e.g. "Total Views :" -> "total_views".
+pub(crate) fn stat_key(label: &str) -> String {
+    label
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
 /// How to handle Royal Road locked (premium) chapters. Only applies to Royal Road.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LockedChapterBehavior {
@@ -47,32 +152,936 @@ pub enum EmptyChapterBehavior {
     Fail,
 }
 
+/// What to do when resuming and a TOC entry has the index of a checkpoint chapter but a different
+/// URL, e.g. after the source renumbered its chapters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndexCollisionBehavior {
+    /// Keep the checkpoint chapter and skip the TOC entry (default; matches earlier releases).
+    #[default]
+    KeepExisting,
+    /// Drop the checkpoint chapter and fetch the TOC entry.
+    PreferNew,
+    /// Fail the scrape.
+    Fail,
+}
+
+/// Where Royal Road chapter content is read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChapterSource {
+    /// Parse the chapter HTML page (default).
+    #[default]
+    Html,
+    /// Try the site's JSON chapter endpoint first; fall back to HTML when it fails.
+    Api,
+}
+
+/// How the Scribble Hub chapter list is fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScribbleHubTocMode {
+    /// AJAX "Show All" first; paginated TOC pages if it yields nothing (default).
+    #[default]
+    Auto,
+    /// AJAX "Show All" only.
+    Ajax,
+    /// Paginated TOC pages only; the AJAX endpoint is never requested.
+    Paginate,
+}
+
+/// How Scribble Hub TOC entries are put into reading order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TocSort {
+    /// Ascending by the site's `order` attribute (default). Warns when the result looks newest-first.
+    #[default]
+    Order,
+    /// Ascending by the chapter number in each title; indices are renumbered 1..n. Falls back to
+    /// `order` when a title has no number.
+    Number,
+}
+
 /// Supported fiction site. Used for dispatch and for --site override (Phase 7).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Deserializes from the config `extra_hosts` values `royalroad` / `scribblehub` / `ao3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Site {
+    #[serde(alias = "rr")]
     RoyalRoad,
+    #[serde(alias = "sh")]
     ScribbleHub,
+    #[serde(alias = "archiveofourown")]
+    Ao3,
 }
 
+impl Site {
+    /// Canonical form of a story or chapter URL on this site, used wherever two URLs must be
+    /// compared (e.g. `--resume`): https, `www.` host for the built-in domains (bare
+    /// `archiveofourown.org` for AO3, which redirects `www.`), no query (`?ref=`
+    /// and other tracking params) or fragment, no empty segments, and no trailing slash or pasted
+    /// punctuation. Royal Road paths also lose their slugs (`/fiction/{id}[/chapter/{id}]`), which
+    /// change when an author renames the story or chapter. Fails with [ScraperError::InvalidUrl]
+    /// when the path is not a story or chapter path for the site.
+    pub fn canonicalize_url(self, input: &str) -> Result<String, ScraperError> {
+        let invalid = |reason: String| ScraperError::InvalidUrl {
+            input: input.to_string(),
+            reason,
+        };
+        let trimmed = input
+            .trim()
+            .trim_end_matches(['.', ',', ';', ')', ']', '>', '"', '\'']);
+        let with_scheme = if trimmed.contains("://") {
+            trimmed.to_string()
+        } else {
+            format!("https://{}", trimmed)
+        };
+        let mut url = Url::parse(&with_scheme).map_err(|e| invalid(e.to_string()))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(invalid(format!("unsupported scheme {}", url.scheme())));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| invalid("URL has no host".to_string()))?
+            .trim_end_matches('.')
+            .to_string();
+        let mut segments: Vec<String> = url
+            .path_segments()
+            .map(|s| s.filter(|seg| !seg.is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        if !self.is_story_or_chapter_path(&segments) {
+            return Err(invalid(format!(
+                "not a {} story or chapter path: /{}",
+                self.name(),
+                segments.join("/")
+            )));
+        }
+        if self == Site::RoyalRoad {
+            // fiction/{id}[/{slug}]/chapter/{id}[/{slug}] -> fiction/{id}/chapter/{id}
+            let chapter = royalroad_chapter_segment(&segments);
+            segments = [0, 1, chapter, chapter + 1]
+                .iter()
+                .filter_map(|&i| segments.get(i).cloned())
+                .collect();
+        }
+        let _ = url.set_scheme("https");
+        if let Some((domain, _)) = SITE_DOMAINS
+            .iter()
+            .find(|(d, site)| *site == self && (host == *d || host.ends_with(&format!(".{}", d))))
+        {
+            let host = match self {
+                Site::Ao3 => domain.to_string(),
+                _ => format!("www.{}", domain),
+            };
+            url.set_host(Some(&host))
+                .map_err(|e| invalid(e.to_string()))?;
+        }
+        url.set_path(&format!("/{}", segments.join("/")));
+        url.set_query(None);
+        url.set_fragment(None);
+        Ok(url.to_string())
+    }
+
+    /// Display name used in messages.
+    fn name(self) -> &'static str {
+        match self {
+            Site::RoyalRoad => "Royal Road",
+            Site::ScribbleHub => "Scribble Hub",
+            Site::Ao3 => "Archive of Our Own",
+        }
+    }
+
+    /// Royal Road: `fiction/{id}[/slug][/chapter/{id}[/slug]]`. Scribble Hub: `series/{id}[/slug]`
+    /// or `read/{id}-{slug}/chapter/{id}`. AO3: `works/{id}[/chapters/{id}]`.
+    fn is_story_or_chapter_path(self, segments: &[String]) -> bool {
+        let is_id = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        let seg = |i: usize| segments.get(i).map(String::as_str);
+        match self {
+            Site::RoyalRoad => {
+                let chapter = royalroad_chapter_segment(segments);
+                seg(0) == Some("fiction")
+                    && seg(1).is_some_and(is_id)
+                    && match seg(chapter) {
+                        None => true,
+                        Some("chapter") => {
+                            seg(chapter + 1).is_some_and(is_id) && segments.len() <= chapter + 3
+                        }
+                        Some(_) => false,
+                    }
+            }
+            Site::ScribbleHub => match seg(0) {
+                Some("series") => seg(1).is_some_and(is_id) && segments.len() <= 3,
+                Some("read") => {
+                    seg(1).is_some_and(|s| is_id(s.split('-').next().unwrap_or("")))
+                        && seg(2) == Some("chapter")
+                        && seg(3).is_some_and(is_id)
+                        && segments.len() == 4
+                }
+                _ => false,
+            },
+            Site::Ao3 => {
+                seg(0) == Some("works")
+                    && seg(1).is_some_and(is_id)
+                    && match seg(2) {
+                        None => true,
+                        Some("chapters") => seg(3).is_some_and(is_id) && segments.len() == 4,
+                        Some(_) => false,
+                    }
+            }
+        }
+    }
+}
+
+/// Position of the `chapter` segment in a Royal Road path: 2 in the slugless
+/// `fiction/{id}/chapter/{id}` form that [Site::canonicalize_url] returns, else 3 (after the story
+/// slug).
+fn royalroad_chapter_segment(segments: &[String]) -> usize {
+    if segments.get(2).is_some_and(|s| s == "chapter") {
+        2
+    } else {
+        3
+    }
+}
+
+/// [ScrapeOptions::progress] callback: (done, total, chapter title).
+pub type ProgressCallback<'a> = &'a dyn Fn(u32, u32, &str);
+
+/// [ScrapeOptions::on_toc] callback, given the story's full parsed TOC.
+pub type TocCallback<'a> = &'a dyn Fn(&[TocEntry]);
+
 /// Options for a scrape run: progress callback, chapter range, resume state, checkpoint, locked/empty handling, toc-only, cancel check.
+#[derive(Default)]
 pub struct ScrapeOptions<'a> {
-    pub progress: Option<&'a dyn Fn(u32, u32)>,
-    pub chapter_range: Option<(u32, u32)>,
+    /// Called after each chapter is added with (done, total, chapter title).
+    pub progress: Option<ProgressCallback<'a>>,
+    /// `--chapters`: only chapters whose 1-based TOC index is in the selection.
+    pub chapter_range: Option<ChapterSelection>,
     pub initial_book: Option<&'a Book>,
     pub on_checkpoint: Option<&'a dyn Fn(&Book)>,
+    /// Called once with the chapters fetched so far when the scrape ends in an error (including a
+    /// cancel), so the caller can save them without copying the book at every checkpoint.
+    pub on_stop: Option<&'a dyn Fn(&Book)>,
     pub locked_behavior: Option<LockedChapterBehavior>,
     /// How to handle empty body or missing content container (default Skip).
     pub empty_chapter_behavior: Option<EmptyChapterBehavior>,
     pub toc_only: bool,
     /// If present, called between chapters; when true, scraper returns Err(ScraperError::Cancelled).
     pub cancel_check: Option<&'a dyn Fn() -> bool>,
+    /// When title/author selectors all miss, take the title from `<title>` and use [UNKNOWN_AUTHOR] instead of failing.
+    pub allow_missing_metadata: bool,
+    /// When the title selectors all miss, use the story URL slug, title-cased, before `<title>`.
+    pub title_from_url: bool,
+    /// Royal Road: fetch all unlocked chapters before inserting locked placeholders (book order is unchanged).
+    pub unlocked_first: bool,
+    /// Treat the URL as a chapter page: fetch only that page and return a one-chapter book. TOC, range, and resume are not used.
+    pub single_chapter: bool,
+    /// Royal Road: read chapter content from the JSON endpoint or the HTML page (default Html).
+    pub chapter_source: ChapterSource,
+    /// Chapters with fewer plain-text words go through the empty-chapter policy. 0 disables the check.
+    pub min_chapter_words: u32,
+    /// Tags kept when re-serializing chapter content (config `allowed_block_tags` / `allowed_inline_tags`).
+    pub allowed_tags: BodyOptions,
+    /// Scribble Hub: chapter list strategy (AJAX, pagination, or AJAX then pagination).
+    pub scribblehub_toc_mode: ScribbleHubTocMode,
+    /// Resume: how to treat a TOC entry whose index is already used by a checkpoint chapter from another URL.
+    pub index_collision: IndexCollisionBehavior,
+    /// TOC entries whose title matches are never fetched, on fresh and resumed runs alike.
+    pub title_exclude: Option<Regex>,
+    /// Fetch only these chapters (`--include-chapters`); other TOC entries are dropped.
+    pub include_chapters: Option<ChapterAllowlist>,
+    /// Refetch a chapter page up to this many times when a 200 response looks truncated.
+    pub chapter_parse_retries: u32,
+    /// Scribble Hub: reading order of the TOC (site `order` attribute or title chapter numbers).
+    pub toc_sort: TocSort,
+    /// Abort on the first chapter network, HTTP, or body-read error instead of skipping it.
+    pub fail_fast: bool,
+    /// Collect story-page popularity stats (views, followers, favorites) into [Book::stats].
+    pub stats: bool,
+    /// Config `extra_hosts`: additional hosts the adapters accept as their site's URLs.
+    pub extra_hosts: Option<&'a ExtraHosts>,
+    /// `--strict`: a failed chapter-order check ([Book::verify_order]) is an error, not a warning.
+    pub strict: bool,
+    /// Library hook: rewrite each fetched chapter body after parsing, before the empty and
+    /// minimum-word checks and before it is added to the book. Receives minimal HTML (see
+    /// [normalize_body_html](crate::formats::normalize_body_html)) and must return minimal HTML.
+    /// Not applied to placeholders or to chapters restored from `initial_book`.
+    pub transform_body: Option<&'a dyn Fn(&str) -> String>,
+    /// Previously parsed TOC (`--toc-cache-ttl`) to use instead of fetching the story page. Only
+    /// used with `initial_book`, which supplies the metadata, and without `stats`.
+    pub cached_toc: Option<&'a [TocEntry]>,
+    /// Called with each freshly parsed TOC, before sorting and filtering, so it can be cached.
+    pub on_toc: Option<TocCallback<'a>>,
+    /// Chapter pages fetched at once (`--concurrency`, see [Prefetch]). 0 and 1 fetch one at a time.
+    pub concurrency: u32,
+    /// Royal Road: class-name patterns whose chapter paragraphs are dropped as hidden anti-theft
+    /// text, in addition to classes the page's stylesheet hides and inline `display: none`.
+    pub hidden_classes: Vec<Regex>,
+}
+
+/// One parsed TOC entry as cached for `--toc-cache-ttl`. Scribble Hub entries are always unlocked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TocEntry {
+    pub index: u32,
+    pub url: String,
+    pub title: String,
+    pub unlocked: bool,
+}
+
+/// An adapter's TOC row tuple, convertible to and from [TocEntry]: (index, chapter URL, title,
+/// unlocked) on Royal Road, (index, chapter URL, title) on Scribble Hub, where all are unlocked.
+pub(crate) trait TocRow {
+    fn to_entry(&self) -> TocEntry;
+    fn from_entry(entry: &TocEntry) -> Self;
+}
+
+impl TocRow for (u32, String, String, bool) {
+    fn to_entry(&self) -> TocEntry {
+        TocEntry {
+            index: self.0,
+            url: self.1.clone(),
+            title: self.2.clone(),
+            unlocked: self.3,
+        }
+    }
+
+    fn from_entry(e: &TocEntry) -> Self {
+        (e.index, e.url.clone(), e.title.clone(), e.unlocked)
+    }
+}
+
+impl TocRow for (u32, String, String) {
+    fn to_entry(&self) -> TocEntry {
+        TocEntry {
+            index: self.0,
+            url: self.1.clone(),
+            title: self.2.clone(),
+            unlocked: true,
+        }
+    }
+
+    fn from_entry(e: &TocEntry) -> Self {
+        (e.index, e.url.clone(), e.title.clone())
+    }
+}
+
+/// Pass the freshly parsed `toc` to [ScrapeOptions::on_toc], if set.
+pub(crate) fn report_toc<R: TocRow>(options: &ScrapeOptions<'_>, toc: &[R]) {
+    if let Some(cb) = options.on_toc {
+        let entries: Vec<TocEntry> = toc.iter().map(TocRow::to_entry).collect();
+        cb(&entries);
+    }
+}
+
+/// The [ScrapeOptions::cached_toc] to use in place of the story page, if it can stand in for it.
+pub(crate) fn usable_cached_toc<'o>(options: &ScrapeOptions<'o>) -> Option<&'o [TocEntry]> {
+    options
+        .cached_toc
+        .filter(|_| options.initial_book.is_some() && !options.stats)
+}
+
+/// `--chapters` selection: 1-based inclusive index ranges. An open start is stored as 1 and an
+/// open end as `u32::MAX`; a single chapter is a range of one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChapterSelection {
+    pub ranges: Vec<(u32, u32)>,
+}
+
+impl ChapterSelection {
+    /// Whether any range contains `index`.
+    pub fn contains(&self, index: u32) -> bool {
+        self.ranges
+            .iter()
+            .any(|&(from, to)| from <= index && index <= to)
+    }
+}
+
+/// Hand-picked chapters, by 1-based TOC index or chapter URL. URLs match ignoring a trailing `/`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChapterAllowlist {
+    pub indices: BTreeSet<u32>,
+    pub urls: BTreeSet<String>,
+}
+
+impl ChapterAllowlist {
+    fn contains(&self, index: u32, url: &str) -> bool {
+        self.indices.contains(&index) || self.urls.contains(url.trim_end_matches('/'))
+    }
+}
+
+/// Why a chapter has no usable content. Every case is routed through [EmptyChapterBehavior].
+#[derive(Debug)]
+pub(crate) enum MissingContent {
+    /// Page parsed but the body is empty. Carries the parsed chapter title.
+    Empty { title: String },
+    /// Content container not found on the page.
+    Unparseable,
+    /// Chapter page returned HTTP 4xx. Carries the TOC title.
+    ClientError { title: String, status: u16 },
+    /// Body has fewer words than `--min-chapter-words`. Carries the parsed title and word count.
+    TooShort {
+        title: String,
+        words: usize,
+        min: u32,
+    },
+}
+
+/// Whether a 200 chapter page that produced `missing` looks cut off in transit (worth refetching)
+/// rather than genuinely empty: the content container is absent, or the page has no closing
+/// `</html>` tag near its end. HTTP 4xx (locked or removed chapters) never counts.
+pub(crate) fn looks_truncated(missing: &MissingContent, html: &str) -> bool {
+    match missing {
+        MissingContent::Unparseable => true,
+        MissingContent::Empty { .. } | MissingContent::TooShort { .. } => {
+            let tail = &html.as_bytes()[html.len().saturating_sub(4096)..];
+            !tail.windows(7).any(|w| w.eq_ignore_ascii_case(b"</html>"))
+        }
+        MissingContent::ClientError { .. } => false,
+    }
+}
+
+/// Read a response body as text, transcoding when the page declares a legacy charset.
+///
+/// The charset comes from a byte order mark, then the Content-Type header, then a `<meta>`
+/// charset in the first 1024 bytes. UTF-8 and unknown labels decode leniently as before;
+/// malformed bytes in a legacy encoding are [ScraperError::Encoding].
+pub(crate) fn response_text(
+    response: reqwest::blocking::Response,
+    url: &str,
+) -> Result<String, ScraperError> {
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    let bytes = response
+        .bytes()
+        .map_err(|e| ScraperError::BodyRead { source: e })?;
+    decode_body(&bytes, content_type.as_deref(), url)
+}
+
+/// A chapter page response: fetched just now, or ahead of time by [Prefetch].
+pub(crate) enum ChapterResponse {
+    Live(reqwest::blocking::Response),
+    Prefetched(FetchedPage),
+}
+
+impl ChapterResponse {
+    pub(crate) fn status(&self) -> reqwest::StatusCode {
+        match self {
+            ChapterResponse::Live(response) => response.status(),
+            ChapterResponse::Prefetched(page) => page.status,
+        }
+    }
+
+    /// The body as text, decoded like [response_text].
+    pub(crate) fn text(self, url: &str) -> Result<String, ScraperError> {
+        match self {
+            ChapterResponse::Live(response) => response_text(response, url),
+            ChapterResponse::Prefetched(page) => {
+                let bytes = page
+                    .body
+                    .map_err(|e| ScraperError::BodyRead { source: e })?;
+                decode_body(&bytes, page.content_type.as_deref(), url)
+            }
+        }
+    }
+}
+
+/// `--concurrency`: chapter pages fetched ahead of the chapter loop, up to `workers` at a time.
+/// The loop itself stays serial, so parsing, progress and checkpoints run in TOC order on one
+/// thread. With one worker nothing is fetched ahead and every page is a live request.
+pub(crate) struct Prefetch {
+    workers: usize,
+    pages: HashMap<String, Result<FetchedPage, reqwest::Error>>,
+}
+
+impl Prefetch {
+    pub(crate) fn new(concurrency: u32) -> Prefetch {
+        Prefetch {
+            workers: concurrency.max(1) as usize,
+            pages: HashMap::new(),
+        }
+    }
+
+    /// Unless `url` is already waiting, fetch it together with the next `upcoming` (index, URL)
+    /// entries that `book` does not have yet, `workers` pages in all.
+    pub(crate) fn fill<'u>(
+        &mut self,
+        client: &mut PoliteClient,
+        book: &Book,
+        url: &str,
+        upcoming: impl Iterator<Item = (u32, &'u str)>,
+    ) {
+        if self.workers <= 1 || self.pages.contains_key(url) {
+            return;
+        }
+        let mut batch = vec![url.to_string()];
+        batch.extend(
+            upcoming
+                .filter(|(index, u)| {
+                    *u != url
+                        && !self.pages.contains_key(*u)
+                        && !book.chapters.iter().any(|c| c.index == *index)
+                })
+                .map(|(_, u)| u.to_string())
+                .take(self.workers - 1),
+        );
+        let pages = client.get_pages(&batch, self.workers);
+        // A URL with no result is left out, so get() fetches it live and reports its own error.
+        self.pages.extend(
+            batch
+                .into_iter()
+                .zip(pages)
+                .filter_map(|(url, page)| Some((url, page?))),
+        );
+    }
+
+    /// The page at `url`: the prefetched copy when there is one (each is used once, so a
+    /// truncated page is refetched live), else a GET with retries.
+    pub(crate) fn get(
+        &mut self,
+        client: &mut PoliteClient,
+        url: &str,
+    ) -> Result<ChapterResponse, reqwest::Error> {
+        match self.pages.remove(url) {
+            Some(page) => page.map(ChapterResponse::Prefetched),
+            None => client.get_with_retry(url).map(ChapterResponse::Live),
+        }
+    }
+}
+
+/// Value of the first `charset=` parameter in `s` (Content-Type header or `<meta>` markup).
+fn charset_label(s: &str) -> Option<&str> {
+    let lower = s.to_ascii_lowercase();
+    let start = lower.find("charset")? + "charset".len();
+    let rest = s[start..].trim_start().strip_prefix('=')?.trim_start();
+    let rest = rest.trim_start_matches(['"', '\'']);
+    let end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "-_:.".contains(c)))
+        .unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
+
+fn decode_body(
+    bytes: &[u8],
+    content_type: Option<&str>,
+    url: &str,
+) -> Result<String, ScraperError> {
+    let (encoding, bom_len) = match Encoding::for_bom(bytes) {
+        Some((encoding, len)) => (encoding, len),
+        None => {
+            let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+            let declared = content_type
+                .and_then(charset_label)
+                .or_else(|| charset_label(&head))
+                .and_then(|label| Encoding::for_label(label.as_bytes()))
+                .unwrap_or(UTF_8);
+            (declared.output_encoding(), 0)
+        }
+    };
+    let bytes = &bytes[bom_len..];
+    if encoding == UTF_8 {
+        return Ok(String::from_utf8_lossy(bytes).into_owned());
+    }
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .map(|text| text.into_owned())
+        .ok_or_else(|| ScraperError::Encoding {
+            url: url.to_string(),
+            reason: format!("malformed {} byte sequence", encoding.name()),
+        })
+}
+
+/// Word count of `body` if it falls below `min` (0 disables the check); None if long enough.
+fn short_chapter_words(body: &str, min: u32) -> Option<usize> {
+    if min == 0 {
+        return None;
+    }
+    let words = body_word_count(body);
+    (words < min as usize).then_some(words)
+}
+
+/// Run [ScrapeOptions::transform_body] on a parsed chapter body, if set.
+pub(crate) fn transform_body(body: String, options: &ScrapeOptions<'_>) -> String {
+    match options.transform_body {
+        Some(transform) => transform(&body),
+        None => body,
+    }
+}
+
+/// A parsed chapter (after [transform_body]), or why it counts as missing: empty body, or fewer
+/// words than [ScrapeOptions::min_chapter_words].
+pub(crate) fn chapter_or_missing(
+    title: String,
+    index: u32,
+    body: String,
+    options: &ScrapeOptions<'_>,
+) -> Result<Chapter, MissingContent> {
+    let body = transform_body(body, options);
+    let min_words = options.min_chapter_words;
+    if body.is_empty() {
+        return Err(MissingContent::Empty { title });
+    }
+    if let Some(words) = short_chapter_words(&body, min_words) {
+        return Err(MissingContent::TooShort {
+            title,
+            words,
+            min: min_words,
+        });
+    }
+    Ok(Chapter {
+        title,
+        index,
+        body,
+        reported_word_count: None,
+        url: None,
+    })
+}
+
+/// Apply the empty-chapter policy to a chapter without content: log and skip (Ok(None)),
+/// return a placeholder chapter, or fail with the matching error.
+pub(crate) fn handle_missing_chapter(
+    behavior: EmptyChapterBehavior,
+    missing: MissingContent,
+    index: u32,
+    url: &str,
+) -> Result<Option<Chapter>, ScraperError> {
+    match behavior {
+        EmptyChapterBehavior::Skip => {
+            match &missing {
+                MissingContent::Empty { .. } => {
+                    eprintln!("Chapter {} returned no content at {}. Skipped.", index, url)
+                }
+                MissingContent::Unparseable => eprintln!(
+                    "Chapter {}: could not parse content at {}. Skipped.",
+                    index, url
+                ),
+                MissingContent::ClientError { status, .. } => {
+                    eprintln!("Chapter {}: HTTP {} at {}. Skipped.", index, status, url)
+                }
+                MissingContent::TooShort { words, min, .. } => eprintln!(
+                    "Chapter {} has {} word(s), below --min-chapter-words {}, at {}. Skipped.",
+                    index, words, min, url
+                ),
+            }
+            Ok(None)
+        }
+        EmptyChapterBehavior::Placeholder => Ok(Some(missing_placeholder(missing, index))),
+        EmptyChapterBehavior::Fail => Err(match missing {
+            MissingContent::Empty { .. } => ScraperError::EmptyChapter {
+                index,
+                url: url.to_string(),
+            },
+            MissingContent::Unparseable => ScraperError::ParseChapter {
+                index,
+                url: url.to_string(),
+            },
+            MissingContent::ClientError { status, .. } => ScraperError::HttpStatus {
+                status,
+                url: url.to_string(),
+                context: Some(format!("chapter {}", index)),
+            },
+            MissingContent::TooShort { words, min, .. } => ScraperError::ChapterTooShort {
+                index,
+                words,
+                min,
+                url: url.to_string(),
+            },
+        }),
+    }
+}
+
+/// Placeholder chapter for missing content. 404/410 read as removed, 401/403 as blocked.
+fn missing_placeholder(missing: MissingContent, index: u32) -> Chapter {
+    let (title, body) = match missing {
+        MissingContent::Empty { title } => (
+            format!("{} (no content)", title),
+            "<p>This chapter returned no content.</p>".to_string(),
+        ),
+        MissingContent::Unparseable => (
+            format!("Chapter {} (unable to parse)", index),
+            "<p>This chapter could not be parsed (missing content container).</p>".to_string(),
+        ),
+        MissingContent::ClientError {
+            title,
+            status: status @ (404 | 410),
+        } => (
+            format!("{} (removed)", title),
+            format!(
+                "<p>This chapter was not found (HTTP {}); it may have been removed.</p>",
+                status
+            ),
+        ),
+        MissingContent::ClientError {
+            title,
+            status: status @ (401 | 403),
+        } => (
+            format!("{} (blocked)", title),
+            format!(
+                "<p>Access to this chapter was blocked (HTTP {}).</p>",
+                status
+            ),
+        ),
+        MissingContent::ClientError { title, status } => (
+            format!("{} (unavailable)", title),
+            format!(
+                "<p>This chapter could not be retrieved (HTTP {}).</p>",
+                status
+            ),
+        ),
+        MissingContent::TooShort { title, words, min } => (
+            format!("{} (too short)", title),
+            format!(
+                "<p>This chapter had {} word(s), below the minimum of {}, and was left out.</p>",
+                words, min
+            ),
+        ),
+    };
+    Chapter {
+        title,
+        index,
+        body,
+        reported_word_count: None,
+        url: None,
+    }
+}
+
+/// Drop TOC entries outside `--chapters` or `--include-chapters`, or whose title matches
+/// `--title-exclude`. Runs before the resume checkpoint is consulted, so a resumed run skips
+/// exactly what a fresh run would. `key` gives an entry's (index, URL, title).
+pub(crate) fn retain_wanted_chapters<T>(
+    toc: &mut Vec<T>,
+    options: &ScrapeOptions<'_>,
+    key: impl Fn(&T) -> (u32, &str, &str),
+) {
+    if let Some(allow) = &options.include_chapters {
+        warn_unmatched_allowlist(allow, toc.iter().map(&key));
+    }
+    let mut excluded = 0usize;
+    toc.retain(|entry| {
+        let (index, url, title) = key(entry);
+        if let Some(selection) = &options.chapter_range {
+            if !selection.contains(index) {
+                return false;
+            }
+        }
+        if let Some(allow) = &options.include_chapters {
+            if !allow.contains(index, url) {
+                return false;
+            }
+        }
+        if options
+            .title_exclude
+            .as_ref()
+            .is_some_and(|re| re.is_match(title))
+        {
+            excluded += 1;
+            return false;
+        }
+        true
+    });
+    if excluded > 0 {
+        eprintln!(
+            "Excluded {} chapter(s) whose title matches --title-exclude.",
+            excluded
+        );
+    }
+}
+
+/// Warn about `--include-chapters` entries that match nothing in the TOC.
+fn warn_unmatched_allowlist<'e>(
+    allow: &ChapterAllowlist,
+    toc: impl Iterator<Item = (u32, &'e str, &'e str)>,
+) {
+    let mut indices = allow.indices.clone();
+    let mut urls = allow.urls.clone();
+    for (index, url, _) in toc {
+        indices.remove(&index);
+        urls.remove(url.trim_end_matches('/'));
+    }
+    for index in indices {
+        eprintln!(
+            "Warning: --include-chapters: chapter {} is not in the TOC.",
+            index
+        );
+    }
+    for url in urls {
+        eprintln!("Warning: --include-chapters: {} is not in the TOC.", url);
+    }
+}
+
+/// Whether the TOC entry `index` at `url` still has to be fetched when resuming into `book`.
+///
+/// A chapter already at `index` counts as the same chapter when its URL matches or is unknown
+/// (checkpoints written before chapter URLs were recorded). A different URL is a collision: warn,
+/// then keep the checkpoint chapter, replace it, or fail per `behavior`.
+pub(crate) fn needs_fetch(
+    book: &mut Book,
+    index: u32,
+    url: &str,
+    behavior: IndexCollisionBehavior,
+) -> Result<bool, ScraperError> {
+    let Some(pos) = book.chapters.iter().position(|c| c.index == index) else {
+        return Ok(true);
+    };
+    let existing_url = match &book.chapters[pos].url {
+        Some(u) if u.trim_end_matches('/') != url.trim_end_matches('/') => u.clone(),
+        _ => return Ok(false),
+    };
+    match behavior {
+        IndexCollisionBehavior::KeepExisting => {
+            eprintln!(
+                "Warning: chapter {} in the resume file is {}, but the TOC lists {}. Keeping the resumed chapter.",
+                index, existing_url, url
+            );
+            Ok(false)
+        }
+        IndexCollisionBehavior::PreferNew => {
+            eprintln!(
+                "Warning: chapter {} in the resume file is {}, but the TOC lists {}. Replacing it.",
+                index, existing_url, url
+            );
+            book.chapters.remove(pos);
+            Ok(true)
+        }
+        IndexCollisionBehavior::Fail => Err(ScraperError::IndexCollision {
+            index,
+            existing_url,
+            new_url: url.to_string(),
+        }),
+    }
+}
+
+/// Add a fetched or placeholder chapter (with the URL it came from) in TOC order, then report
+/// progress and checkpoint.
+pub(crate) fn record_chapter(
+    book: &mut Book,
+    mut chapter: Chapter,
+    url: &str,
+    done: &mut u32,
+    total: u32,
+    options: &ScrapeOptions<'_>,
+) {
+    chapter.url = Some(url.to_string());
+    *done += 1;
+    if let Some(p) = options.progress {
+        p(*done, total, &chapter.title);
+    }
+    book.chapters.push(chapter);
+    book.chapters.sort_by_key(|c| c.index);
+    if let Some(cb) = options.on_checkpoint {
+        cb(book);
+    }
+}
+
+/// Hand the chapters fetched so far to [ScrapeOptions::on_stop] and return `err` to abort the
+/// scrape: `--fail-fast`, a cancel, or any other error that ends the chapter loop.
+pub(crate) fn stop_early(
+    book: &Book,
+    options: &ScrapeOptions<'_>,
+    err: ScraperError,
+) -> ScraperError {
+    if let Some(cb) = options.on_stop {
+        cb(book);
+    }
+    err
+}
+
+/// Chapters at or below this many words can be treated as navigation artifacts.
+const NAV_ARTIFACT_MAX_WORDS: usize = 5;
+
+/// Titles that look like navigation links rather than chapters (compared lowercase, without punctuation).
+const NAV_ARTIFACT_TITLES: &[&str] = &[
+    "next",
+    "next chapter",
+    "previous",
+    "previous chapter",
+    "prev",
+    "index",
+    "table of contents",
+    "contents",
+    "back",
+];
+
+/// True if the chapter looks like footer/next-button text scraped as content: a navigation-like
+/// title and a body of at most [NAV_ARTIFACT_MAX_WORDS] words.
+fn is_nav_artifact(ch: &Chapter) -> bool {
+    let title: String = ch
+        .title
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    NAV_ARTIFACT_TITLES.contains(&title.as_str())
+        && body_word_count(&ch.body) <= NAV_ARTIFACT_MAX_WORDS
+}
+
+/// Post-scrape cleanup (`--drop-nav-artifacts`): remove chapters that are navigation text
+/// misread as content and return them so the caller can warn. Genuine short chapters are kept.
+pub fn drop_nav_artifacts(book: &mut Book) -> Vec<Chapter> {
+    let (artifacts, chapters) = std::mem::take(&mut book.chapters)
+        .into_iter()
+        .partition(is_nav_artifact);
+    book.chapters = chapters;
+    artifacts
+}
+
+/// Built-in site domains. The bare domain and any subdomain (`www.`, `m.`, regional) match.
+const SITE_DOMAINS: &[(&str, Site)] = &[
+    ("royalroad.com", Site::RoyalRoad),
+    ("scribblehub.com", Site::ScribbleHub),
+    ("archiveofourown.org", Site::Ao3),
+];
+
+/// Extra host → site associations from the config `extra_hosts` table.
+pub type ExtraHosts = BTreeMap<String, Site>;
+
+/// Map a URL host to its site. Built-in domains are checked first, then `extra_hosts`. Matching
+/// is suffix-based on whole labels and case-insensitive, as for `blocked_hosts`:
+/// `scribblehub.com` matches `m.scribblehub.com` but not `notscribblehub.com`.
+pub fn site_for_host(host: &str, extra_hosts: Option<&ExtraHosts>) -> Option<Site> {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let matches = |domain: &str| {
+        let d = domain.trim().trim_start_matches('.').to_ascii_lowercase();
+        !d.is_empty() && (host == d || host.ends_with(&format!(".{}", d)))
+    };
+    SITE_DOMAINS
+        .iter()
+        .find(|(domain, _)| matches(domain))
+        .map(|(_, site)| *site)
+        .or_else(|| {
+            extra_hosts?
+                .iter()
+                .find(|(domain, _)| matches(domain))
+                .map(|(_, site)| *site)
+        })
 }
 
 /// Resolve which site to use from URL and optional override. Messages per ERROR_HANDLING.md 2.2.
-pub fn resolve_site(url_input: &str, override_site: Option<Site>) -> Result<Site, ScraperError> {
+/// `extra_hosts` (config) extends the built-in host list; see [site_for_host].
+pub fn resolve_site(
+    url_input: &str,
+    override_site: Option<Site>,
+    extra_hosts: Option<&ExtraHosts>,
+) -> Result<Site, ScraperError> {
     if let Some(site) = override_site {
         return Ok(site);
     }
+    detect_site(url_input, extra_hosts)
+}
+
+/// When `--site` is given and the URL host is a recognised site other than the override, return
+/// the detected site so the caller can warn. The override still wins; unparseable or unknown
+/// hosts return `None`.
+pub fn site_override_mismatch(
+    url_input: &str,
+    override_site: Option<Site>,
+    extra_hosts: Option<&ExtraHosts>,
+) -> Option<Site> {
+    let site = override_site?;
+    detect_site(url_input, extra_hosts)
+        .ok()
+        .filter(|detected| *detected != site)
+}
+
+fn detect_site(url_input: &str, extra_hosts: Option<&ExtraHosts>) -> Result<Site, ScraperError> {
     let url = Url::parse(url_input).map_err(|e| ScraperError::InvalidUrl {
         input: url_input.to_string(),
         reason: e.to_string(),
@@ -81,18 +1090,38 @@ pub fn resolve_site(url_input: &str, override_site: Option<Site>) -> Result<Site
         input: url_input.to_string(),
         reason: "URL has no host".to_string(),
     })?;
-    if host.contains("royalroad.com") {
-        Ok(Site::RoyalRoad)
-    } else if host.contains("scribblehub.com") {
-        Ok(Site::ScribbleHub)
-    } else {
-        Err(ScraperError::UnrecognizedHost {
-            host: host.to_string(),
-        })
+    site_for_host(host, extra_hosts).ok_or_else(|| ScraperError::UnrecognizedHost {
+        host: host.to_string(),
+    })
+}
+
+/// Reject the URL if its host matches a `blocked_hosts` entry. Matching is suffix-based on whole
+/// labels and case-insensitive: `example.org` blocks `example.org` and `www.example.org`.
+/// Unparseable URLs pass through; [resolve_site] reports them.
+pub fn check_blocked_host(url_input: &str, blocked_hosts: &[String]) -> Result<(), ScraperError> {
+    let host = match Url::parse(url_input)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+    {
+        Some(h) => h,
+        None => return Ok(()),
+    };
+    for pattern in blocked_hosts {
+        let p = pattern.trim().trim_start_matches('.').to_ascii_lowercase();
+        if p.is_empty() {
+            continue;
+        }
+        if host == p || host.ends_with(&format!(".{}", p)) {
+            return Err(ScraperError::BlockedHost {
+                host,
+                pattern: pattern.clone(),
+            });
+        }
     }
+    Ok(())
 }
 
-/// Trait implemented by site adapters (Royal Road, Scribble Hub).
+/// Trait implemented by site adapters (Royal Road, Scribble Hub, Archive of Our Own).
 ///
 /// Returns the canonical [Book](crate::model::Book) (shape per OUTPUT_SHAPE.md).
 /// See [ScrapeOptions] for the meaning of each option.
@@ -101,14 +1130,15 @@ pub trait Scraper {
         -> Result<Book, ScraperError>;
 }
 
-/// Dispatch by site: build the appropriate adapter and call scrape_book.
+/// Dispatch by site: build the appropriate adapter and call scrape_book, then check the
+/// chapter order (see [check_chapter_order]).
 pub fn scrape_book(
     site: Site,
     url: &str,
     client: &mut PoliteClient,
     options: &ScrapeOptions<'_>,
 ) -> Result<Book, ScraperError> {
-    match site {
+    let book = match site {
         Site::RoyalRoad => {
             let mut adapter = royalroad::RoyalRoadScraper::new(client);
             adapter.scrape_book(url, options)
@@ -117,7 +1147,35 @@ pub fn scrape_book(
             let mut adapter = scribblehub::ScribbleHubScraper::new(client);
             adapter.scrape_book(url, options)
         }
+        Site::Ao3 => {
+            let mut adapter = ao3::Ao3Scraper::new(client);
+            adapter.scrape_book(url, options)
+        }
+    }?;
+    check_chapter_order(&book, options.strict).map_err(|e| stop_early(&book, options, e))?;
+    Ok(book)
+}
+
+/// Defensive check after all TOC sorting and resume merging: print a warning per
+/// [Book::verify_order] problem, or fail with [ScraperError::ChapterOrder] when `strict`.
+/// [Book::repeated_titles] are warnings either way.
+fn check_chapter_order(book: &Book, strict: bool) -> Result<(), ScraperError> {
+    for repeated in book.repeated_titles() {
+        eprintln!("Warning: chapter order: {}.", repeated);
+    }
+    let problems = book.verify_order();
+    if problems.is_empty() {
+        return Ok(());
+    }
+    if strict {
+        return Err(ScraperError::ChapterOrder {
+            problems: problems.join("; "),
+        });
+    }
+    for problem in &problems {
+        eprintln!("Warning: chapter order: {}.", problem);
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -151,21 +1209,64 @@ mod tests {
 
     #[test]
     fn site_detection_royalroad() -> Result<(), ScraperError> {
-        let site = resolve_site("https://www.royalroad.com/fiction/123/slug", None)?;
+        let site = resolve_site("https://www.royalroad.com/fiction/123/slug", None, None)?;
         assert_eq!(site, Site::RoyalRoad);
         Ok(())
     }
 
     #[test]
     fn site_detection_scribblehub() -> Result<(), ScraperError> {
-        let site = resolve_site("https://www.scribblehub.com/series/1/slug/", None)?;
+        let site = resolve_site("https://www.scribblehub.com/series/1/slug/", None, None)?;
+        assert_eq!(site, Site::ScribbleHub);
+        Ok(())
+    }
+
+    #[test]
+    fn site_detection_ao3() -> Result<(), ScraperError> {
+        let site = resolve_site("https://archiveofourown.org/works/2080878", None, None)?;
+        assert_eq!(site, Site::Ao3);
+        Ok(())
+    }
+
+    #[test]
+    fn site_detection_scribblehub_mobile_and_bare_hosts() -> Result<(), ScraperError> {
+        for url in [
+            "https://m.scribblehub.com/series/1/slug/",
+            "https://scribblehub.com/series/1/slug/",
+            "https://M.ScribbleHub.com/series/1/slug/",
+        ] {
+            assert_eq!(resolve_site(url, None, None)?, Site::ScribbleHub, "{}", url);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn site_for_host_matches_on_label_boundary() {
+        assert_eq!(
+            site_for_host("m.royalroad.com", None),
+            Some(Site::RoyalRoad)
+        );
+        assert_eq!(site_for_host("notscribblehub.com", None), None);
+        assert_eq!(site_for_host("scribblehub.com.evil.example", None), None);
+    }
+
+    #[test]
+    fn site_for_host_uses_extra_hosts() -> Result<(), ScraperError> {
+        let mut extra = ExtraHosts::new();
+        extra.insert("SH-Mirror.example".to_string(), Site::ScribbleHub);
+        assert_eq!(
+            site_for_host("www.sh-mirror.example", Some(&extra)),
+            Some(Site::ScribbleHub)
+        );
+        assert_eq!(site_for_host("other.example", Some(&extra)), None);
+        let site = resolve_site("https://sh-mirror.example/series/1/x/", None, Some(&extra))?;
         assert_eq!(site, Site::ScribbleHub);
         Ok(())
     }
 
     #[test]
     fn site_detection_unrecognized_host_errors() -> Result<(), String> {
-        let result = resolve_site("https://example.com/foo", None);
+        let result = resolve_site("https://example.com/foo", None, None);
         match &result {
             Err(ScraperError::UnrecognizedHost { host }) if host == "example.com" => Ok(()),
             _ => Err(format!("expected UnrecognizedHost, got {:?}", result)),
@@ -174,7 +1275,7 @@ mod tests {
 
     #[test]
     fn site_detection_invalid_url_errors() -> Result<(), String> {
-        let result = resolve_site("not-a-url", None);
+        let result = resolve_site("not-a-url", None, None);
         match &result {
             Err(ScraperError::InvalidUrl { input, .. }) if input == "not-a-url" => Ok(()),
             _ => Err(format!("expected InvalidUrl, got {:?}", result)),
@@ -183,8 +1284,734 @@ mod tests {
 
     #[test]
     fn site_override_ignores_url_host() -> Result<(), ScraperError> {
-        let site = resolve_site("https://example.com/foo", Some(Site::RoyalRoad))?;
+        let site = resolve_site("https://example.com/foo", Some(Site::RoyalRoad), None)?;
         assert_eq!(site, Site::RoyalRoad);
         Ok(())
     }
+
+    #[test]
+    fn site_override_mismatch_reports_detected_site() {
+        let rr = "https://www.royalroad.com/fiction/123/slug";
+        assert_eq!(
+            site_override_mismatch(rr, Some(Site::ScribbleHub), None),
+            Some(Site::RoyalRoad)
+        );
+        assert_eq!(
+            site_override_mismatch(rr, Some(Site::RoyalRoad), None),
+            None
+        );
+        assert_eq!(site_override_mismatch(rr, None, None), None);
+        assert_eq!(
+            site_override_mismatch("https://example.com/foo", Some(Site::RoyalRoad), None),
+            None
+        );
+    }
+
+    #[test]
+    fn blocked_host_matches_suffix_on_label_boundary() {
+        let blocked = vec!["mirror.example".to_string()];
+        assert!(matches!(
+            check_blocked_host("https://www.Mirror.example/fiction/1", &blocked),
+            Err(ScraperError::BlockedHost { ref host, .. }) if host == "www.mirror.example"
+        ));
+        assert!(check_blocked_host("https://mirror.example/x", &blocked).is_err());
+        assert!(check_blocked_host("https://notmirror.example/x", &blocked).is_ok());
+        assert!(check_blocked_host("https://www.royalroad.com/fiction/1", &blocked).is_ok());
+        assert!(check_blocked_host("https://www.royalroad.com/fiction/1", &[]).is_ok());
+    }
+
+    #[test]
+    fn missing_chapter_placeholder_distinguishes_removed_and_blocked() -> Result<(), ScraperError> {
+        let removed = handle_missing_chapter(
+            EmptyChapterBehavior::Placeholder,
+            MissingContent::ClientError {
+                title: "Ch 3".to_string(),
+                status: 404,
+            },
+            3,
+            "https://example.com/3",
+        )?
+        .expect("placeholder");
+        assert_eq!(removed.title, "Ch 3 (removed)");
+        assert_eq!(removed.index, 3);
+        assert!(removed.body.contains("404"));
+
+        let blocked = handle_missing_chapter(
+            EmptyChapterBehavior::Placeholder,
+            MissingContent::ClientError {
+                title: "Ch 4".to_string(),
+                status: 403,
+            },
+            4,
+            "https://example.com/4",
+        )?
+        .expect("placeholder");
+        assert_eq!(blocked.title, "Ch 4 (blocked)");
+        assert!(blocked.body.contains("blocked"));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_chapter_skip_and_fail() {
+        let skipped = handle_missing_chapter(
+            EmptyChapterBehavior::Skip,
+            MissingContent::ClientError {
+                title: "Ch 1".to_string(),
+                status: 404,
+            },
+            1,
+            "https://example.com/1",
+        );
+        assert!(matches!(skipped, Ok(None)));
+
+        let failed = handle_missing_chapter(
+            EmptyChapterBehavior::Fail,
+            MissingContent::ClientError {
+                title: "Ch 1".to_string(),
+                status: 404,
+            },
+            1,
+            "https://example.com/1",
+        );
+        assert!(matches!(
+            failed,
+            Err(ScraperError::HttpStatus { status: 404, .. })
+        ));
+
+        let empty = handle_missing_chapter(
+            EmptyChapterBehavior::Fail,
+            MissingContent::Empty {
+                title: "Ch 2".to_string(),
+            },
+            2,
+            "https://example.com/2",
+        );
+        assert!(matches!(
+            empty,
+            Err(ScraperError::EmptyChapter { index: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn short_chapter_words_threshold() {
+        let body = "<p>Sorry, taking a <em>break</em> this week.</p>";
+        assert_eq!(body_word_count(body), 6);
+        assert_eq!(short_chapter_words(body, 0), None);
+        assert_eq!(short_chapter_words(body, 6), None);
+        assert_eq!(short_chapter_words(body, 7), Some(6));
+        assert_eq!(short_chapter_words(body, 50), Some(6));
+    }
+
+    fn chapter(title: &str, index: u32, body: &str) -> Chapter {
+        Chapter {
+            title: title.to_string(),
+            index,
+            body: body.to_string(),
+            reported_word_count: None,
+            url: None,
+        }
+    }
+
+    #[test]
+    fn drop_nav_artifacts_keeps_genuine_short_chapter() {
+        let mut book = Book {
+            title: "T".to_string(),
+            author: "A".to_string(),
+            chapters: vec![
+                chapter("Epilogue", 1, "<p>The end.</p>"),
+                chapter("Next Steps", 2, "<p>Short.</p>"),
+                chapter("Next \u{bb}", 3, "<p>Next chapter</p>"),
+                chapter("Index", 4, "<p>Back to index</p>"),
+            ],
+            ..Default::default()
+        };
+        let dropped = drop_nav_artifacts(&mut book);
+        let kept: Vec<u32> = book.chapters.iter().map(|c| c.index).collect();
+        let dropped: Vec<u32> = dropped.iter().map(|c| c.index).collect();
+        assert_eq!(kept, vec![1, 2]);
+        assert_eq!(dropped, vec![3, 4]);
+    }
+
+    #[test]
+    fn nav_like_title_with_real_body_is_kept() {
+        let long = "<p>This chapter is titled Next but has plenty of story text in it.</p>";
+        assert!(!is_nav_artifact(&chapter("Next", 5, long)));
+        assert!(is_nav_artifact(&chapter("  \u{ab} Previous  ", 6, "")));
+    }
+
+    #[test]
+    fn decode_body_transcodes_windows_1252_meta_charset() {
+        let bytes = b"<html><head><meta charset=\"windows-1252\"></head>\
+<body><p>caf\xe9 \x93quoted\x94</p></body></html>";
+        let text = decode_body(bytes, Some("text/html"), "https://example.com/c/1").unwrap();
+        assert!(
+            text.contains("<p>caf\u{e9} \u{201c}quoted\u{201d}</p>"),
+            "{}",
+            text
+        );
+    }
+
+    #[test]
+    fn decode_body_header_charset_wins_over_meta() {
+        let bytes = b"<meta charset=\"utf-8\"><p>\xe9t\xe9</p>";
+        let text = decode_body(bytes, Some("text/html; charset=ISO-8859-1"), "u").unwrap();
+        assert!(text.contains("<p>\u{e9}t\u{e9}</p>"));
+        let utf8 = "<p>\u{e9}t\u{e9}</p>".as_bytes();
+        assert_eq!(
+            decode_body(utf8, None, "u").unwrap(),
+            "<p>\u{e9}t\u{e9}</p>"
+        );
+    }
+
+    #[test]
+    fn toc_rows_round_trip_through_toc_entries() {
+        let locked = (3, "https://x/3".to_string(), "Three".to_string(), false);
+        let entry = locked.to_entry();
+        assert!(!entry.unlocked);
+        assert_eq!(<(u32, String, String, bool)>::from_entry(&entry), locked);
+        let sh = (4, "https://x/4".to_string(), "Four".to_string());
+        let entry = sh.to_entry();
+        assert!(entry.unlocked);
+        assert_eq!(<(u32, String, String)>::from_entry(&entry), sh);
+    }
+
+    #[test]
+    fn chapter_title_or_toc_replaces_book_title_fallback() {
+        let book = "Mother of Learning";
+        assert_eq!(
+            chapter_title_or_toc("1. Good Morning".to_string(), "1. Good Morning", book, 1),
+            "1. Good Morning"
+        );
+        assert_eq!(
+            chapter_title_or_toc("Mother of Learning".to_string(), "2. Life", book, 2),
+            "2. Life"
+        );
+        assert_eq!(
+            chapter_title_or_toc(" mother of learning ".to_string(), "", book, 3),
+            "Chapter 3"
+        );
+        assert_eq!(
+            chapter_title_or_toc("Wormhole".to_string(), "5. Arc 5", "Worm", 5),
+            "Wormhole"
+        );
+        assert_eq!(
+            chapter_title_or_toc("Interlude".to_string(), "", "", 4),
+            "Interlude"
+        );
+    }
+
+    #[test]
+    fn canonicalize_url_normalizes_messy_royalroad_urls() -> Result<(), ScraperError> {
+        let canonical = "https://www.royalroad.com/fiction/21220";
+        for messy in [
+            canonical,
+            "https://www.royalroad.com/fiction/21220/mother-of-learning/",
+            "https://www.royalroad.com/fiction/21220/old-title",
+            "http://royalroad.com/fiction/21220/mother-of-learning?ref=home&utm_source=x",
+            "  www.royalroad.com/fiction/21220/mother-of-learning/#comments ",
+            "https://WWW.RoyalRoad.com//fiction/21220/mother-of-learning/).",
+        ] {
+            assert_eq!(
+                Site::RoyalRoad.canonicalize_url(messy)?,
+                canonical,
+                "{}",
+                messy
+            );
+        }
+        assert_eq!(
+            Site::RoyalRoad.canonicalize_url(
+                "https://m.royalroad.com/fiction/1/x/chapter/10/one/?fbclid=abc"
+            )?,
+            "https://www.royalroad.com/fiction/1/chapter/10"
+        );
+        for bad in [
+            "https://www.royalroad.com/fictions/best-rated",
+            "https://www.royalroad.com/fiction/abc/x",
+            "https://www.royalroad.com/fiction/1/x/reviews",
+            "ftp://www.royalroad.com/fiction/1/x",
+        ] {
+            let err = Site::RoyalRoad.canonicalize_url(bad).unwrap_err();
+            assert_eq!(err.kind_name(), "invalid_url", "{}", bad);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn canonicalize_url_normalizes_messy_scribblehub_urls() -> Result<(), ScraperError> {
+        let canonical = "https://www.scribblehub.com/series/862913/hp-the-arcane-thief-litrpg";
+        for messy in [
+            "https://www.scribblehub.com/series/862913/hp-the-arcane-thief-litrpg/",
+            "http://m.scribblehub.com/series/862913/hp-the-arcane-thief-litrpg/?ref=rss",
+            "scribblehub.com/series/862913/hp-the-arcane-thief-litrpg/#synopsis",
+        ] {
+            assert_eq!(
+                Site::ScribbleHub.canonicalize_url(messy)?,
+                canonical,
+                "{}",
+                messy
+            );
+        }
+        assert_eq!(
+            Site::ScribbleHub.canonicalize_url(
+                "https://www.scribblehub.com/read/862913-hp-the-arcane-thief-litrpg/chapter/862914/?utm_medium=x"
+            )?,
+            "https://www.scribblehub.com/read/862913-hp-the-arcane-thief-litrpg/chapter/862914"
+        );
+        assert_eq!(
+            Site::ScribbleHub.canonicalize_url("https://sh-mirror.example/series/1/x/?ref=y")?,
+            "https://sh-mirror.example/series/1/x"
+        );
+        for bad in [
+            "https://www.scribblehub.com/profile/1/someone/",
+            "https://www.scribblehub.com/read/862913-x/",
+            "https://www.scribblehub.com/series/x/slug/",
+        ] {
+            let err = Site::ScribbleHub.canonicalize_url(bad).unwrap_err();
+            assert_eq!(err.kind_name(), "invalid_url", "{}", bad);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn canonicalize_url_keeps_bare_ao3_host() -> Result<(), ScraperError> {
+        assert_eq!(
+            Site::Ao3.canonicalize_url(
+                "https://www.archiveofourown.org/works/2080878/?view_full_work=true"
+            )?,
+            "https://archiveofourown.org/works/2080878"
+        );
+        assert_eq!(
+            Site::Ao3
+                .canonicalize_url("archiveofourown.org/works/2080878/chapters/4525509#main")?,
+            "https://archiveofourown.org/works/2080878/chapters/4525509"
+        );
+        let err = Site::Ao3
+            .canonicalize_url("https://archiveofourown.org/users/someone/works")
+            .unwrap_err();
+        assert_eq!(err.kind_name(), "invalid_url");
+        Ok(())
+    }
+
+    #[test]
+    fn canonicalize_url_is_idempotent() -> Result<(), ScraperError> {
+        for (site, url) in [
+            (
+                Site::RoyalRoad,
+                "https://www.royalroad.com/fiction/21220/mother-of-learning",
+            ),
+            (
+                Site::RoyalRoad,
+                "https://www.royalroad.com/fiction/21220/mother-of-learning/chapter/301778/1-good-morning-brother",
+            ),
+            (
+                Site::RoyalRoad,
+                "https://www.royalroad.com/fiction/21220/chapter/301778/1-good-morning-brother",
+            ),
+            (
+                Site::ScribbleHub,
+                "https://www.scribblehub.com/series/862913/hp-the-arcane-thief-litrpg/",
+            ),
+            (
+                Site::ScribbleHub,
+                "https://www.scribblehub.com/read/862913-hp-the-arcane-thief-litrpg/chapter/862914/",
+            ),
+            (
+                Site::Ao3,
+                "https://www.archiveofourown.org/works/2080878/chapters/4525509?view_adult=true",
+            ),
+        ] {
+            let once = site.canonicalize_url(url)?;
+            assert_eq!(site.canonicalize_url(&once)?, once, "{}", url);
+        }
+        assert_eq!(
+            Site::RoyalRoad
+                .canonicalize_url("https://www.royalroad.com/fiction/21220/chapter/301778/")?,
+            "https://www.royalroad.com/fiction/21220/chapter/301778"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn absolutize_image_srcs_resolves_against_chapter_url() {
+        let body = r#"<p><img src="/img/map.png" alt="Map"/></p><p>x <img src="stats.jpg?a=1&amp;b=2" alt=""/></p><p><img src="https://cdn.example/a.gif" alt=""/></p>"#;
+        assert_eq!(
+            absolutize_image_srcs(body, "https://www.royalroad.com/fiction/1/x/chapter/10/one"),
+            r#"<p><img src="https://www.royalroad.com/img/map.png" alt="Map"/></p><p>x <img src="https://www.royalroad.com/fiction/1/x/chapter/10/stats.jpg?a=1&amp;b=2" alt=""/></p><p><img src="https://cdn.example/a.gif" alt=""/></p>"#
+        );
+        assert_eq!(absolutize_image_srcs(body, "not a url"), body);
+    }
+
+    #[test]
+    fn title_from_url_slug_title_cases_slug_after_id() {
+        assert_eq!(
+            title_from_url_slug("https://www.royalroad.com/fiction/21220/mother-of-learning")
+                .as_deref(),
+            Some("Mother Of Learning")
+        );
+        assert_eq!(
+            title_from_url_slug("https://www.scribblehub.com/series/123456/re-zero--arc-2_side/")
+                .as_deref(),
+            Some("Re Zero Arc 2 Side")
+        );
+        assert_eq!(
+            title_from_url_slug("https://www.royalroad.com/fiction/1/2084").as_deref(),
+            Some("2084")
+        );
+        assert_eq!(
+            title_from_url_slug("https://www.royalroad.com/fiction/21220"),
+            None
+        );
+        assert_eq!(
+            title_from_url_slug("https://www.royalroad.com/fiction/x"),
+            None
+        );
+        assert_eq!(title_from_url_slug("not a url"), None);
+    }
+
+    #[test]
+    fn decode_body_malformed_legacy_bytes_is_encoding_error() {
+        let err =
+            decode_body(b"<p>\x81 </p>", Some("text/html; charset=shift_jis"), "u").unwrap_err();
+        assert_eq!(err.kind_name(), "encoding");
+        assert_eq!(err.url(), Some("u"));
+    }
+
+    #[test]
+    fn chapter_or_missing_applies_transform_before_checks() {
+        let strip = |body: &str| body.replace("<p>Read on example.com</p>", "");
+        let options = ScrapeOptions {
+            transform_body: Some(&strip),
+            ..Default::default()
+        };
+        let body = "<p>Text.</p><p>Read on example.com</p>".to_string();
+        let ch = chapter_or_missing("C".to_string(), 1, body, &options).unwrap();
+        assert_eq!(ch.body, "<p>Text.</p>");
+        let only_watermark = "<p>Read on example.com</p>".to_string();
+        assert!(matches!(
+            chapter_or_missing("C".to_string(), 2, only_watermark, &options),
+            Err(MissingContent::Empty { .. })
+        ));
+    }
+
+    #[test]
+    fn check_chapter_order_errors_only_when_strict() {
+        let chapter = |index: u32| Chapter {
+            title: format!("Chapter {}", index),
+            index,
+            body: String::new(),
+            reported_word_count: None,
+            url: None,
+        };
+        let mut book = Book {
+            title: "T".to_string(),
+            author: "A".to_string(),
+            chapters: vec![chapter(1), chapter(2)],
+            ..Default::default()
+        };
+        assert!(check_chapter_order(&book, true).is_ok());
+        book.chapters[1].title = "Chapter 1".to_string();
+        assert!(check_chapter_order(&book, true).is_ok());
+        book.chapters.swap(0, 1);
+        assert!(check_chapter_order(&book, false).is_ok());
+        assert!(matches!(
+            check_chapter_order(&book, true),
+            Err(ScraperError::ChapterOrder { problems }) if problems.contains("chapter 1 follows chapter 2")
+        ));
+    }
+
+    #[test]
+    fn every_error_variant_maps_to_a_kind() {
+        let reqwest_err = || {
+            reqwest::blocking::Client::new()
+                .get("not a url")
+                .build()
+                .unwrap_err()
+        };
+        let s = || "x".to_string();
+        let cases = vec![
+            (
+                ScraperError::InvalidUrl {
+                    input: s(),
+                    reason: s(),
+                },
+                ScraperErrorKind::InvalidInput,
+            ),
+            (
+                ScraperError::UnrecognizedHost { host: s() },
+                ScraperErrorKind::InvalidInput,
+            ),
+            (
+                ScraperError::BlockedHost {
+                    host: s(),
+                    pattern: s(),
+                },
+                ScraperErrorKind::Blocked,
+            ),
+            (
+                ScraperError::Network {
+                    url: s(),
+                    source: reqwest_err(),
+                },
+                ScraperErrorKind::Network,
+            ),
+            (
+                ScraperError::HttpStatus {
+                    status: 404,
+                    url: s(),
+                    context: None,
+                },
+                ScraperErrorKind::HttpStatus,
+            ),
+            (
+                ScraperError::Redirect {
+                    url: s(),
+                    reason: s(),
+                },
+                ScraperErrorKind::Network,
+            ),
+            (
+                ScraperError::Tls {
+                    source: reqwest_err(),
+                },
+                ScraperErrorKind::Network,
+            ),
+            (
+                ScraperError::BodyRead {
+                    source: reqwest_err(),
+                },
+                ScraperErrorKind::Network,
+            ),
+            (
+                ScraperError::ParseStoryPage { message: s() },
+                ScraperErrorKind::Parse,
+            ),
+            (
+                ScraperError::ParseChapter { index: 1, url: s() },
+                ScraperErrorKind::Parse,
+            ),
+            (
+                ScraperError::EmptyChapter { index: 1, url: s() },
+                ScraperErrorKind::Content,
+            ),
+            (
+                ScraperError::ChapterTooShort {
+                    index: 1,
+                    words: 1,
+                    min: 5,
+                    url: s(),
+                },
+                ScraperErrorKind::Content,
+            ),
+            (
+                ScraperError::Encoding {
+                    url: s(),
+                    reason: s(),
+                },
+                ScraperErrorKind::Parse,
+            ),
+            (
+                ScraperError::ChapterListParse { reason: s() },
+                ScraperErrorKind::Parse,
+            ),
+            (ScraperError::EmptyChapterList, ScraperErrorKind::Content),
+            (
+                ScraperError::AccessBlocked { url: s() },
+                ScraperErrorKind::Blocked,
+            ),
+            (
+                ScraperError::StoryNotFound { url: s() },
+                ScraperErrorKind::NotFound,
+            ),
+            (ScraperError::NoChaptersRetrieved, ScraperErrorKind::Content),
+            (
+                ScraperError::ChapterOrder { problems: s() },
+                ScraperErrorKind::Content,
+            ),
+            (
+                ScraperError::LockedChaptersNotAllowed { count: 1 },
+                ScraperErrorKind::Content,
+            ),
+            (
+                ScraperError::IndexCollision {
+                    index: 1,
+                    existing_url: s(),
+                    new_url: s(),
+                },
+                ScraperErrorKind::Resume,
+            ),
+            (ScraperError::Cancelled, ScraperErrorKind::Cancelled),
+        ];
+        for (err, kind) in cases {
+            assert_eq!(err.kind(), kind, "{}", err.kind_name());
+        }
+    }
+
+    fn resumed_book() -> Book {
+        Book {
+            title: "T".to_string(),
+            author: "A".to_string(),
+            chapters: vec![Chapter {
+                title: "Old 2".to_string(),
+                index: 2,
+                body: "<p>old</p>".to_string(),
+                reported_word_count: None,
+                url: Some("https://example.com/chapter/old-2/".to_string()),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stop_early_hands_over_book_before_returning_error() {
+        let book = resumed_book();
+        let saved = std::cell::Cell::new(0);
+        let on_stop = |b: &Book| saved.set(b.chapters.len());
+        let options = ScrapeOptions {
+            on_stop: Some(&on_stop),
+            fail_fast: true,
+            ..Default::default()
+        };
+        let err = stop_early(&book, &options, ScraperError::NoChaptersRetrieved);
+        assert!(matches!(err, ScraperError::NoChaptersRetrieved));
+        assert_eq!(saved.get(), 1);
+    }
+
+    #[test]
+    fn needs_fetch_same_or_unknown_url_is_not_a_collision() {
+        let mut book = resumed_book();
+        let fail = IndexCollisionBehavior::Fail;
+        assert!(needs_fetch(&mut book, 1, "https://example.com/chapter/1", fail).unwrap());
+        assert!(!needs_fetch(&mut book, 2, "https://example.com/chapter/old-2", fail).unwrap());
+        book.chapters[0].url = None;
+        assert!(!needs_fetch(&mut book, 2, "https://example.com/chapter/new-2", fail).unwrap());
+    }
+
+    #[test]
+    fn needs_fetch_collision_policies() {
+        let new_url = "https://example.com/chapter/new-2";
+        let mut book = resumed_book();
+        let keep = IndexCollisionBehavior::KeepExisting;
+        assert!(!needs_fetch(&mut book, 2, new_url, keep).unwrap());
+        assert_eq!(book.chapters.len(), 1);
+
+        let prefer = IndexCollisionBehavior::PreferNew;
+        assert!(needs_fetch(&mut book, 2, new_url, prefer).unwrap());
+        assert!(book.chapters.is_empty());
+
+        let mut book = resumed_book();
+        let err = needs_fetch(&mut book, 2, new_url, IndexCollisionBehavior::Fail).unwrap_err();
+        assert!(matches!(err, ScraperError::IndexCollision { index: 2, .. }));
+        assert_eq!(err.kind_name(), "index_collision");
+        assert_eq!(book.chapters.len(), 1);
+    }
+
+    #[test]
+    fn resumed_run_never_fetches_excluded_titles() {
+        // Checkpoint from an earlier run holds chapter 1; the new TOC adds an author's note
+        // (excluded) and chapter 3. Only chapter 3 is left to fetch, as on a fresh run.
+        let mut book = resumed_book();
+        book.chapters[0].index = 1;
+        book.chapters[0].url = Some("https://example.com/chapter/1".to_string());
+        let mut toc: Vec<(u32, String, String)> = [
+            (1, "chapter/1", "Chapter 1"),
+            (2, "chapter/note", "Author's Note: hiatus"),
+            (3, "chapter/3", "Chapter 2"),
+        ]
+        .into_iter()
+        .map(|(i, path, title)| {
+            (
+                i,
+                format!("https://example.com/{}", path),
+                title.to_string(),
+            )
+        })
+        .collect();
+        let options = ScrapeOptions {
+            initial_book: Some(&book),
+            title_exclude: Some(Regex::new("(?i)author'?s note").unwrap()),
+            ..ScrapeOptions::default()
+        };
+        retain_wanted_chapters(&mut toc, &options, |(index, url, title)| {
+            (*index, url.as_str(), title.as_str())
+        });
+        let mut resumed = book.clone();
+        let to_fetch: Vec<u32> = toc
+            .iter()
+            .filter(|(index, url, _)| {
+                needs_fetch(&mut resumed, *index, url, IndexCollisionBehavior::Fail).unwrap()
+            })
+            .map(|(index, _, _)| *index)
+            .collect();
+        assert_eq!(to_fetch, vec![3]);
+    }
+
+    #[test]
+    fn retain_wanted_chapters_applies_range_and_exclusion() {
+        let mut toc = vec![(1, "One"), (2, "Side Story"), (3, "Three"), (4, "Four")];
+        let options = ScrapeOptions {
+            chapter_range: Some(ChapterSelection {
+                ranges: vec![(2, 3)],
+            }),
+            title_exclude: Some(Regex::new("^Side").unwrap()),
+            ..ScrapeOptions::default()
+        };
+        retain_wanted_chapters(&mut toc, &options, |(index, title)| (*index, "", *title));
+        assert_eq!(toc, vec![(3, "Three")]);
+    }
+
+    #[test]
+    fn chapter_selection_contains_any_range() {
+        let selection = ChapterSelection {
+            ranges: vec![(1, 3), (7, 7), (20, u32::MAX)],
+        };
+        let picked: Vec<u32> = (1..=25).filter(|&i| selection.contains(i)).collect();
+        assert_eq!(picked, vec![1, 2, 3, 7, 20, 21, 22, 23, 24, 25]);
+        assert!(!ChapterSelection { ranges: Vec::new() }.contains(1));
+    }
+
+    #[test]
+    fn retain_wanted_chapters_include_list_by_index_and_url() {
+        let mut toc: Vec<(u32, &str)> = vec![
+            (1, "https://example.com/chapter/1"),
+            (2, "https://example.com/chapter/2"),
+            (3, "https://example.com/chapter/3"),
+        ];
+        let allow = ChapterAllowlist {
+            indices: [1, 9].into_iter().collect(),
+            urls: ["https://example.com/chapter/3".to_string()]
+                .into_iter()
+                .collect(),
+        };
+        let options = ScrapeOptions {
+            include_chapters: Some(allow),
+            ..ScrapeOptions::default()
+        };
+        retain_wanted_chapters(&mut toc, &options, |(index, url)| (*index, *url, ""));
+        let kept: Vec<u32> = toc.iter().map(|(index, _)| *index).collect();
+        assert_eq!(kept, vec![1, 3]);
+    }
+
+    #[test]
+    fn looks_truncated_distinguishes_cut_off_pages() {
+        let empty = MissingContent::Empty {
+            title: "T".to_string(),
+        };
+        assert!(looks_truncated(
+            &empty,
+            "<html><body><div class=\"chapter\"><p>Half a sen"
+        ));
+        assert!(!looks_truncated(
+            &empty,
+            "<html><body><div></div></body></HTML>\n"
+        ));
+        assert!(looks_truncated(
+            &MissingContent::Unparseable,
+            "<html></html>"
+        ));
+        let removed = MissingContent::ClientError {
+            title: "T".to_string(),
+            status: 404,
+        };
+        assert!(!looks_truncated(&removed, ""));
+    }
 }
@@ -2,18 +2,33 @@
 //!
 //! Cloudflare: cookie jar and browser-like User-Agent are used; captcha is not handled (see README.md, Known edge cases).
 
-use crate::model::{Book, Chapter};
+use crate::formats::{normalize_body_html, BodyOptions};
+use crate::model::{clean_tags, Book, Chapter};
 use crate::scraper::error::ScraperError;
 use crate::scraper::{
-    strip_title_site_suffix, EmptyChapterBehavior, LockedChapterBehavior, PoliteClient,
-    ScrapeOptions, Scraper,
+    absolutize_image_srcs, chapter_or_missing, chapter_title_or_toc, handle_missing_chapter,
+    looks_truncated, needs_fetch, record_chapter, report_toc, response_text,
+    retain_wanted_chapters, stat_key, stop_early, strip_title_site_suffix, title_from_page_title,
+    title_from_url_slug, transform_body, usable_cached_toc, ChapterSource, EmptyChapterBehavior,
+    LockedChapterBehavior, MissingContent, PoliteClient, Prefetch, ScrapeOptions, Scraper, TocRow,
+    UNKNOWN_AUTHOR,
 };
+use regex::Regex;
 use reqwest::Url;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::LazyLock;
 
 const ROYALROAD_BASE: &str = "https://www.royalroad.com";
 
+/// Consecutive chapter API failures after which `--source api` gives up on the endpoint for the
+/// rest of the run; each failed chapter is read from its HTML page instead.
+const API_FAILURE_LIMIT: u32 = 3;
+
+/// Site suffixes stripped from `<title>` / og:title.
+const TITLE_SUFFIXES: &[&str] = &[" _ Royal Road", " - Royal Road", " | Royal Road"];
+
 /// Parse a CSS selector or return a parse error (avoids panics from Selector::parse).
 fn parse_selector(sel: &str) -> Result<Selector, ScraperError> {
     Selector::parse(sel).map_err(|e| ScraperError::ParseStoryPage {
@@ -43,6 +58,62 @@ fn default_true() -> bool {
     true
 }
 
+/// Shape of the JSON chapter endpoint response: chapter title and content HTML.
+#[derive(Debug, Deserialize)]
+struct ApiChapter {
+    title: String,
+    content: String,
+}
+
+/// JSON chapter endpoint for a chapter page URL (`.../chapter/{id}/...` -> `/api/chapter/{id}`).
+/// Not a documented public API; callers fall back to HTML when it fails.
+fn api_chapter_url(chapter_url: &str) -> Option<String> {
+    let parsed = Url::parse(chapter_url).ok()?;
+    let mut segments = parsed.path_segments()?;
+    segments.find(|s| *s == "chapter")?;
+    let id = segments.next().filter(|s| s.parse::<u64>().is_ok())?;
+    Some(
+        parsed
+            .join(&format!("/api/chapter/{}", id))
+            .ok()?
+            .to_string(),
+    )
+}
+
+/// Parse a JSON chapter response into (title, body) with the same minimal `<p>` body as the HTML
+/// path: top-level blocks allowed by `tags` (`<p>` by default), with inline tags filtered by `tags`.
+fn parse_api_chapter(
+    json: &str,
+    index: u32,
+    url: &str,
+    tags: &BodyOptions,
+    hidden_classes: &[Regex],
+) -> Result<(String, String), ScraperError> {
+    let api: ApiChapter = serde_json::from_str(json).map_err(|_| ScraperError::ParseChapter {
+        index,
+        url: url.to_string(),
+    })?;
+    // Direct children only, like the HTML path, so nested blocks are not emitted twice.
+    let fragment = Html::parse_fragment(&api.content);
+    let img_sel = parse_selector("img")?;
+    let visible: String = fragment
+        .root_element()
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|el| {
+            !el.text().collect::<String>().trim().is_empty() || el.select(&img_sel).next().is_some()
+        })
+        .filter(|el| !is_hidden(*el, &BTreeSet::new(), hidden_classes))
+        .map(|el| el.html())
+        .collect();
+    let body = normalize_body_html(&visible, tags);
+    let body = absolutize_image_srcs(&body, url);
+    let title = Some(api.title.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| format!("Chapter {}", index));
+    Ok((title, body))
+}
+
 /// Require fiction URL (no /chapter/ in path). Returns the URL as-is if valid.
 fn ensure_fiction_url(url: &str) -> Result<String, ScraperError> {
     let parsed = Url::parse(url).map_err(|e| ScraperError::InvalidUrl {
@@ -58,7 +129,21 @@ fn ensure_fiction_url(url: &str) -> Result<String, ScraperError> {
     Ok(url.to_string())
 }
 
-/// Check response status and read body as UTF-8. Returns body or ScraperError.
+/// Require a chapter URL (path contains /chapter/). Used only with `--single-chapter`.
+fn ensure_chapter_url(url: &str) -> Result<String, ScraperError> {
+    let parsed = Url::parse(url).map_err(|e| ScraperError::InvalidUrl {
+        input: url.to_string(),
+        reason: e.to_string(),
+    })?;
+    if !parsed.path().contains("/chapter/") {
+        return Err(ScraperError::ParseStoryPage {
+            message: "Expected a chapter URL with --single-chapter, e.g. https://www.royalroad.com/fiction/21220/mother-of-learning/chapter/301778/1-good-morning-brother".to_string(),
+        });
+    }
+    Ok(url.to_string())
+}
+
+/// Check response status and read body as text (see [response_text]). Returns body or ScraperError.
 fn check_response(
     response: reqwest::blocking::Response,
     url: &str,
@@ -72,14 +157,16 @@ fn check_response(
             context: context.map(String::from),
         });
     }
-    response
-        .text()
-        .map_err(|e| ScraperError::BodyRead { source: e })
+    response_text(response, url)
 }
 
 /// Extract metadata from fiction page HTML: JSON-LD Book first, then DOM fallback.
+/// A missing title is replaced by `url_title` (`--title-from-url`) when given. With
+/// `allow_missing_metadata`, a missing title falls back to `<title>` and a missing author to [UNKNOWN_AUTHOR].
 fn parse_metadata(
     html: &str,
+    allow_missing_metadata: bool,
+    url_title: Option<String>,
 ) -> Result<(String, String, Option<String>, Option<String>), ScraperError> {
     // Prefer JSON-LD @type "Book"
     if let Some(script) = html.find("<script type=\"application/ld+json\">") {
@@ -146,14 +233,56 @@ fn parse_metadata(
         .and_then(|e| e.value().attr("content").map(String::from))
         .filter(|s| !s.is_empty());
 
+    let title = title.or(url_title);
     match (title, author) {
         (Some(t), Some(a)) => Ok((t, a, description, cover_url)),
+        (title, author) if allow_missing_metadata => {
+            let title = title
+                .or_else(|| title_from_page_title(&doc, TITLE_SUFFIXES))
+                .ok_or_else(|| ScraperError::ParseStoryPage {
+                    message: "missing title (no selector or <title> matched)".to_string(),
+                })?;
+            let author = author.unwrap_or_else(|| UNKNOWN_AUTHOR.to_string());
+            Ok((title, author, description, cover_url))
+        }
         _ => Err(ScraperError::ParseStoryPage {
             message: "missing title or author (selector or structure may have changed)".to_string(),
         }),
     }
 }
 
+/// Popularity stats from the fiction page stats block: `li` labels ending in ":" ("Total Views :")
+/// each followed by a value `li`. Star ratings have no text and are left out. Empty when the block
+/// is missing.
+fn parse_stats(html: &str) -> Result<BTreeMap<String, String>, ScraperError> {
+    let doc = Html::parse_document(html);
+    let li_sel = parse_selector("div.stats-content li")?;
+    let items: Vec<String> = doc
+        .select(&li_sel)
+        .map(|li| li.text().collect::<String>().trim().to_string())
+        .collect();
+    let mut stats = BTreeMap::new();
+    for pair in items.windows(2) {
+        let Some(label) = pair[0].strip_suffix(':') else {
+            continue;
+        };
+        if !pair[1].is_empty() && !pair[1].ends_with(':') {
+            stats.insert(stat_key(label), pair[1].clone());
+        }
+    }
+    Ok(stats)
+}
+
+/// Genre tags from the fiction page (`span.tags a`, e.g. "Fantasy", "LitRPG"), cleaned with
+/// [clean_tags]. Empty when the block is missing.
+fn parse_tags(html: &str) -> Result<Vec<String>, ScraperError> {
+    let doc = Html::parse_document(html);
+    let tag_sel = parse_selector("span.tags a")?;
+    Ok(clean_tags(
+        doc.select(&tag_sel).map(|a| a.text().collect::<String>()),
+    ))
+}
+
 fn strip_html_tags(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     let mut in_tag = false;
@@ -168,9 +297,38 @@ fn strip_html_tags(s: &str) -> String {
     out.replace("\n\n\n", "\n\n").trim().to_string()
 }
 
+/// Royal Road "not found" page served with HTTP 200 for deleted or private fictions: no
+/// `window.chapters` script, and a not-found `<title>` or body message.
+fn is_not_found_page(html: &str) -> bool {
+    if html.contains("window.chapters") {
+        return false;
+    }
+    let doc = Html::parse_document(html);
+    let title = title_from_page_title(&doc, TITLE_SUFFIXES)
+        .unwrap_or_default()
+        .to_lowercase();
+    if title.contains("not found") || title.starts_with("404") {
+        return true;
+    }
+    let text = doc.root_element().text().collect::<String>().to_lowercase();
+    NOT_FOUND_MESSAGES.iter().any(|m| text.contains(m))
+}
+
+/// Lowercased body messages that mark the Royal Road "not found" page.
+const NOT_FOUND_MESSAGES: &[&str] = &[
+    "could not be found",
+    "couldn't find the fiction",
+    "this fiction has been deleted",
+    "fiction is not available",
+];
+
 /// Extract window.chapters array from script. Returns (index_1based, full_url, title, is_unlocked).
-/// Relative URLs resolved against ROYALROAD_BASE.
-fn parse_toc_with_locked(html: &str) -> Result<Vec<(u32, String, String, bool)>, ScraperError> {
+/// Relative URLs are resolved against the origin of `page_url` (the fiction page), so mirrors and
+/// test servers keep chapter requests on the same host.
+fn parse_toc_with_locked(
+    html: &str,
+    page_url: &str,
+) -> Result<Vec<(u32, String, String, bool)>, ScraperError> {
     let needle = "window.chapters = ";
     let start = html
         .find(needle)
@@ -193,9 +351,11 @@ fn parse_toc_with_locked(html: &str) -> Result<Vec<(u32, String, String, bool)>,
         serde_json::from_str(array_slice).map_err(|e| ScraperError::ChapterListParse {
             reason: e.to_string(),
         })?;
-    let base = Url::parse(ROYALROAD_BASE).map_err(|e| ScraperError::ChapterListParse {
-        reason: e.to_string(),
-    })?;
+    let base = Url::parse(page_url)
+        .and_then(|u| u.join("/"))
+        .map_err(|e| ScraperError::ChapterListParse {
+            reason: e.to_string(),
+        })?;
     let mut toc = Vec::with_capacity(chapters.len());
     for ch in chapters {
         let full_url = base
@@ -214,7 +374,7 @@ fn parse_toc_with_locked(html: &str) -> Result<Vec<(u32, String, String, bool)>,
 /// Like parse_toc_with_locked but only returns unlocked chapters (used by tests).
 #[allow(dead_code)]
 fn parse_toc(html: &str) -> Result<Vec<(u32, String, String)>, ScraperError> {
-    let toc = parse_toc_with_locked(html)?;
+    let toc = parse_toc_with_locked(html, ROYALROAD_BASE)?;
     let unlocked: Vec<_> = toc
         .into_iter()
         .filter(|(_, _, _, u)| *u)
@@ -226,6 +386,18 @@ fn parse_toc(html: &str) -> Result<Vec<(u32, String, String)>, ScraperError> {
     Ok(unlocked)
 }
 
+/// Order in which TOC entries are fetched. With `unlocked_first`, unlocked chapters come before locked ones
+/// (stable, so index order is kept within each group); the book's index order is restored by the per-push sort.
+fn fetch_order(
+    mut toc: Vec<(u32, String, String, bool)>,
+    unlocked_first: bool,
+) -> Vec<(u32, String, String, bool)> {
+    if unlocked_first {
+        toc.sort_by_key(|(_, _, _, is_unlocked)| !*is_unlocked);
+    }
+    toc
+}
+
 /// Find the matching closing bracket for the first '[' in s, skipping content inside JSON strings.
 fn extract_json_array_with_strings(s: &str) -> Option<&str> {
     let start = s.find('[')?;
@@ -262,8 +434,102 @@ fn extract_json_array_with_strings(s: &str) -> Option<&str> {
     None
 }
 
-/// Parse chapter page HTML for title and body. Body is direct child <p> of div.chapter-inner.chapter-content.
-fn parse_chapter_page(html: &str, index: u32, url: &str) -> Result<(String, String), ScraperError> {
+static CSS_COMMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)/\*.*?\*/").expect("static regex"));
+/// A selector that is one class and nothing else, e.g. `.cjOTk3NmY1`.
+static SINGLE_CLASS_SELECTOR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\.(-?[_A-Za-z][-_A-Za-z0-9]*)$").expect("static regex"));
+
+/// Class names that the page's own `<style>` blocks hide (`.name { ...display: none... }`). Royal
+/// Road hides its anti-theft paragraphs ("this story has been stolen...") this way, under random
+/// class names that change from page to page. Only top-level rules whose selectors are all a
+/// single class count; rules inside `@media` and other at-rule blocks, and compound selectors
+/// such as `.a .b`, are ignored.
+fn style_hidden_classes(doc: &Html) -> Result<BTreeSet<String>, ScraperError> {
+    let style_sel = parse_selector("style")?;
+    let mut hidden = BTreeSet::new();
+    for style in doc.select(&style_sel) {
+        let css: String = style.text().collect();
+        for (selectors, declarations) in top_level_css_rules(&CSS_COMMENT.replace_all(&css, "")) {
+            let declarations: String = declarations.split_whitespace().collect();
+            if !declarations.to_ascii_lowercase().contains("display:none") {
+                continue;
+            }
+            let names: Option<Vec<&str>> = selectors
+                .split(',')
+                .map(|sel| {
+                    SINGLE_CLASS_SELECTOR
+                        .captures(sel.trim())
+                        .and_then(|c| c.get(1))
+                })
+                .map(|m| m.map(|m| m.as_str()))
+                .collect();
+            hidden.extend(names.into_iter().flatten().map(str::to_string));
+        }
+    }
+    Ok(hidden)
+}
+
+/// (selectors, declarations) of each top-level rule in `css`. At-rules (`@media { ... }`,
+/// `@import ...;`) are skipped along with everything nested in them.
+fn top_level_css_rules(css: &str) -> Vec<(&str, &str)> {
+    let mut rules = Vec::new();
+    let mut depth = 0usize;
+    let mut prelude_start = 0;
+    let mut body_start = 0;
+    let mut at_rule = false;
+    for (i, c) in css.char_indices() {
+        match c {
+            '{' => {
+                if depth == 0 {
+                    at_rule = css[prelude_start..i].trim_start().starts_with('@');
+                    body_start = i + 1;
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    if !at_rule {
+                        rules.push((&css[prelude_start..body_start - 1], &css[body_start..i]));
+                    }
+                    prelude_start = i + 1;
+                }
+            }
+            ';' if depth == 0 => prelude_start = i + 1,
+            _ => {}
+        }
+    }
+    rules
+}
+
+/// A chapter block the reader never sees: inline `display: none`, a class in `style_hidden`
+/// (see [style_hidden_classes]), or a class matching one of `patterns` (config
+/// `royalroad_hidden_classes`).
+fn is_hidden(el: ElementRef<'_>, style_hidden: &BTreeSet<String>, patterns: &[Regex]) -> bool {
+    let inline: String = el
+        .value()
+        .attr("style")
+        .unwrap_or("")
+        .split_whitespace()
+        .collect();
+    inline.to_ascii_lowercase().contains("display:none")
+        || el
+            .value()
+            .classes()
+            .any(|c| style_hidden.contains(c) || patterns.iter().any(|p| p.is_match(c)))
+}
+
+/// Parse chapter page HTML for title and body. Body is the direct children of
+/// div.chapter-inner.chapter-content whose tags are in `tags` (`<p>` by default), minus hidden
+/// anti-theft blocks (see [is_hidden]).
+fn parse_chapter_page(
+    html: &str,
+    index: u32,
+    url: &str,
+    tags: &BodyOptions,
+    hidden_classes: &[Regex],
+) -> Result<(String, String), ScraperError> {
     let doc = Html::parse_document(html);
 
     let h1_sel = parse_selector("h1.font-white.break-word")?;
@@ -278,24 +544,14 @@ fn parse_chapter_page(html: &str, index: u32, url: &str) -> Result<(String, Stri
             doc.select(&og_title_sel)
                 .next()
                 .and_then(|e| e.value().attr("content"))
-                .map(|s| {
-                    strip_title_site_suffix(
-                        s.trim(),
-                        &[" _ Royal Road", " - Royal Road", " | Royal Road"],
-                    )
-                })
+                .map(|s| strip_title_site_suffix(s.trim(), TITLE_SUFFIXES))
                 .filter(|s| !s.is_empty())
         })
         .or_else(|| {
             doc.select(&title_sel)
                 .next()
                 .and_then(|e| e.text().next())
-                .map(|t| {
-                    strip_title_site_suffix(
-                        t.trim(),
-                        &[" _ Royal Road", " - Royal Road", " | Royal Road"],
-                    )
-                })
+                .map(|t| strip_title_site_suffix(t.trim(), TITLE_SUFFIXES))
                 .filter(|s| !s.is_empty())
         })
         .unwrap_or_else(|| format!("Chapter {}", index));
@@ -309,16 +565,20 @@ fn parse_chapter_page(html: &str, index: u32, url: &str) -> Result<(String, Stri
         });
     }
 
-    // Direct child <p> only; ignore obfuscated classes. Output minimal HTML <p>...</p>.
-    let p_sel = parse_selector("div.chapter-inner.chapter-content > p")?;
+    // Direct children only (allowed block tags, <p> by default), without the hidden ones.
+    let style_hidden = style_hidden_classes(&doc)?;
     let body = doc
-        .select(&p_sel)
-        .map(|el| {
-            let text = el.text().collect::<String>().trim().to_string();
-            format!("<p>{}</p>", html_escape_inner(&text))
+        .select(&container_sel)
+        .map(|container| {
+            let visible: String = container
+                .children()
+                .filter_map(ElementRef::wrap)
+                .filter(|el| !is_hidden(*el, &style_hidden, hidden_classes))
+                .map(|el| el.html())
+                .collect();
+            normalize_body_html(&visible, tags)
         })
-        .collect::<Vec<_>>()
-        .join("");
+        .collect::<String>();
     if body.is_empty() {
         return Err(ScraperError::ParseChapter {
             index,
@@ -326,20 +586,93 @@ fn parse_chapter_page(html: &str, index: u32, url: &str) -> Result<(String, Stri
         });
     }
 
-    Ok((title, body))
+    Ok((title, absolutize_image_srcs(&body, url)))
 }
 
-fn html_escape_inner(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
+/// Story title and author from the fiction header on a chapter page. Author falls back to [UNKNOWN_AUTHOR].
+fn parse_chapter_page_story(html: &str) -> Result<(Option<String>, String), ScraperError> {
+    let doc = Html::parse_document(html);
+    let title_sel = parse_selector("div.fic-header h2")?;
+    let author_sel = parse_selector("div.fic-header h3 a")?;
+    let first_text = |sel: &Selector| {
+        doc.select(sel)
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    let title = first_text(&title_sel);
+    let author = first_text(&author_sel).unwrap_or_else(|| UNKNOWN_AUTHOR.to_string());
+    Ok((title, author))
 }
 
 impl<'a> RoyalRoadScraper<'a> {
     pub fn new(client: &'a mut PoliteClient) -> Self {
         Self { client }
     }
+
+    /// Fetch one chapter from the JSON endpoint. Errors (including an empty body) mean "use HTML instead".
+    fn fetch_api_chapter(
+        &mut self,
+        chapter_url: &str,
+        index: u32,
+        tags: &BodyOptions,
+        hidden_classes: &[Regex],
+    ) -> Result<(String, String), ScraperError> {
+        let api_url = api_chapter_url(chapter_url).ok_or_else(|| ScraperError::ParseChapter {
+            index,
+            url: chapter_url.to_string(),
+        })?;
+        let response = self
+            .client
+            .get_with_retry(&api_url)
+            .map_err(|e| ScraperError::from_request(api_url.clone(), e))?;
+        let json = check_response(response, &api_url, Some("chapter API"))?;
+        let (title, body) = parse_api_chapter(&json, index, &api_url, tags, hidden_classes)?;
+        if body.is_empty() {
+            return Err(ScraperError::EmptyChapter {
+                index,
+                url: api_url,
+            });
+        }
+        Ok((title, body))
+    }
+
+    /// `--single-chapter`: fetch one chapter page and wrap it in a one-chapter book, bypassing the TOC.
+    /// The book title is the fiction title from the page header, or the chapter title if absent.
+    fn scrape_single_chapter(
+        &mut self,
+        url: &str,
+        options: &ScrapeOptions<'_>,
+    ) -> Result<Book, ScraperError> {
+        let chapter_url = ensure_chapter_url(url)?;
+        let response = self
+            .client
+            .get_with_retry(&chapter_url)
+            .map_err(|e| ScraperError::from_request(chapter_url.clone(), e))?;
+        let html = check_response(response, &chapter_url, Some("chapter page"))?;
+        let (chapter_title, body) = parse_chapter_page(
+            &html,
+            1,
+            &chapter_url,
+            &options.allowed_tags,
+            &options.hidden_classes,
+        )?;
+        let body = transform_body(body, options);
+        let (story_title, author) = parse_chapter_page_story(&html)?;
+        Ok(Book {
+            title: story_title.unwrap_or_else(|| chapter_title.clone()),
+            author,
+            chapters: vec![Chapter {
+                title: chapter_title,
+                index: 1,
+                body,
+                reported_word_count: None,
+                url: Some(chapter_url.clone()),
+            }],
+            source_url: Some(chapter_url),
+            ..Default::default()
+        })
+    }
 }
 
 impl Scraper for RoyalRoadScraper<'_> {
@@ -348,18 +681,33 @@ impl Scraper for RoyalRoadScraper<'_> {
         url: &str,
         options: &ScrapeOptions<'_>,
     ) -> Result<Book, ScraperError> {
+        if options.single_chapter {
+            return self.scrape_single_chapter(url, options);
+        }
         let fiction_url = ensure_fiction_url(url)?;
 
-        let response =
-            self.client
-                .get_with_retry(&fiction_url)
-                .map_err(|e| ScraperError::Network {
-                    url: fiction_url.clone(),
-                    source: e,
-                })?;
-        let html = check_response(response, &fiction_url, Some("story page"))?;
-
-        let mut toc = parse_toc_with_locked(&html)?;
+        // With a usable cached TOC the story page is not fetched; `html` stays empty, which is
+        // fine because metadata then comes from `initial_book` and stats are off.
+        let (mut toc, html) = match usable_cached_toc(options) {
+            Some(cached) => {
+                let toc: Vec<(u32, String, String, bool)> =
+                    cached.iter().map(TocRow::from_entry).collect();
+                (toc, String::new())
+            }
+            None => {
+                let response = self
+                    .client
+                    .get_with_retry(&fiction_url)
+                    .map_err(|e| ScraperError::from_request(fiction_url.clone(), e))?;
+                let html = check_response(response, &fiction_url, Some("story page"))?;
+                if is_not_found_page(&html) {
+                    return Err(ScraperError::StoryNotFound { url: fiction_url });
+                }
+                let toc = parse_toc_with_locked(&html, &fiction_url)?;
+                report_toc(options, &toc);
+                (toc, html)
+            }
+        };
         let locked_count = toc.iter().filter(|(_, _, _, u)| !*u).count();
         if locked_count > 0
             && options
@@ -373,14 +721,21 @@ impl Scraper for RoyalRoadScraper<'_> {
         }
 
         let total = toc.len() as u32;
-        if let Some((from, to)) = options.chapter_range {
-            toc.retain(|(index, _, _, _)| *index >= from && *index <= to);
-        }
+        retain_wanted_chapters(&mut toc, options, |(index, url, title, _)| {
+            (*index, url.as_str(), title.as_str())
+        });
 
         let mut book: Book = if let Some(init) = options.initial_book {
             init.clone()
         } else {
-            let (title, author, description, cover_url) = parse_metadata(&html)?;
+            let (title, author, description, cover_url) = parse_metadata(
+                &html,
+                options.allow_missing_metadata,
+                options
+                    .title_from_url
+                    .then(|| title_from_url_slug(&fiction_url))
+                    .flatten(),
+            )?;
             Book {
                 title,
                 author,
@@ -388,15 +743,22 @@ impl Scraper for RoyalRoadScraper<'_> {
                 cover_url,
                 chapters: Vec::with_capacity(toc.len()),
                 source_url: Some(fiction_url),
+                ..Default::default()
             }
         };
+        if options.stats {
+            book.stats = parse_stats(&html)?;
+        }
+        if book.tags.is_empty() {
+            book.tags = parse_tags(&html)?;
+        }
 
         if options.toc_only {
             let lb = options
                 .locked_behavior
                 .unwrap_or(LockedChapterBehavior::Skip);
-            for (index, _chapter_url, title, is_unlocked) in toc {
-                if book.chapters.iter().any(|c| c.index == index) {
+            for (index, chapter_url, title, is_unlocked) in toc {
+                if !needs_fetch(&mut book, index, &chapter_url, options.index_collision)? {
                     continue;
                 }
                 if !is_unlocked {
@@ -407,6 +769,8 @@ impl Scraper for RoyalRoadScraper<'_> {
                                 title: format!("{} (locked)", title),
                                 index,
                                 body: String::new(),
+                                reported_word_count: None,
+                                url: Some(chapter_url),
                             });
                         }
                         LockedChapterBehavior::Fail => {}
@@ -416,6 +780,8 @@ impl Scraper for RoyalRoadScraper<'_> {
                         title,
                         index,
                         body: String::new(),
+                        reported_word_count: None,
+                        url: Some(chapter_url),
                     });
                 }
             }
@@ -424,12 +790,20 @@ impl Scraper for RoyalRoadScraper<'_> {
         }
 
         let mut done = 0u32;
-        for (index, chapter_url, title, is_unlocked) in toc {
-            if book.chapters.iter().any(|c| c.index == index) {
+        let mut use_api = options.chapter_source == ChapterSource::Api;
+        let mut api_failures = 0u32;
+        let chapters = fetch_order(toc, options.unlocked_first);
+        let mut prefetch = Prefetch::new(options.concurrency);
+        'chapters: for (pos, (index, chapter_url, title, is_unlocked)) in
+            chapters.clone().into_iter().enumerate()
+        {
+            if !needs_fetch(&mut book, index, &chapter_url, options.index_collision)
+                .map_err(|e| stop_early(&book, options, e))?
+            {
                 continue;
             }
             if options.cancel_check.map(|c| c()).unwrap_or(false) {
-                return Err(ScraperError::Cancelled);
+                return Err(stop_early(&book, options, ScraperError::Cancelled));
             }
             if !is_unlocked {
                 match options
@@ -438,139 +812,187 @@ impl Scraper for RoyalRoadScraper<'_> {
                 {
                     LockedChapterBehavior::Skip => continue,
                     LockedChapterBehavior::Placeholder => {
-                        let placeholder_title = format!("{} (locked)", title);
-                        let placeholder_body =
-                            "<p>This chapter is locked (premium) and could not be retrieved.</p>"
-                                .to_string();
-                        book.chapters.push(Chapter {
-                            title: placeholder_title,
+                        let placeholder = Chapter {
+                            title: format!("{} (locked)", title),
                             index,
-                            body: placeholder_body,
-                        });
-                        book.chapters.sort_by_key(|c| c.index);
-                        done += 1;
-                        if let Some(ref p) = options.progress {
-                            p(done, total);
-                        }
-                        if let Some(ref cb) = options.on_checkpoint {
-                            cb(&book);
-                        }
+                            body: "<p>This chapter is locked (premium) and could not be retrieved.</p>"
+                                .to_string(),
+                            reported_word_count: None,
+                            url: None,
+                        };
+                        record_chapter(
+                            &mut book,
+                            placeholder,
+                            &chapter_url,
+                            &mut done,
+                            total,
+                            options,
+                        );
                         continue;
                     }
                     LockedChapterBehavior::Fail => {
-                        return Err(ScraperError::LockedChaptersNotAllowed {
+                        let err = ScraperError::LockedChaptersNotAllowed {
                             count: locked_count,
-                        });
+                        };
+                        return Err(stop_early(&book, options, err));
                     }
                 }
             }
 
-            let response = match self.client.get_with_retry(&chapter_url) {
-                Ok(r) => r,
-                Err(e) => {
-                    eprintln!(
-                        "Chapter {}: network error at {}: {}. Skipped.",
-                        index, chapter_url, e
-                    );
-                    continue;
-                }
-            };
-
-            if !response.status().is_success() {
-                eprintln!(
-                    "Chapter {}: HTTP {} at {}. Skipped.",
-                    index,
-                    response.status().as_u16(),
-                    chapter_url
-                );
-                continue;
-            }
-
-            let chapter_html = match response.text() {
-                Ok(t) => t,
-                Err(e) => {
-                    eprintln!("Chapter {}: failed to read body: {}. Skipped.", index, e);
-                    continue;
-                }
-            };
-
             let empty_behavior = options
                 .empty_chapter_behavior
                 .unwrap_or(EmptyChapterBehavior::Skip);
-            match parse_chapter_page(&chapter_html, index, &chapter_url) {
-                Ok((parsed_title, body)) => {
-                    if body.is_empty() {
-                        match empty_behavior {
-                            EmptyChapterBehavior::Skip => {
-                                eprintln!(
-                                    "Chapter {} returned no content at {}. Skipped.",
-                                    index, chapter_url
-                                );
-                                continue;
-                            }
-                            EmptyChapterBehavior::Placeholder => {
-                                book.chapters.push(Chapter {
-                                    title: format!("{} (no content)", parsed_title),
-                                    index,
-                                    body: "<p>This chapter returned no content.</p>".to_string(),
-                                });
-                                book.chapters.sort_by_key(|c| c.index);
-                                done += 1;
-                                if let Some(ref p) = options.progress {
-                                    p(done, total);
-                                }
-                                if let Some(ref cb) = options.on_checkpoint {
-                                    cb(&book);
-                                }
-                            }
-                            EmptyChapterBehavior::Fail => {
-                                return Err(ScraperError::EmptyChapter {
-                                    index,
-                                    url: chapter_url.clone(),
-                                });
-                            }
+            if use_api {
+                match self.fetch_api_chapter(
+                    &chapter_url,
+                    index,
+                    &options.allowed_tags,
+                    &options.hidden_classes,
+                ) {
+                    Ok((api_title, body)) => {
+                        api_failures = 0;
+                        let chapter = chapter_or_missing(api_title, index, body, options)
+                            .map(Some)
+                            .or_else(|m| {
+                                handle_missing_chapter(empty_behavior, m, index, &chapter_url)
+                            })
+                            .map_err(|e| stop_early(&book, options, e))?;
+                        if let Some(ch) = chapter {
+                            record_chapter(&mut book, ch, &chapter_url, &mut done, total, options);
                         }
                         continue;
                     }
-                    book.chapters.push(Chapter {
-                        title: parsed_title,
-                        index,
-                        body,
-                    });
-                    book.chapters.sort_by_key(|c| c.index);
-                    done += 1;
-                    if let Some(ref p) = options.progress {
-                        p(done, total);
+                    Err(e) => {
+                        api_failures += 1;
+                        use_api = api_failures < API_FAILURE_LIMIT;
+                        let rest = if use_api {
+                            ""
+                        } else {
+                            " and the rest of this run"
+                        };
+                        eprintln!(
+                            "Warning: chapter API unavailable ({}). Using the HTML page for chapter {}{}.",
+                            e, index, rest
+                        );
                     }
-                    if let Some(ref cb) = options.on_checkpoint {
-                        cb(&book);
+                }
+            }
+
+            let upcoming = chapters[pos + 1..]
+                .iter()
+                .filter(|(_, _, _, unlocked)| *unlocked)
+                .map(|(i, u, _, _)| (*i, u.as_str()));
+            prefetch.fill(self.client, &book, &chapter_url, upcoming);
+            let mut parse_retries_left = options.chapter_parse_retries;
+            let missing = loop {
+                let response = match prefetch.get(self.client, &chapter_url) {
+                    Ok(r) => r,
+                    Err(e) if options.fail_fast => {
+                        return Err(stop_early(
+                            &book,
+                            options,
+                            ScraperError::from_request(chapter_url, e),
+                        ));
                     }
+                    Err(e) => {
+                        eprintln!(
+                            "Chapter {}: network error at {}: {}. Skipped.",
+                            index, chapter_url, e
+                        );
+                        continue 'chapters;
+                    }
+                };
+
+                let status = response.status();
+                if status.is_client_error() {
+                    let missing = MissingContent::ClientError {
+                        title,
+                        status: status.as_u16(),
+                    };
+                    if let Some(ch) =
+                        handle_missing_chapter(empty_behavior, missing, index, &chapter_url)
+                            .map_err(|e| stop_early(&book, options, e))?
+                    {
+                        record_chapter(&mut book, ch, &chapter_url, &mut done, total, options);
+                    }
+                    continue 'chapters;
                 }
-                Err(ScraperError::ParseChapter { index: pi, url: u }) => match empty_behavior {
-                    EmptyChapterBehavior::Skip => {
-                        eprintln!("Chapter {}: could not parse content at {}. Skipped.", pi, u);
+                if !status.is_success() {
+                    if options.fail_fast {
+                        return Err(stop_early(
+                            &book,
+                            options,
+                            ScraperError::HttpStatus {
+                                status: status.as_u16(),
+                                url: chapter_url,
+                                context: Some(format!("chapter {}", index)),
+                            },
+                        ));
                     }
-                    EmptyChapterBehavior::Placeholder => {
-                        book.chapters.push(Chapter {
-                                title: format!("Chapter {} (unable to parse)", pi),
-                                index: pi,
-                                body: "<p>This chapter could not be parsed (missing content container).</p>"
-                                    .to_string(),
-                            });
-                        book.chapters.sort_by_key(|c| c.index);
-                        done += 1;
-                        if let Some(ref p) = options.progress {
-                            p(done, total);
-                        }
-                        if let Some(ref cb) = options.on_checkpoint {
-                            cb(&book);
-                        }
+                    eprintln!(
+                        "Chapter {}: HTTP {} at {}. Skipped.",
+                        index,
+                        status.as_u16(),
+                        chapter_url
+                    );
+                    continue 'chapters;
+                }
+
+                let chapter_html = match response.text(&chapter_url) {
+                    Ok(t) => t,
+                    Err(e @ ScraperError::Encoding { .. }) => {
+                        return Err(stop_early(&book, options, e))
                     }
-                    EmptyChapterBehavior::Fail => {
-                        return Err(ScraperError::ParseChapter { index: pi, url: u });
+                    Err(e) if options.fail_fast => return Err(stop_early(&book, options, e)),
+                    Err(e) => {
+                        eprintln!("Chapter {}: failed to read body: {}. Skipped.", index, e);
+                        continue 'chapters;
+                    }
+                };
+
+                let tags = &options.allowed_tags;
+                let missing = match parse_chapter_page(
+                    &chapter_html,
+                    index,
+                    &chapter_url,
+                    tags,
+                    &options.hidden_classes,
+                ) {
+                    Ok((parsed_title, body)) => {
+                        let chapter_title =
+                            chapter_title_or_toc(parsed_title, &title, &book.title, index);
+                        match chapter_or_missing(chapter_title, index, body, options) {
+                            Ok(ch) => {
+                                record_chapter(
+                                    &mut book,
+                                    ch,
+                                    &chapter_url,
+                                    &mut done,
+                                    total,
+                                    options,
+                                );
+                                continue 'chapters;
+                            }
+                            Err(missing) => missing,
+                        }
                     }
-                },
-                Err(e) => return Err(e),
+                    Err(ScraperError::ParseChapter { .. }) => MissingContent::Unparseable,
+                    Err(e) => return Err(stop_early(&book, options, e)),
+                };
+                if parse_retries_left > 0 && looks_truncated(&missing, &chapter_html) {
+                    parse_retries_left -= 1;
+                    eprintln!(
+                        "Chapter {}: page at {} looks truncated. Retrying ({} left).",
+                        index, chapter_url, parse_retries_left
+                    );
+                    continue;
+                }
+                break missing;
+            };
+            if let Some(ch) = handle_missing_chapter(empty_behavior, missing, index, &chapter_url)
+                .map_err(|e| stop_early(&book, options, e))?
+            {
+                record_chapter(&mut book, ch, &chapter_url, &mut done, total, options);
             }
         }
 
@@ -594,7 +1016,7 @@ mod tests {
 {"@type":"Book","name":"Inline Test Book","author":{"name":"Inline Author"},"description":"A description.","image":"https://example.com/cover.png"}
 </script>
 </body></html>"#;
-        let (title, author, description, cover_url) = parse_metadata(html)?;
+        let (title, author, description, cover_url) = parse_metadata(html, false, None)?;
         assert_eq!(title, "Inline Test Book");
         assert_eq!(author, "Inline Author");
         assert_eq!(description.as_deref(), Some("A description."));
@@ -602,6 +1024,70 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn inline_parse_metadata_title_only_fallback() -> Result<(), ScraperError> {
+        let html =
+            r#"<html><head><title>Only A Title | Royal Road</title></head><body></body></html>"#;
+        assert!(parse_metadata(html, false, None).is_err());
+        let (title, _, _, _) = parse_metadata(html, true, Some("Slug Title".to_string()))?;
+        assert_eq!(title, "Slug Title");
+        let (title, author, description, cover_url) = parse_metadata(html, true, None)?;
+        assert_eq!(title, "Only A Title");
+        assert_eq!(author, UNKNOWN_AUTHOR);
+        assert!(description.is_none());
+        assert!(cover_url.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_tags() -> Result<(), ScraperError> {
+        let html = r##"<div class="fiction-info"><span class="tags">
+<a class="fiction-tag" href="/fictions/search?tagsAdd=fantasy">Fantasy</a>
+<a class="fiction-tag" href="/fictions/search?tagsAdd=litrpg"> LitRPG </a>
+<a class="fiction-tag" href="/fictions/search?tagsAdd=fantasy">fantasy</a>
+<a class="fiction-tag" href="#"></a>
+</span></div>"##;
+        assert_eq!(parse_tags(html)?, vec!["Fantasy", "LitRPG"]);
+        assert!(parse_tags("<html><body></body></html>")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_stats() -> Result<(), ScraperError> {
+        let html = r#"<div class="fiction-stats"><div class="stats-content"><ul class="list-unstyled">
+<li class="bold uppercase font-red-sunglo">Total Views :</li><li class="bold uppercase">1,234,567</li>
+<li class="bold uppercase font-red-sunglo">Followers :</li><li class="bold uppercase">12,345</li>
+<li class="bold uppercase font-red-sunglo">Favorites :</li><li class="bold uppercase">2,468</li>
+<li class="bold uppercase font-red-sunglo">Ratings :</li><li><span class="star" data-content="4.5"></span></li>
+</ul></div></div>"#;
+        let stats = parse_stats(html)?;
+        assert_eq!(
+            stats.get("total_views").map(String::as_str),
+            Some("1,234,567")
+        );
+        assert_eq!(stats.get("followers").map(String::as_str), Some("12,345"));
+        assert_eq!(stats.get("favorites").map(String::as_str), Some("2,468"));
+        assert!(!stats.contains_key("ratings"));
+        assert!(parse_stats("<html><body></body></html>")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn not_found_page_detected() {
+        let soft_404 = r#"<!DOCTYPE html><html><head><title>Not Found | Royal Road</title></head>
+<body><div class="page-content"><h3>404</h3><p>The page you were looking for could not be found.</p></div></body></html>"#;
+        assert!(is_not_found_page(soft_404));
+        let by_message = r#"<html><head><title>Royal Road</title></head>
+<body><p>We couldn't find the fiction you were looking for.</p></body></html>"#;
+        assert!(is_not_found_page(by_message));
+        let story = r#"<html><head><title>Not Found: A Story | Royal Road</title></head>
+<body><script>window.chapters = [];</script></body></html>"#;
+        assert!(!is_not_found_page(story));
+        assert!(!is_not_found_page(
+            "<html><head><title>Fiction | Royal Road</title></head></html>"
+        ));
+    }
+
     #[test]
     fn inline_parse_toc() -> Result<(), ScraperError> {
         let html = r#"<script>
@@ -627,6 +1113,23 @@ window.chapters = [{"id":1,"title":"Free","url":"/fiction/1/s/free","order":0,"i
         Ok(())
     }
 
+    #[test]
+    fn fetch_order_unlocked_first_keeps_index_order_within_groups() {
+        let toc = vec![
+            (1, "u1".to_string(), "One".to_string(), true),
+            (2, "u2".to_string(), "Two".to_string(), false),
+            (3, "u3".to_string(), "Three".to_string(), true),
+            (4, "u4".to_string(), "Four".to_string(), false),
+        ];
+        let default: Vec<u32> = fetch_order(toc.clone(), false)
+            .iter()
+            .map(|e| e.0)
+            .collect();
+        assert_eq!(default, vec![1, 2, 3, 4]);
+        let reordered: Vec<u32> = fetch_order(toc, true).iter().map(|e| e.0).collect();
+        assert_eq!(reordered, vec![1, 3, 2, 4]);
+    }
+
     #[test]
     fn inline_parse_chapter_page() -> Result<(), ScraperError> {
         let html = r#"<!DOCTYPE html><html><head><meta property="og:title" content="1. Good Morning - Book _ Royal Road"/></head><body>
@@ -640,6 +1143,8 @@ window.chapters = [{"id":1,"title":"Free","url":"/fiction/1/s/free","order":0,"i
             html,
             1,
             "https://www.royalroad.com/fiction/1/slug/chapter/1/good-morning",
+            &BodyOptions::default(),
+            &[],
         )?;
         assert_eq!(title, "1. Good Morning");
         assert!(body.contains("<p>"));
@@ -648,17 +1153,187 @@ window.chapters = [{"id":1,"title":"Free","url":"/fiction/1/s/free","order":0,"i
         Ok(())
     }
 
+    #[test]
+    fn inline_parse_chapter_page_drops_hidden_theft_notices() -> Result<(), ScraperError> {
+        let html = r#"<!DOCTYPE html><html><head><style>
+.cjOTk3NmY1 { display: none; speak: never; }
+.author-note { font-style: italic; }
+</style></head><body>
+<h1 class="font-white break-word">5. Visible</h1>
+<div class="chapter-inner chapter-content">
+<p>The visible paragraph.</p>
+<p class="cjOTk3NmY1">This story has been stolen from Royal Road. If you see it on Amazon, report it.</p>
+<p style="display : none">Unauthorized reproduction: this story is on Amazon without permission.</p>
+<p class="antitheft-x1">A story taken without the author's consent.</p>
+</div>
+</body></html>"#;
+        let url = "https://www.royalroad.com/fiction/1/slug/chapter/5/visible";
+        let patterns = [Regex::new("^antitheft-").unwrap()];
+        let (_, body) = parse_chapter_page(html, 5, url, &BodyOptions::default(), &patterns)?;
+        assert_eq!(body, "<p>The visible paragraph.</p>");
+        let (_, body) = parse_chapter_page(html, 5, url, &BodyOptions::default(), &[])?;
+        assert!(body.contains("without the author's consent"), "{}", body);
+        assert!(!body.contains("Amazon"), "{}", body);
+        Ok(())
+    }
+
+    #[test]
+    fn style_hidden_classes_only_top_level_single_class_rules() -> Result<(), ScraperError> {
+        let doc = Html::parse_document(
+            r#"<html><head><style>
+@import url("print.css");
+/* .commented { display: none } */
+.theft, .notice { display: none; }
+@media (max-width: 600px) { .sidebar { display: none; } }
+.chapter .aside { display: none; }
+p.lead { display: none; }
+.shown { display: block; }
+</style></head><body></body></html>"#,
+        );
+        let hidden = style_hidden_classes(&doc)?;
+        let names: Vec<&str> = hidden.iter().map(String::as_str).collect();
+        assert_eq!(names, vec!["notice", "theft"]);
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_chapter_page_keeps_scene_breaks() -> Result<(), ScraperError> {
+        let html = r#"<!DOCTYPE html><html><body>
+<h1 class="font-white break-word">4. Scenes</h1>
+<div class="chapter-inner chapter-content">
+<p>Before the break.</p>
+<hr>
+<p>After the break.</p>
+<p style="text-align: center">* * *</p>
+<p>Last scene.</p>
+</div>
+</body></html>"#;
+        let (_, body) = parse_chapter_page(
+            html,
+            4,
+            "https://www.royalroad.com/fiction/1/slug/chapter/4/scenes",
+            &BodyOptions::default(),
+            &[],
+        )?;
+        assert_eq!(
+            body,
+            "<p>Before the break.</p><hr/><p>After the break.</p>\
+             <p class=\"scene-break\">* * *</p><p>Last scene.</p>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_chapter_page_keeps_internal_subheadings() -> Result<(), ScraperError> {
+        let html = r#"<!DOCTYPE html><html><body>
+<h1 class="font-white break-word">3. Two Scenes</h1>
+<div class="chapter-inner chapter-content">
+<h1>Part One</h1>
+<p>The first scene.</p>
+<h3>Meanwhile</h3>
+<p>The second scene.</p>
+</div>
+</body></html>"#;
+        let (title, body) = parse_chapter_page(
+            html,
+            3,
+            "https://www.royalroad.com/fiction/1/slug/chapter/3/two-scenes",
+            &BodyOptions::default(),
+            &[],
+        )?;
+        assert_eq!(title, "3. Two Scenes");
+        assert_eq!(
+            body,
+            "<h2>Part One</h2><p>The first scene.</p><h4>Meanwhile</h4><p>The second scene.</p>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_chapter_page_keeps_emphasis() -> Result<(), ScraperError> {
+        let html = r#"<!DOCTYPE html><html><body>
+<h1 class="font-white break-word">4. Voices</h1>
+<div class="chapter-inner chapter-content">
+<p>He said <em>no</em>.</p>
+<p><i>Can you hear me?</i> <span class="x">she</span> <b>thought</b>.</p>
+</div>
+</body></html>"#;
+        let (_, body) = parse_chapter_page(
+            html,
+            4,
+            "https://www.royalroad.com/fiction/1/slug/chapter/4/voices",
+            &BodyOptions::default(),
+            &[],
+        )?;
+        assert_eq!(
+            body,
+            "<p>He said <em>no</em>.</p><p><i>Can you hear me?</i> she <b>thought</b>.</p>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_chapter_page_keeps_ordered_list() -> Result<(), ScraperError> {
+        let html = r#"<!DOCTYPE html><html><body>
+<h1 class="font-white break-word">2. Status</h1>
+<div class="chapter-inner chapter-content">
+<p>Skills gained:</p>
+<ol>
+<li>Fireball &lt;Lv 1&gt;</li>
+<li><strong>Mana</strong> Shield</li>
+</ol>
+</div>
+</body></html>"#;
+        let (_, body) = parse_chapter_page(
+            html,
+            2,
+            "https://www.royalroad.com/fiction/1/slug/chapter/2/status",
+            &BodyOptions::default(),
+            &[],
+        )?;
+        assert_eq!(
+            body,
+            "<p>Skills gained:</p><ol><li>Fireball &lt;Lv 1&gt;</li><li><strong>Mana</strong> Shield</li></ol>"
+        );
+        Ok(())
+    }
+
     #[test]
     fn inline_parse_chapter_page_title_fallback_with_dash_and_pipe() -> Result<(), ScraperError> {
         // No h1; title from og:title. Chapter title contains " - " and suffix uses " _ ".
         let html = r#"<!DOCTYPE html><html><head><meta property="og:title" content="1. Good Morning - Brother - Book _ Royal Road"/></head><body>
 <div class="chapter-inner chapter-content"><p>Content.</p></div></body></html>"#;
-        let (title, _) =
-            parse_chapter_page(html, 1, "https://www.royalroad.com/fiction/1/s/chapter/1")?;
+        let (title, _) = parse_chapter_page(
+            html,
+            1,
+            "https://www.royalroad.com/fiction/1/s/chapter/1",
+            &BodyOptions::default(),
+            &[],
+        )?;
         assert_eq!(title, "1. Good Morning - Brother - Book");
         Ok(())
     }
 
+    #[test]
+    fn inline_parse_chapter_page_div_paragraphs() -> Result<(), ScraperError> {
+        let html = r#"<html><body><h1 class="font-white break-word">2. Divs</h1>
+<div class="chapter-inner chapter-content">
+<div class="cnXyz">She opened the door.</div>
+<div class="cnXyz">Nobody was there.</div>
+</div>
+</body></html>"#;
+        let (title, body) = parse_chapter_page(
+            html,
+            2,
+            "https://www.royalroad.com/fiction/1/x/chapter/2/divs",
+            &BodyOptions::default(),
+            &[],
+        )?;
+        assert_eq!(title, "2. Divs");
+        assert_eq!(body, "<p>She opened the door.</p><p>Nobody was there.</p>");
+        Ok(())
+    }
+
     /// Fixture test: parse fiction page and chapter page from saved HTML fixtures.
     /// Skips if fixture files are not present (e.g. in CI). Returns Err to fail test without panicking.
     #[test]
@@ -677,7 +1352,7 @@ window.chapters = [{"id":1,"title":"Free","url":"/fiction/1/s/free","order":0,"i
             Err(_) => return Ok(()), // skip if fixtures not present
         };
 
-        let (title, author, description, cover_url) = parse_metadata(&fiction_html)?;
+        let (title, author, description, cover_url) = parse_metadata(&fiction_html, false, None)?;
         assert_eq!(title, "Mother of Learning");
         assert_eq!(author, "nobody103");
         assert!(description.is_some());
@@ -693,7 +1368,7 @@ window.chapters = [{"id":1,"title":"Free","url":"/fiction/1/s/free","order":0,"i
             Ok(s) => s,
             Err(_) => return Ok(()),
         };
-        let (ch_title, body) = parse_chapter_page(&chapter_html, 1, "https://www.royalroad.com/fiction/21220/mother-of-learning/chapter/301778/1-good-morning-brother")?;
+        let (ch_title, body) = parse_chapter_page(&chapter_html, 1, "https://www.royalroad.com/fiction/21220/mother-of-learning/chapter/301778/1-good-morning-brother", &BodyOptions::default(), &[])?;
         assert_eq!(ch_title, "1. Good Morning Brother");
         assert!(!body.is_empty());
         assert!(body.contains("<p>"));
@@ -718,7 +1393,7 @@ window.chapters = [{"id":1,"title":"Free","url":"/fiction/1/s/free","order":0,"i
             Err(_) => return Ok(()),
         };
 
-        let (title, author, description, cover_url) = parse_metadata(&fiction_html)?;
+        let (title, author, description, cover_url) = parse_metadata(&fiction_html, false, None)?;
         assert_eq!(title, "Imma be a speedster");
         assert_eq!(author, "UnproperMadman");
         assert!(description.is_some());
@@ -738,10 +1413,78 @@ window.chapters = [{"id":1,"title":"Free","url":"/fiction/1/s/free","order":0,"i
             &chapter_html,
             1,
             "https://www.royalroad.com/fiction/136335/imma-be-a-speedster/chapter/123/chapter-1-smart-decisions",
+            &BodyOptions::default(),
+            &[],
         )?;
         assert_eq!(ch_title, "Chapter 1 - Smart decisions");
         assert!(!body.is_empty());
         assert!(body.contains("<p>"));
         Ok(())
     }
+
+    #[test]
+    fn ensure_chapter_url_requires_chapter_path() {
+        assert!(ensure_chapter_url(
+            "https://www.royalroad.com/fiction/21220/mother-of-learning/chapter/301778/1-good-morning-brother"
+        )
+        .is_ok());
+        assert!(matches!(
+            ensure_chapter_url("https://www.royalroad.com/fiction/21220/mother-of-learning"),
+            Err(ScraperError::ParseStoryPage { .. })
+        ));
+    }
+
+    #[test]
+    fn inline_parse_chapter_page_story_header() -> Result<(), ScraperError> {
+        let html = r#"<html><body><div class="row fic-header">
+<h2 class="font-white inline-block">Mother of Learning</h2>
+<h3 class="font-white inline-block">by <a href="/profile/1">nobody103</a></h3>
+</div></body></html>"#;
+        let (title, author) = parse_chapter_page_story(html)?;
+        assert_eq!(title.as_deref(), Some("Mother of Learning"));
+        assert_eq!(author, "nobody103");
+
+        let (title, author) = parse_chapter_page_story("<html><body></body></html>")?;
+        assert!(title.is_none());
+        assert_eq!(author, UNKNOWN_AUTHOR);
+        Ok(())
+    }
+
+    #[test]
+    fn api_chapter_url_from_chapter_id() {
+        assert_eq!(
+            api_chapter_url(
+                "https://www.royalroad.com/fiction/21220/mother-of-learning/chapter/301778/1-good-morning-brother"
+            )
+            .as_deref(),
+            Some("https://www.royalroad.com/api/chapter/301778")
+        );
+        assert!(
+            api_chapter_url("https://www.royalroad.com/fiction/21220/mother-of-learning").is_none()
+        );
+    }
+
+    #[test]
+    fn inline_parse_api_chapter() -> Result<(), ScraperError> {
+        let json = r#"{"title":"1. Start","content":"<p>One &amp; two</p><p> </p><blockquote><p>Three</p></blockquote>"}"#;
+        let tags = BodyOptions::default();
+        let (title, body) = parse_api_chapter(
+            json,
+            1,
+            "https://www.royalroad.com/api/chapter/1",
+            &tags,
+            &[],
+        )?;
+        assert_eq!(title, "1. Start");
+        assert_eq!(body, "<p>One &amp; two</p>");
+        let mut quotes = BodyOptions::default();
+        quotes.block_tags.push("blockquote".to_string());
+        let (_, body) = parse_api_chapter(json, 1, "u", &quotes, &[])?;
+        assert_eq!(body.matches("Three").count(), 1);
+        assert!(matches!(
+            parse_api_chapter("not json", 1, "u", &tags, &[]),
+            Err(ScraperError::ParseChapter { index: 1, .. })
+        ));
+        Ok(())
+    }
 }
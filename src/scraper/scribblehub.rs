@@ -2,16 +2,26 @@
 //!
 //! TOC source: series page only. Chapter body: #chp_raw only (see README.md, Known edge cases).
 
-use crate::model::{Book, Chapter};
+use crate::formats::{body_word_count, normalize_body_html, BodyOptions};
+use crate::model::{clean_tags, Book, Chapter};
 use crate::scraper::error::ScraperError;
 use crate::scraper::{
-    strip_title_site_suffix, EmptyChapterBehavior, PoliteClient, ScrapeOptions, Scraper,
+    absolutize_image_srcs, chapter_or_missing, chapter_title_or_toc, handle_missing_chapter,
+    looks_truncated, needs_fetch, record_chapter, report_toc, response_text,
+    retain_wanted_chapters, site_for_host, stat_key, stop_early, strip_title_site_suffix,
+    title_from_page_title, title_from_url_slug, transform_body, usable_cached_toc,
+    EmptyChapterBehavior, ExtraHosts, MissingContent, PoliteClient, Prefetch, ScrapeOptions,
+    Scraper, ScribbleHubTocMode, Site, TocRow, TocSort, UNKNOWN_AUTHOR,
 };
 use reqwest::Url;
 use scraper::{Html, Selector};
+use std::collections::BTreeMap;
 
 const SCRIBBLEHUB_BASE: &str = "https://www.scribblehub.com";
 
+/// Site suffixes stripped from `<title>`.
+const TITLE_SUFFIXES: &[&str] = &[" | Scribble Hub", " - Scribble Hub"];
+
 /// Parse a CSS selector or return a parse error (avoids panics from Selector::parse).
 fn parse_selector(sel: &str) -> Result<Selector, ScraperError> {
     Selector::parse(sel).map_err(|e| ScraperError::ParseStoryPage {
@@ -37,7 +47,8 @@ fn extract_series_id_from_url(url: &str) -> Option<String> {
 }
 
 /// Require series URL (path contains /series/; reject /read/.../chapter/). Returns the URL as-is if valid.
-fn ensure_series_url(url: &str) -> Result<String, ScraperError> {
+/// The host must be a Scribble Hub host per [site_for_host], including config `extra_hosts`.
+fn ensure_series_url(url: &str, extra_hosts: Option<&ExtraHosts>) -> Result<String, ScraperError> {
     let parsed = Url::parse(url).map_err(|e| ScraperError::InvalidUrl {
         input: url.to_string(),
         reason: e.to_string(),
@@ -46,7 +57,7 @@ fn ensure_series_url(url: &str) -> Result<String, ScraperError> {
         input: url.to_string(),
         reason: "URL has no host".to_string(),
     })?;
-    if !host.contains("scribblehub.com") {
+    if site_for_host(host, extra_hosts) != Some(Site::ScribbleHub) {
         return Err(ScraperError::ParseStoryPage {
             message: "Expected a Scribble Hub series URL (host scribblehub.com).".to_string(),
         });
@@ -65,7 +76,22 @@ fn ensure_series_url(url: &str) -> Result<String, ScraperError> {
     Ok(url.to_string())
 }
 
-/// Check response status and read body as UTF-8. Returns body or ScraperError.
+/// Require a chapter URL (path contains /read/ and /chapter/). Used only with `--single-chapter`.
+fn ensure_chapter_url(url: &str) -> Result<String, ScraperError> {
+    let parsed = Url::parse(url).map_err(|e| ScraperError::InvalidUrl {
+        input: url.to_string(),
+        reason: e.to_string(),
+    })?;
+    let path = parsed.path();
+    if !(path.contains("/read/") && path.contains("/chapter/")) {
+        return Err(ScraperError::ParseStoryPage {
+            message: "Expected a chapter URL with --single-chapter, e.g. https://www.scribblehub.com/read/862913-hp-the-arcane-thief-litrpg/chapter/862914/".to_string(),
+        });
+    }
+    Ok(url.to_string())
+}
+
+/// Check response status and read body as text (see [response_text]). Returns body or ScraperError.
 fn check_response(
     response: reqwest::blocking::Response,
     url: &str,
@@ -79,17 +105,19 @@ fn check_response(
             context: context.map(String::from),
         });
     }
-    response
-        .text()
-        .map_err(|e| ScraperError::BodyRead { source: e })
+    response_text(response, url)
 }
 
 const LD_JSON_OPEN: &str = "<script type=\"application/ld+json\">";
 const LD_JSON_CLOSE: &str = "</script>";
 
 /// Extract metadata from series page HTML: JSON-LD Book first (scan all ld+json scripts for @type Book), then DOM fallback.
+/// A missing title is replaced by `url_title` (`--title-from-url`) when given. With
+/// `allow_missing_metadata`, a missing title falls back to `<title>` and a missing author to [UNKNOWN_AUTHOR].
 fn parse_metadata(
     html: &str,
+    allow_missing_metadata: bool,
+    url_title: Option<String>,
 ) -> Result<(String, String, Option<String>, Option<String>), ScraperError> {
     let mut search_start = 0;
     while let Some(script) = html[search_start..].find(LD_JSON_OPEN) {
@@ -166,14 +194,50 @@ fn parse_metadata(
         .and_then(|e| e.value().attr("content").map(String::from))
         .filter(|s| !s.is_empty());
 
+    let title = title.or(url_title);
     match (title, author) {
         (Some(t), Some(a)) => Ok((t, a, None, cover_url)),
+        (title, author) if allow_missing_metadata => {
+            let title = title
+                .or_else(|| title_from_page_title(&doc, TITLE_SUFFIXES))
+                .ok_or_else(|| ScraperError::ParseStoryPage {
+                    message: "missing title (no selector or <title> matched)".to_string(),
+                })?;
+            let author = author.unwrap_or_else(|| UNKNOWN_AUTHOR.to_string());
+            Ok((title, author, None, cover_url))
+        }
         _ => Err(ScraperError::ParseStoryPage {
             message: "missing title or author (selector or structure may have changed)".to_string(),
         }),
     }
 }
 
+/// Popularity stats from the series page: each `span.st_item` in `.fic_stats` reads like
+/// "229.8k Views" (value, then label). Empty when the block is missing.
+fn parse_stats(html: &str) -> Result<BTreeMap<String, String>, ScraperError> {
+    let doc = Html::parse_document(html);
+    let item_sel = parse_selector(".fic_stats span.st_item")?;
+    Ok(doc
+        .select(&item_sel)
+        .filter_map(|item| {
+            let text = item.text().collect::<String>();
+            let (value, label) = text.trim().split_once(char::is_whitespace)?;
+            let key = stat_key(label);
+            (!key.is_empty()).then(|| (key, value.to_string()))
+        })
+        .collect())
+}
+
+/// Genre tags from the series page (`a.fic_genre`, e.g. "Fantasy", "Romance"), cleaned with
+/// [clean_tags]. Empty when there are none.
+fn parse_tags(html: &str) -> Result<Vec<String>, ScraperError> {
+    let doc = Html::parse_document(html);
+    let genre_sel = parse_selector("a.fic_genre")?;
+    Ok(clean_tags(
+        doc.select(&genre_sel).map(|a| a.text().collect::<String>()),
+    ))
+}
+
 fn strip_html_tags(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     let mut in_tag = false;
@@ -297,15 +361,78 @@ fn merge_toc_entries(mut all_entries: Vec<(u32, String, String)>) -> Vec<(u32, S
     all_entries
 }
 
+/// Chapter number from a TOC title: the digits after "chapter"/"ch", else the first digit run.
+fn title_chapter_number(title: &str) -> Option<u32> {
+    let lower = title.to_lowercase();
+    let after_label = ["chapter", "ch."]
+        .iter()
+        .find_map(|label| lower.find(label).map(|pos| &lower[pos + label.len()..]))
+        .map(|rest| rest.trim_start_matches(|c: char| c.is_whitespace() || c == '#'))
+        .filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit()));
+    let digits_from = after_label.or_else(|| {
+        lower
+            .find(|c: char| c.is_ascii_digit())
+            .map(|pos| &lower[pos..])
+    })?;
+    let end = digits_from
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits_from.len());
+    digits_from[..end].parse().ok()
+}
+
+/// Put merged TOC entries into reading order per `sort`. With [TocSort::Number] the entries are
+/// ordered by title chapter number and re-indexed from 1; if any title lacks a number the `order`
+/// sort is kept. With [TocSort::Order] a warning is printed when the first chapter number is higher
+/// than the last, which means the site listed the TOC newest-first.
+fn apply_toc_sort(
+    mut entries: Vec<(u32, String, String)>,
+    sort: TocSort,
+) -> Vec<(u32, String, String)> {
+    let numbers: Option<Vec<u32>> = entries
+        .iter()
+        .map(|(_, _, title)| title_chapter_number(title))
+        .collect();
+    match (sort, numbers) {
+        (TocSort::Number, Some(numbers)) => {
+            let mut keyed: Vec<_> = numbers.into_iter().zip(entries).collect();
+            keyed.sort_by_key(|(number, _)| *number);
+            entries = keyed
+                .into_iter()
+                .enumerate()
+                .map(|(i, (_, (_, url, title)))| (i as u32 + 1, url, title))
+                .collect();
+        }
+        (TocSort::Number, None) => {
+            eprintln!(
+                "Warning: --toc-sort number: some chapter titles have no number; using the site order."
+            );
+        }
+        (TocSort::Order, Some(numbers)) => {
+            if numbers.len() > 1 && numbers.first() > numbers.last() {
+                eprintln!(
+                    "Warning: the chapter list looks newest-first (chapter {} comes before chapter {}). Use --toc-sort number to order by chapter number.",
+                    numbers[0],
+                    numbers[numbers.len() - 1]
+                );
+            }
+        }
+        (TocSort::Order, None) => {}
+    }
+    entries
+}
+
 const SCRIBBLEHUB_AJAX_URL: &str = "https://www.scribblehub.com/wp-admin/admin-ajax.php";
 
+/// TOC rows as (index, chapter URL, title).
+type TocRows = Vec<(u32, String, String)>;
+
 /// Fetch full TOC via ScribbleHub's AJAX "Show All Chapters" (wi_getreleases_pagination pagenum=-1).
 /// Returns all chapters in one request. ScribbleHub loads the TOC via JavaScript; the initial HTML
 /// only contains ~15 chapters per page, so pagination often fails. The AJAX endpoint returns all.
 fn fetch_full_toc_via_ajax(
     client: &mut PoliteClient,
     series_url: &str,
-) -> Option<Result<Vec<(u32, String, String)>, ScraperError>> {
+) -> Option<Result<TocRows, ScraperError>> {
     let mypostid = extract_series_id_from_url(series_url)?;
     let base = match Url::parse(SCRIBBLEHUB_BASE) {
         Ok(b) => b,
@@ -324,10 +451,10 @@ fn fetch_full_toc_via_ajax(
     ) {
         Ok(r) => r,
         Err(e) => {
-            return Some(Err(ScraperError::Network {
-                url: SCRIBBLEHUB_AJAX_URL.to_string(),
-                source: e,
-            }))
+            return Some(Err(ScraperError::from_request(
+                SCRIBBLEHUB_AJAX_URL.to_string(),
+                e,
+            )))
         }
     };
     let html = match check_response(response, SCRIBBLEHUB_AJAX_URL, Some("TOC AJAX")) {
@@ -337,17 +464,31 @@ fn fetch_full_toc_via_ajax(
     Some(parse_toc_page(&html, &base).map(merge_toc_entries))
 }
 
-/// Fetch full TOC: try AJAX "Show All" first (reliable), then fall back to paginated requests.
-/// Returns (order, full_url, title) sorted by reading order, deduplicated by URL.
+/// Fetch full TOC per `mode`: by default try AJAX "Show All" first (reliable), then fall back to
+/// paginated requests. Returns (order, full_url, title) sorted by reading order, deduplicated by URL.
 fn fetch_full_toc(
     client: &mut PoliteClient,
     series_url: &str,
     first_page_html: &str,
+    mode: ScribbleHubTocMode,
 ) -> Result<Vec<(u32, String, String)>, ScraperError> {
-    if let Some(result) = fetch_full_toc_via_ajax(client, series_url) {
-        let entries = result?;
-        if !entries.is_empty() {
-            return Ok(entries);
+    if mode != ScribbleHubTocMode::Paginate {
+        match fetch_full_toc_via_ajax(client, series_url) {
+            Some(result) => {
+                let entries = result?;
+                if !entries.is_empty() {
+                    return Ok(entries);
+                }
+            }
+            None if mode == ScribbleHubTocMode::Ajax => {
+                return Err(ScraperError::ChapterListParse {
+                    reason: "no series id in URL for the AJAX chapter list".to_string(),
+                });
+            }
+            None => {}
+        }
+        if mode == ScribbleHubTocMode::Ajax {
+            return Err(ScraperError::EmptyChapterList);
         }
     }
 
@@ -364,10 +505,7 @@ fn fetch_full_toc(
     while let Some(next_url) = current_url.clone() {
         let response = client
             .get_with_retry(&next_url)
-            .map_err(|e| ScraperError::Network {
-                url: next_url.clone(),
-                source: e,
-            })?;
+            .map_err(|e| ScraperError::from_request(next_url.clone(), e))?;
         let html = check_response(response, &next_url, Some("TOC page"))?;
         let page_entries = parse_toc_page(&html, &base)?;
         all_entries.extend(page_entries);
@@ -382,7 +520,12 @@ fn fetch_full_toc(
 }
 
 /// Parse chapter page: title from div.chapter-title or <title>; body from #chp_raw.chp_raw direct children.
-fn parse_chapter_page(html: &str, index: u32, url: &str) -> Result<(String, String), ScraperError> {
+fn parse_chapter_page(
+    html: &str,
+    index: u32,
+    url: &str,
+    tags: &BodyOptions,
+) -> Result<(String, String), ScraperError> {
     let doc = Html::parse_document(html);
 
     let chapter_title_sel = parse_selector("div.chapter-title")?;
@@ -396,7 +539,7 @@ fn parse_chapter_page(html: &str, index: u32, url: &str) -> Result<(String, Stri
             doc.select(&title_sel)
                 .next()
                 .and_then(|e| e.text().next())
-                .map(|t| strip_title_site_suffix(t.trim(), &[" | Scribble Hub", " - Scribble Hub"]))
+                .map(|t| strip_title_site_suffix(t.trim(), TITLE_SUFFIXES))
                 .filter(|s| !s.is_empty())
         })
         .unwrap_or_else(|| format!("Chapter {}", index));
@@ -409,15 +552,20 @@ fn parse_chapter_page(html: &str, index: u32, url: &str) -> Result<(String, Stri
         });
     }
 
-    let p_sel = parse_selector("#chp_raw.chp_raw > p")?;
-    let body = doc
-        .select(&p_sel)
-        .map(|el| {
-            let text = el.text().collect::<String>().trim().to_string();
-            format!("<p>{}</p>", html_escape_inner(&text))
-        })
-        .collect::<Vec<_>>()
-        .join("");
+    let mut body = doc
+        .select(&chp_raw_sel)
+        .map(|container| normalize_body_html(&container.inner_html(), tags))
+        .collect::<String>();
+
+    // Some chapters wrap their paragraphs in one extra <div>. Only fall back to those wrappers when
+    // the container has no direct blocks, so footnote paragraphs nested deeper stay out.
+    if body.is_empty() {
+        let wrapper_sel = parse_selector("#chp_raw.chp_raw > div")?;
+        body = doc
+            .select(&wrapper_sel)
+            .map(|wrapper| normalize_body_html(&wrapper.inner_html(), tags))
+            .collect::<String>();
+    }
 
     if body.is_empty() {
         return Err(ScraperError::ParseChapter {
@@ -426,20 +574,95 @@ fn parse_chapter_page(html: &str, index: u32, url: &str) -> Result<(String, Stri
         });
     }
 
-    Ok((title, body))
+    Ok((title, absolutize_image_srcs(&body, url)))
 }
 
-fn html_escape_inner(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
+/// Site-reported chapter word count: the first element whose class mentions "word" and whose
+/// text reads like "1,234 words". None when the page does not show one.
+fn parse_reported_word_count(html: &str) -> Result<Option<u32>, ScraperError> {
+    let doc = Html::parse_document(html);
+    let sel = parse_selector("[class*=\"word\"]")?;
+    Ok(doc.select(&sel).find_map(|el| {
+        let text = el.text().collect::<String>();
+        let mut tokens = text.split_whitespace();
+        let number = tokens.next()?;
+        let unit = tokens.next()?.to_ascii_lowercase();
+        if unit != "words" && unit != "word" {
+            return None;
+        }
+        number.replace(',', "").parse().ok()
+    }))
+}
+
+/// True when our extracted word count is off from the site's count by more than 20% (and at
+/// least 50 words), which usually means the content selector dropped part of the chapter.
+fn word_count_diverges(reported: u32, extracted: usize) -> bool {
+    let reported = reported as usize;
+    let diff = reported.abs_diff(extracted);
+    diff > 50 && diff * 5 > reported
+}
+
+/// Warn when the extracted body is far from the site-reported word count.
+fn warn_if_words_dropped(ch: &Chapter, url: &str) {
+    if let Some(reported) = ch.reported_word_count {
+        let extracted = body_word_count(&ch.body);
+        if word_count_diverges(reported, extracted) {
+            eprintln!(
+                "Warning: chapter {}: extracted {} words but the site reports {} at {}. Content may have been dropped.",
+                ch.index, extracted, reported, url
+            );
+        }
+    }
+}
+
+/// Series title from the breadcrumb on a chapter page. Chapter pages carry no author, so it is [UNKNOWN_AUTHOR].
+fn parse_chapter_page_story(html: &str) -> Result<(Option<String>, String), ScraperError> {
+    let doc = Html::parse_document(html);
+    let series_sel = parse_selector("div.wi_breadcrumb a[href*=\"/series/\"]")?;
+    let title = doc
+        .select(&series_sel)
+        .next()
+        .map(|e| e.text().collect::<String>().trim().to_string())
+        .filter(|s| !s.is_empty());
+    Ok((title, UNKNOWN_AUTHOR.to_string()))
 }
 
 impl<'a> ScribbleHubScraper<'a> {
     pub fn new(client: &'a mut PoliteClient) -> Self {
         Self { client }
     }
+
+    /// `--single-chapter`: fetch one chapter page and wrap it in a one-chapter book, bypassing the TOC.
+    /// The book title is the series title from the breadcrumb, or the chapter title if absent.
+    fn scrape_single_chapter(
+        &mut self,
+        url: &str,
+        options: &ScrapeOptions<'_>,
+    ) -> Result<Book, ScraperError> {
+        let chapter_url = ensure_chapter_url(url)?;
+        let response = self
+            .client
+            .get_with_retry(&chapter_url)
+            .map_err(|e| ScraperError::from_request(chapter_url.clone(), e))?;
+        let html = check_response(response, &chapter_url, Some("chapter page"))?;
+        let (chapter_title, body) =
+            parse_chapter_page(&html, 1, &chapter_url, &options.allowed_tags)?;
+        let body = transform_body(body, options);
+        let (story_title, author) = parse_chapter_page_story(&html)?;
+        Ok(Book {
+            title: story_title.unwrap_or_else(|| chapter_title.clone()),
+            author,
+            chapters: vec![Chapter {
+                title: chapter_title,
+                index: 1,
+                body,
+                reported_word_count: None,
+                url: Some(chapter_url.clone()),
+            }],
+            source_url: Some(chapter_url),
+            ..Default::default()
+        })
+    }
 }
 
 impl Scraper for ScribbleHubScraper<'_> {
@@ -448,27 +671,52 @@ impl Scraper for ScribbleHubScraper<'_> {
         url: &str,
         options: &ScrapeOptions<'_>,
     ) -> Result<Book, ScraperError> {
-        let series_url = ensure_series_url(url)?;
-
-        let response =
-            self.client
-                .get_with_retry(&series_url)
-                .map_err(|e| ScraperError::Network {
-                    url: series_url.clone(),
-                    source: e,
-                })?;
-        let html = check_response(response, &series_url, Some("story page"))?;
-
-        let mut toc = fetch_full_toc(self.client, &series_url, &html)?;
-        let total = toc.len() as u32;
-        if let Some((from, to)) = options.chapter_range {
-            toc.retain(|(index, _, _)| *index >= from && *index <= to);
+        if options.single_chapter {
+            return self.scrape_single_chapter(url, options);
         }
+        let series_url = ensure_series_url(url, options.extra_hosts)?;
+
+        // With a usable cached TOC neither the series page nor the TOC pages are fetched; `html`
+        // stays empty, which is fine because metadata then comes from `initial_book`.
+        let (toc, html) = match usable_cached_toc(options) {
+            Some(cached) => {
+                let toc: Vec<(u32, String, String)> =
+                    cached.iter().map(TocRow::from_entry).collect();
+                (toc, String::new())
+            }
+            None => {
+                let response = self
+                    .client
+                    .get_with_retry(&series_url)
+                    .map_err(|e| ScraperError::from_request(series_url.clone(), e))?;
+                let html = check_response(response, &series_url, Some("story page"))?;
+                let toc = fetch_full_toc(
+                    self.client,
+                    &series_url,
+                    &html,
+                    options.scribblehub_toc_mode,
+                )?;
+                report_toc(options, &toc);
+                (toc, html)
+            }
+        };
+        let mut toc = apply_toc_sort(toc, options.toc_sort);
+        let total = toc.len() as u32;
+        retain_wanted_chapters(&mut toc, options, |(index, url, title)| {
+            (*index, url.as_str(), title.as_str())
+        });
 
         let mut book: Book = if let Some(init) = options.initial_book {
             init.clone()
         } else {
-            let (title, author, description, cover_url) = parse_metadata(&html)?;
+            let (title, author, description, cover_url) = parse_metadata(
+                &html,
+                options.allow_missing_metadata,
+                options
+                    .title_from_url
+                    .then(|| title_from_url_slug(&series_url))
+                    .flatten(),
+            )?;
             Book {
                 title,
                 author,
@@ -476,18 +724,27 @@ impl Scraper for ScribbleHubScraper<'_> {
                 cover_url,
                 chapters: Vec::with_capacity(toc.len()),
                 source_url: Some(series_url),
+                ..Default::default()
             }
         };
+        if options.stats {
+            book.stats = parse_stats(&html)?;
+        }
+        if book.tags.is_empty() {
+            book.tags = parse_tags(&html)?;
+        }
 
         if options.toc_only {
-            for (index, _chapter_url, title) in toc {
-                if book.chapters.iter().any(|c| c.index == index) {
+            for (index, chapter_url, title) in toc {
+                if !needs_fetch(&mut book, index, &chapter_url, options.index_collision)? {
                     continue;
                 }
                 book.chapters.push(Chapter {
                     title,
                     index,
                     body: String::new(),
+                    reported_word_count: None,
+                    url: Some(chapter_url),
                 });
             }
             book.chapters.sort_by_key(|c| c.index);
@@ -495,119 +752,129 @@ impl Scraper for ScribbleHubScraper<'_> {
         }
 
         let mut done = 0u32;
-        for (index, chapter_url, _) in toc {
-            if book.chapters.iter().any(|c| c.index == index) {
+        let mut prefetch = Prefetch::new(options.concurrency);
+        'chapters: for (pos, (index, chapter_url, title)) in toc.clone().into_iter().enumerate() {
+            if !needs_fetch(&mut book, index, &chapter_url, options.index_collision)
+                .map_err(|e| stop_early(&book, options, e))?
+            {
                 continue;
             }
             if options.cancel_check.map(|c| c()).unwrap_or(false) {
-                return Err(ScraperError::Cancelled);
+                return Err(stop_early(&book, options, ScraperError::Cancelled));
             }
-            let response = match self.client.get_with_retry(&chapter_url) {
-                Ok(r) => r,
-                Err(e) => {
-                    eprintln!(
-                        "Chapter {}: network error at {}: {}. Skipped.",
-                        index, chapter_url, e
-                    );
-                    continue;
-                }
-            };
-
-            if !response.status().is_success() {
-                eprintln!(
-                    "Chapter {}: HTTP {} at {}. Skipped.",
-                    index,
-                    response.status().as_u16(),
-                    chapter_url
-                );
-                continue;
-            }
-
-            let chapter_html = match response.text() {
-                Ok(t) => t,
-                Err(e) => {
-                    eprintln!("Chapter {}: failed to read body: {}. Skipped.", index, e);
-                    continue;
-                }
-            };
-
             let empty_behavior = options
                 .empty_chapter_behavior
                 .unwrap_or(EmptyChapterBehavior::Skip);
-            match parse_chapter_page(&chapter_html, index, &chapter_url) {
-                Ok((parsed_title, body)) => {
-                    if body.is_empty() {
-                        match empty_behavior {
-                            EmptyChapterBehavior::Skip => {
-                                eprintln!(
-                                    "Chapter {} returned no content at {}. Skipped.",
-                                    index, chapter_url
-                                );
-                                continue;
-                            }
-                            EmptyChapterBehavior::Placeholder => {
-                                book.chapters.push(Chapter {
-                                    title: format!("{} (no content)", parsed_title),
-                                    index,
-                                    body: "<p>This chapter returned no content.</p>".to_string(),
-                                });
-                                book.chapters.sort_by_key(|c| c.index);
-                                done += 1;
-                                if let Some(ref p) = options.progress {
-                                    p(done, total);
-                                }
-                                if let Some(ref cb) = options.on_checkpoint {
-                                    cb(&book);
-                                }
-                            }
-                            EmptyChapterBehavior::Fail => {
-                                return Err(ScraperError::EmptyChapter {
-                                    index,
-                                    url: chapter_url.clone(),
-                                });
-                            }
-                        }
-                        continue;
+            let upcoming = toc[pos + 1..].iter().map(|(i, u, _)| (*i, u.as_str()));
+            prefetch.fill(self.client, &book, &chapter_url, upcoming);
+            let mut parse_retries_left = options.chapter_parse_retries;
+            let missing = loop {
+                let response = match prefetch.get(self.client, &chapter_url) {
+                    Ok(r) => r,
+                    Err(e) if options.fail_fast => {
+                        return Err(stop_early(
+                            &book,
+                            options,
+                            ScraperError::from_request(chapter_url, e),
+                        ));
                     }
-                    book.chapters.push(Chapter {
-                        title: parsed_title,
-                        index,
-                        body,
-                    });
-                    book.chapters.sort_by_key(|c| c.index);
-                    done += 1;
-                    if let Some(ref p) = options.progress {
-                        p(done, total);
+                    Err(e) => {
+                        eprintln!(
+                            "Chapter {}: network error at {}: {}. Skipped.",
+                            index, chapter_url, e
+                        );
+                        continue 'chapters;
                     }
-                    if let Some(ref cb) = options.on_checkpoint {
-                        cb(&book);
+                };
+
+                let status = response.status();
+                if status.is_client_error() {
+                    let missing = MissingContent::ClientError {
+                        title,
+                        status: status.as_u16(),
+                    };
+                    if let Some(ch) =
+                        handle_missing_chapter(empty_behavior, missing, index, &chapter_url)
+                            .map_err(|e| stop_early(&book, options, e))?
+                    {
+                        record_chapter(&mut book, ch, &chapter_url, &mut done, total, options);
                     }
+                    continue 'chapters;
                 }
-                Err(ScraperError::ParseChapter { index: pi, url: u }) => match empty_behavior {
-                    EmptyChapterBehavior::Skip => {
-                        eprintln!("Chapter {}: could not parse content at {}. Skipped.", pi, u);
+                if !status.is_success() {
+                    if options.fail_fast {
+                        return Err(stop_early(
+                            &book,
+                            options,
+                            ScraperError::HttpStatus {
+                                status: status.as_u16(),
+                                url: chapter_url,
+                                context: Some(format!("chapter {}", index)),
+                            },
+                        ));
                     }
-                    EmptyChapterBehavior::Placeholder => {
-                        book.chapters.push(Chapter {
-                                title: format!("Chapter {} (unable to parse)", pi),
-                                index: pi,
-                                body: "<p>This chapter could not be parsed (missing content container).</p>"
-                                    .to_string(),
-                            });
-                        book.chapters.sort_by_key(|c| c.index);
-                        done += 1;
-                        if let Some(ref p) = options.progress {
-                            p(done, total);
-                        }
-                        if let Some(ref cb) = options.on_checkpoint {
-                            cb(&book);
-                        }
+                    eprintln!(
+                        "Chapter {}: HTTP {} at {}. Skipped.",
+                        index,
+                        status.as_u16(),
+                        chapter_url
+                    );
+                    continue 'chapters;
+                }
+
+                let chapter_html = match response.text(&chapter_url) {
+                    Ok(t) => t,
+                    Err(e @ ScraperError::Encoding { .. }) => {
+                        return Err(stop_early(&book, options, e))
                     }
-                    EmptyChapterBehavior::Fail => {
-                        return Err(ScraperError::ParseChapter { index: pi, url: u });
+                    Err(e) if options.fail_fast => return Err(stop_early(&book, options, e)),
+                    Err(e) => {
+                        eprintln!("Chapter {}: failed to read body: {}. Skipped.", index, e);
+                        continue 'chapters;
                     }
-                },
-                Err(e) => return Err(e),
+                };
+
+                let tags = &options.allowed_tags;
+                let missing = match parse_chapter_page(&chapter_html, index, &chapter_url, tags) {
+                    Ok((parsed_title, body)) => {
+                        let chapter_title =
+                            chapter_title_or_toc(parsed_title, &title, &book.title, index);
+                        match chapter_or_missing(chapter_title, index, body, options) {
+                            Ok(mut ch) => {
+                                ch.reported_word_count =
+                                    parse_reported_word_count(&chapter_html)
+                                        .map_err(|e| stop_early(&book, options, e))?;
+                                warn_if_words_dropped(&ch, &chapter_url);
+                                record_chapter(
+                                    &mut book,
+                                    ch,
+                                    &chapter_url,
+                                    &mut done,
+                                    total,
+                                    options,
+                                );
+                                continue 'chapters;
+                            }
+                            Err(missing) => missing,
+                        }
+                    }
+                    Err(ScraperError::ParseChapter { .. }) => MissingContent::Unparseable,
+                    Err(e) => return Err(stop_early(&book, options, e)),
+                };
+                if parse_retries_left > 0 && looks_truncated(&missing, &chapter_html) {
+                    parse_retries_left -= 1;
+                    eprintln!(
+                        "Chapter {}: page at {} looks truncated. Retrying ({} left).",
+                        index, chapter_url, parse_retries_left
+                    );
+                    continue;
+                }
+                break missing;
+            };
+            if let Some(ch) = handle_missing_chapter(empty_behavior, missing, index, &chapter_url)
+                .map_err(|e| stop_early(&book, options, e))?
+            {
+                record_chapter(&mut book, ch, &chapter_url, &mut done, total, options);
             }
         }
 
@@ -631,7 +898,7 @@ mod tests {
 {"@type":"Book","name":"SH Inline Book","author":{"name":"SH Author"},"description":"Desc","image":"https://example.com/cover.jpg"}
 </script>
 </body></html>"#;
-        let (title, author, description, cover_url) = parse_metadata(html)?;
+        let (title, author, description, cover_url) = parse_metadata(html, false, None)?;
         assert_eq!(title, "SH Inline Book");
         assert_eq!(author, "SH Author");
         assert_eq!(description.as_deref(), Some("Desc"));
@@ -639,6 +906,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn inline_parse_metadata_title_only_fallback() -> Result<(), ScraperError> {
+        let html =
+            r#"<html><head><title>Only A Title | Scribble Hub</title></head><body></body></html>"#;
+        assert!(parse_metadata(html, false, None).is_err());
+        let (title, author, _, _) = parse_metadata(html, true, None)?;
+        assert_eq!(title, "Only A Title");
+        assert_eq!(author, UNKNOWN_AUTHOR);
+        Ok(())
+    }
+
     #[test]
     fn inline_parse_toc_page() -> Result<(), ScraperError> {
         let base_url =
@@ -657,6 +935,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn inline_parse_tags() -> Result<(), ScraperError> {
+        let html = r##"<div class="wi_fic_genre"><span class="wi_fic_genre">
+<a class="fic_genre" href="https://www.scribblehub.com/genre/fantasy/">Fantasy</a>
+<a class="fic_genre" href="https://www.scribblehub.com/genre/romance/">Romance</a>
+<a class="fic_genre" href="https://www.scribblehub.com/genre/fantasy/">Fantasy</a>
+<a class="fic_genre" href="#"> </a>
+</span></div>"##;
+        assert_eq!(parse_tags(html)?, vec!["Fantasy", "Romance"]);
+        assert!(parse_tags("<html><body></body></html>")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_stats() -> Result<(), ScraperError> {
+        let html = r#"<div class="fic_stats">
+<span class="st_item"><i class="fa fa-eye"></i>229.8k Views</span>
+<span class="st_item"><i class="fa fa-heart"></i>1,024 Favorites</span>
+<span class="st_item"><i class="fa fa-list-alt"></i>85 Chapters</span>
+<span class="st_item">Unlabelled</span>
+</div>"#;
+        let stats = parse_stats(html)?;
+        assert_eq!(stats.get("views").map(String::as_str), Some("229.8k"));
+        assert_eq!(stats.get("favorites").map(String::as_str), Some("1,024"));
+        assert_eq!(stats.get("chapters").map(String::as_str), Some("85"));
+        assert_eq!(stats.len(), 3);
+        assert!(parse_stats("<html><body></body></html>")?.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn inline_parse_chapter_page() -> Result<(), ScraperError> {
         let html = r#"<!DOCTYPE html><html><head><title>Book - Chapter 1: Intro | Scribble Hub</title></head><body>
@@ -670,6 +978,7 @@ mod tests {
             html,
             1,
             "https://www.scribblehub.com/read/123/slug/chapter/1/",
+            &BodyOptions::default(),
         )?;
         assert_eq!(title, "Chapter 1: Intro");
         assert!(body.contains("<p>"));
@@ -678,6 +987,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn inline_parse_chapter_page_keeps_emphasis() -> Result<(), ScraperError> {
+        let html = r#"<!DOCTYPE html><html><head><title>Book - Chapter 3 | Scribble Hub</title></head><body>
+<div id="chp_raw" class="chp_raw">
+<p>He said <em>no</em>.</p>
+<p><strong>[Skill acquired]</strong><br>Fireball</p>
+</div>
+</body></html>"#;
+        let (_, body) = parse_chapter_page(
+            html,
+            3,
+            "https://www.scribblehub.com/read/123/slug/chapter/3/",
+            &BodyOptions::default(),
+        )?;
+        assert_eq!(
+            body,
+            "<p>He said <em>no</em>.</p><p><strong>[Skill acquired]</strong><br/>Fireball</p>"
+        );
+        Ok(())
+    }
+
     #[test]
     fn inline_parse_chapter_page_title_fallback_with_dash_and_pipe() -> Result<(), ScraperError> {
         // No div.chapter-title; title from <title>. Chapter title contains " - " and " | ".
@@ -687,6 +1017,7 @@ mod tests {
             html,
             1,
             "https://www.scribblehub.com/read/123/slug/chapter/1/",
+            &BodyOptions::default(),
         )?;
         assert_eq!(title, "Book - Chapter 1 - The Beginning");
         let html_pipe = r#"<!DOCTYPE html><html><head><title>Book - Chapter 1 | Part 2 | Scribble Hub</title></head><body>
@@ -695,11 +1026,48 @@ mod tests {
             html_pipe,
             1,
             "https://www.scribblehub.com/read/123/slug/chapter/1/",
+            &BodyOptions::default(),
         )?;
         assert_eq!(title2, "Book - Chapter 1 | Part 2");
         Ok(())
     }
 
+    #[test]
+    fn inline_parse_chapter_page_wrapped_paragraphs() -> Result<(), ScraperError> {
+        let html = r#"<html><head><title>Book - Chapter 2 | Scribble Hub</title></head><body>
+<div id="chp_raw" class="chp_raw">
+<div class="wrapped"><p>First wrapped line.</p><p>Second wrapped line.</p>
+<div class="footnote"><p>Deep footnote.</p></div></div>
+</div>
+</body></html>"#;
+        let (_, body) = parse_chapter_page(
+            html,
+            2,
+            "https://www.scribblehub.com/read/123/slug/chapter/2/",
+            &BodyOptions::default(),
+        )?;
+        assert_eq!(
+            body,
+            "<p>First wrapped line.</p><p>Second wrapped line.</p>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_chapter_page_prefers_direct_paragraphs() -> Result<(), ScraperError> {
+        let html = r#"<html><body>
+<div id="chp_raw" class="chp_raw"><p>Direct.</p><div><p>Nested note.</p></div></div>
+</body></html>"#;
+        let (_, body) = parse_chapter_page(
+            html,
+            1,
+            "https://www.scribblehub.com/read/123/slug/chapter/1/",
+            &BodyOptions::default(),
+        )?;
+        assert_eq!(body, "<p>Direct.</p>");
+        Ok(())
+    }
+
     #[test]
     fn merge_toc_entries_merges_and_sorts() {
         let page1 = vec![
@@ -735,6 +1103,68 @@ mod tests {
         assert_eq!(merged[1].2, "Ch2");
     }
 
+    #[test]
+    fn title_chapter_number_prefers_chapter_label() {
+        assert_eq!(title_chapter_number("Chapter 12: Start"), Some(12));
+        assert_eq!(title_chapter_number("Vol. 2 Chapter 7"), Some(7));
+        assert_eq!(title_chapter_number("Ch. 3 - Return"), Some(3));
+        assert_eq!(title_chapter_number("Side Story 4"), Some(4));
+        assert_eq!(title_chapter_number("Prologue"), None);
+    }
+
+    #[test]
+    fn descending_toc_sorted_by_title_number() -> Result<(), ScraperError> {
+        let base_url =
+            Url::parse(SCRIBBLEHUB_BASE).map_err(|e| ScraperError::ChapterListParse {
+                reason: e.to_string(),
+            })?;
+        let html = r#"<html><body>
+<ol class="toc_ol">
+<li class="toc_w" order="1"><a class="toc_a" href="/read/123/slug/chapter/30/">Chapter 3: End</a></li>
+<li class="toc_w" order="2"><a class="toc_a" href="/read/123/slug/chapter/20/">Chapter 2: Middle</a></li>
+<li class="toc_w" order="3"><a class="toc_a" href="/read/123/slug/chapter/10/">Chapter 1: Start</a></li>
+</ol>
+</body></html>"#;
+        let entries = merge_toc_entries(parse_toc_page(html, &base_url)?);
+
+        let by_order = apply_toc_sort(entries.clone(), TocSort::Order);
+        assert_eq!(by_order[0].2, "Chapter 3: End");
+
+        let by_number = apply_toc_sort(entries, TocSort::Number);
+        let titles: Vec<_> = by_number.iter().map(|(_, _, t)| t.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["Chapter 1: Start", "Chapter 2: Middle", "Chapter 3: End"]
+        );
+        let indices: Vec<_> = by_number.iter().map(|(i, _, _)| *i).collect();
+        assert_eq!(indices, [1, 2, 3]);
+        assert!(by_number[0].1.ends_with("/chapter/10/"));
+        Ok(())
+    }
+
+    #[test]
+    fn number_sort_keeps_order_when_a_title_has_no_number() {
+        let entries = vec![
+            (
+                1,
+                "https://example.com/p".to_string(),
+                "Prologue".to_string(),
+            ),
+            (
+                2,
+                "https://example.com/2".to_string(),
+                "Chapter 2".to_string(),
+            ),
+            (
+                3,
+                "https://example.com/1".to_string(),
+                "Chapter 1".to_string(),
+            ),
+        ];
+        let sorted = apply_toc_sort(entries.clone(), TocSort::Number);
+        assert_eq!(sorted, entries);
+    }
+
     #[test]
     fn next_toc_page_url_returns_some_when_next_link_present() {
         let series_base = Url::parse("https://www.scribblehub.com/series/123/slug/").unwrap();
@@ -780,7 +1210,7 @@ mod tests {
             Err(_) => return Ok(()),
         };
 
-        let (title, author, description, cover_url) = parse_metadata(&series_html)?;
+        let (title, author, description, cover_url) = parse_metadata(&series_html, false, None)?;
         assert_eq!(title, "HP: The Arcane Thief (LitRPG)");
         assert_eq!(author, "Snollygoster");
         assert!(description.is_some());
@@ -817,6 +1247,7 @@ mod tests {
             &chapter_html,
             239,
             "https://www.scribblehub.com/read/862913-hp-the-arcane-thief-litrpg/chapter/1383859/",
+            &BodyOptions::default(),
         )?;
         assert_eq!(ch_title, "Chapter 239: All Hail, King Axel");
         assert!(!body.is_empty());
@@ -843,7 +1274,7 @@ mod tests {
             Err(_) => return Ok(()),
         };
 
-        let (title, author, description, cover_url) = parse_metadata(&series_html)?;
+        let (title, author, description, cover_url) = parse_metadata(&series_html, false, None)?;
         assert_eq!(title, "Immortal Paladin");
         assert_eq!(author, "Alfir");
         assert!(description.is_some());
@@ -865,10 +1296,111 @@ mod tests {
             &chapter_html,
             1,
             "https://www.scribblehub.com/read/1414286-immortal-paladin/chapter/2133716/",
+            &BodyOptions::default(),
         )?;
         assert_eq!(ch_title, "Book 1 – Yellow Dragon Festival [REWRITE][Part1]");
         assert!(!body.is_empty());
         assert!(body.contains("<p>"));
         Ok(())
     }
+
+    #[test]
+    fn ensure_chapter_url_requires_read_chapter_path() {
+        assert!(ensure_chapter_url(
+            "https://www.scribblehub.com/read/862913-hp-the-arcane-thief-litrpg/chapter/862914/"
+        )
+        .is_ok());
+        assert!(matches!(
+            ensure_chapter_url(
+                "https://www.scribblehub.com/series/862913/hp-the-arcane-thief-litrpg/"
+            ),
+            Err(ScraperError::ParseStoryPage { .. })
+        ));
+    }
+
+    #[test]
+    fn ensure_series_url_accepts_scribblehub_subdomains_and_extra_hosts() {
+        let path = "/series/862913/hp-the-arcane-thief-litrpg/";
+        for host in [
+            "m.scribblehub.com",
+            "scribblehub.com",
+            "www.scribblehub.com",
+        ] {
+            let url = format!("https://{}{}", host, path);
+            assert!(ensure_series_url(&url, None).is_ok(), "{}", url);
+        }
+        let mirror = format!("https://sh.mirror.example{}", path);
+        assert!(ensure_series_url(&mirror, None).is_err());
+        let mut extra = ExtraHosts::new();
+        extra.insert("mirror.example".to_string(), Site::ScribbleHub);
+        assert!(ensure_series_url(&mirror, Some(&extra)).is_ok());
+        extra.insert("mirror.example".to_string(), Site::RoyalRoad);
+        assert!(ensure_series_url(&mirror, Some(&extra)).is_err());
+    }
+
+    #[test]
+    fn inline_parse_chapter_page_story_breadcrumb() -> Result<(), ScraperError> {
+        let html = r#"<html><body><div class="wi_breadcrumb chapter">
+<a href="https://www.scribblehub.com/">Home</a>
+<a href="https://www.scribblehub.com/series/862913/hp-the-arcane-thief-litrpg/">HP: The Arcane Thief</a>
+</div></body></html>"#;
+        let (title, author) = parse_chapter_page_story(html)?;
+        assert_eq!(title.as_deref(), Some("HP: The Arcane Thief"));
+        assert_eq!(author, UNKNOWN_AUTHOR);
+        Ok(())
+    }
+
+    #[test]
+    fn inline_parse_reported_word_count() -> Result<(), ScraperError> {
+        let html = r#"<html><body><div class="chapter-title">Ch 1</div>
+<span class="chp_wordcount">1,234 words</span>
+<div id="chp_raw" class="chp_raw"><p>Content.</p></div></body></html>"#;
+        assert_eq!(parse_reported_word_count(html)?, Some(1234));
+        let none =
+            r#"<html><body><div id="chp_raw" class="chp_raw"><p>Content.</p></div></body></html>"#;
+        assert_eq!(parse_reported_word_count(none)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn word_count_diverges_only_on_large_gap() {
+        assert!(!word_count_diverges(1000, 990));
+        assert!(!word_count_diverges(100, 60));
+        assert!(word_count_diverges(1000, 500));
+        assert!(word_count_diverges(2000, 2600));
+    }
+
+    #[test]
+    fn paginate_toc_mode_does_not_request_ajax() -> Result<(), ScraperError> {
+        let html = r#"<html><body>
+<ol class="toc_ol">
+<li class="toc_w" order="2"><a class="toc_a" href="/read/123/slug/chapter/2/">Two</a></li>
+<li class="toc_w" order="1"><a class="toc_a" href="/read/123/slug/chapter/1/">One</a></li>
+</ol>
+</body></html>"#;
+        let mut client = PoliteClient::new().expect("client");
+        let entries = fetch_full_toc(
+            &mut client,
+            "https://www.scribblehub.com/series/123/slug/",
+            html,
+            ScribbleHubTocMode::Paginate,
+        )?;
+        let titles: Vec<&str> = entries.iter().map(|(_, _, t)| t.as_str()).collect();
+        assert_eq!(titles, vec!["One", "Two"]);
+        assert!(client.last_request_at().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn ajax_toc_mode_without_series_id_fails_without_request() {
+        let mut client = PoliteClient::new().expect("client");
+        let result = fetch_full_toc(
+            &mut client,
+            "https://www.scribblehub.com/read/123/slug/",
+            "<html></html>",
+            ScribbleHubTocMode::Ajax,
+        );
+        assert!(matches!(result, Err(ScraperError::ChapterListParse { .. })));
+        assert!(client.last_request_at().is_none());
+    }
 }